@@ -0,0 +1,166 @@
+#ifndef CAIT_SITH_KEPLR_H
+#define CAIT_SITH_KEPLR_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// What to do after poking a session.
+typedef enum CskActionKind {
+  // Wait for more messages.
+  CSK_ACTION_KIND_WAIT = 0,
+  // Send `data` to every other participant.
+  CSK_ACTION_KIND_SEND_MANY = 1,
+  // Send `data` to `to` only, over a private channel.
+  CSK_ACTION_KIND_SEND_PRIVATE = 2,
+  // The session is done, and `csk_session_output` returns its output.
+  CSK_ACTION_KIND_RETURN = 3,
+} CskActionKind;
+
+// The result of a call.
+typedef enum CskStatus {
+  CSK_STATUS_OK = 0,
+  // A required pointer was null.
+  CSK_STATUS_NULL_POINTER = 1,
+  // The config was not valid UTF-8 or JSON, or did not suit the flow.
+  CSK_STATUS_INVALID_CONFIG = 2,
+  // An input could not be decoded.
+  CSK_STATUS_DECODE_ERROR = 3,
+  // The protocol failed.
+  CSK_STATUS_PROTOCOL_ERROR = 4,
+  // Another party sent a bad share, and should be considered malicious.
+  CSK_STATUS_BAD_SHARE = 5,
+  // The session has not produced its output yet.
+  CSK_STATUS_NOT_FINISHED = 6,
+  // The session failed earlier, and can't be used anymore.
+  CSK_STATUS_POISONED = 7,
+  // An internal error, which should never happen.
+  CSK_STATUS_PANIC = 8,
+  // Another party sent a proof which failed to verify, and should be considered malicious.
+  CSK_STATUS_BAD_PROOF = 9,
+  // A message from another party never arrived.
+  CSK_STATUS_MISSING_MESSAGE = 10,
+  // A step was run before the steps it depends on.
+  CSK_STATUS_OUT_OF_ORDER = 11,
+} CskStatus;
+
+// A session running one flow for one party.
+typedef struct CskSession CskSession;
+
+// A buffer owned by the caller, to be released with `csk_buffer_free`.
+typedef struct CskBuffer {
+  uint8_t *data;
+  uintptr_t len;
+} CskBuffer;
+
+// The action produced by `csk_session_poke`.
+typedef struct CskAction {
+  enum CskActionKind kind;
+  // The recipient, for `CSK_ACTION_KIND_SEND_PRIVATE`.
+  uint32_t to;
+  // The message, for `CSK_ACTION_KIND_SEND_MANY` and `CSK_ACTION_KIND_SEND_PRIVATE`.
+  struct CskBuffer data;
+} CskAction;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Start key generation.
+//
+// # Safety
+//
+// `config` must be a NUL terminated string, and `out` a valid pointer.
+enum CskStatus csk_keygen_new(const char *config, struct CskSession **out);
+
+// Start generating the two triples a presignature uses.
+//
+// # Safety
+//
+// `config` must be a NUL terminated string, and `out` a valid pointer.
+enum CskStatus csk_triples_new(const char *config, struct CskSession **out);
+
+// Start presigning with the outputs of a triples and a keygen session.
+//
+// # Safety
+//
+// `config` must be a NUL terminated string, the buffers valid for their
+// lengths, and `out` a valid pointer.
+enum CskStatus csk_presign_new(const char *config,
+                               const uint8_t *triples,
+                               uintptr_t triples_len,
+                               const uint8_t *keygen,
+                               uintptr_t keygen_len,
+                               struct CskSession **out);
+
+// Start signing the 32 byte `msg_hash` with the output of a presign session.
+//
+// # Safety
+//
+// `config` must be a NUL terminated string, `msg_hash` point to 32 bytes,
+// `presign` be valid for `presign_len` bytes, and `out` a valid pointer.
+enum CskStatus csk_sign_new(const char *config,
+                            const uint8_t *msg_hash,
+                            const uint8_t *presign,
+                            uintptr_t presign_len,
+                            struct CskSession **out);
+
+// Feed in a message sent by participant `from`.
+//
+// # Safety
+//
+// `session` must come from one of the `_new` functions, and `data` be valid
+// for `len` bytes.
+enum CskStatus csk_session_message(struct CskSession *session,
+                                   uint32_t from,
+                                   const uint8_t *data,
+                                   uintptr_t len);
+
+// Advance the session, storing what to do next in `action`.
+//
+// # Safety
+//
+// `session` must come from one of the `_new` functions, and `action` be a
+// valid pointer.
+enum CskStatus csk_session_poke(struct CskSession *session, struct CskAction *action);
+
+// Copy the output of a finished session into `out`.
+//
+// # Safety
+//
+// `session` must come from one of the `_new` functions, and `out` be a valid
+// pointer.
+enum CskStatus csk_session_output(struct CskSession *session, struct CskBuffer *out);
+
+// Copy the message of the last error into `out`, as UTF-8 without a NUL terminator.
+//
+// `out` is left empty if no call on this session has failed.
+//
+// # Safety
+//
+// `session` must come from one of the `_new` functions, and `out` be a valid
+// pointer.
+enum CskStatus csk_session_last_error(const struct CskSession *session, struct CskBuffer *out);
+
+// Free a session. Passing null is allowed, and does nothing.
+//
+// # Safety
+//
+// `session` must come from one of the `_new` functions, and not be used afterwards.
+void csk_session_free(struct CskSession *session);
+
+// Zero and free a buffer. Passing an empty buffer is allowed, and does nothing.
+//
+// # Safety
+//
+// `buffer` must have been handed out by this library, and not be used afterwards.
+void csk_buffer_free(struct CskBuffer buffer);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CAIT_SITH_KEPLR_H */
@@ -0,0 +1,104 @@
+//! Encodings of threshold public keys, and the addresses derived from them.
+use elliptic_curve::sec1::{ModulusSize, ToEncodedPoint};
+use elliptic_curve::FieldBytesSize;
+
+use crate::compat::CSCurve;
+use crate::keyshare::KeygenOutput;
+
+/// The SEC1 encoding of a point, compressed to 33 bytes or uncompressed to 65 bytes.
+pub fn sec1_bytes<C: CSCurve>(point: &C::AffinePoint, compress: bool) -> Vec<u8>
+where
+    C::AffinePoint: ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    point.to_encoded_point(compress).as_bytes().to_vec()
+}
+
+impl<C: CSCurve> KeygenOutput<C>
+where
+    C::AffinePoint: ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    /// The compressed SEC1 encoding of the public key.
+    pub fn public_key_compressed(&self) -> Vec<u8> {
+        sec1_bytes::<C>(&self.public_key, true)
+    }
+
+    /// The uncompressed SEC1 encoding of the public key.
+    pub fn public_key_uncompressed(&self) -> Vec<u8> {
+        sec1_bytes::<C>(&self.public_key, false)
+    }
+}
+
+#[cfg(feature = "k256")]
+pub use self::secp256k1::*;
+
+#[cfg(feature = "k256")]
+mod secp256k1 {
+    use bech32::{Bech32, Hrp};
+    use k256::{AffinePoint, Secp256k1};
+    use ripemd::{Digest, Ripemd160};
+
+    use super::sec1_bytes;
+    use crate::keyshare::KeygenOutput;
+    use crate::payload::{cosmos::sha256, eth::keccak256};
+    use crate::protocol::ProtocolError;
+
+    /// The 20 byte Ethereum address of a public key.
+    pub fn eth_address_bytes(public_key: &AffinePoint) -> [u8; 20] {
+        // The hash skips the 0x04 tag of the uncompressed encoding.
+        let hash = keccak256(&sec1_bytes::<Secp256k1>(public_key, false)[1..]);
+        let mut out = [0u8; 20];
+        out.copy_from_slice(&hash[12..]);
+        out
+    }
+
+    /// The `0x` prefixed, EIP-55 checksummed form of an Ethereum address.
+    pub fn eip55_checksum(address: &[u8; 20]) -> String {
+        let lower: String = address.iter().map(|b| format!("{b:02x}")).collect();
+        let hash = keccak256(lower.as_bytes());
+
+        let mut out = String::from("0x");
+        for (i, c) in lower.chars().enumerate() {
+            // A letter is uppercased when the matching nibble of the hash is at least 8.
+            let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0xf;
+            if nibble >= 8 {
+                out.push(c.to_ascii_uppercase());
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    /// The checksummed Ethereum address of a public key.
+    pub fn eth_address(public_key: &AffinePoint) -> String {
+        eip55_checksum(&eth_address_bytes(public_key))
+    }
+
+    /// The 20 byte Cosmos account address, `RIPEMD160(SHA256(compressed key))`.
+    pub fn cosmos_address_bytes(public_key: &AffinePoint) -> [u8; 20] {
+        let hash = sha256(&sec1_bytes::<Secp256k1>(public_key, true));
+        Ripemd160::digest(hash).into()
+    }
+
+    /// The bech32 Cosmos address of a public key, for a chain prefix such as `cosmos` or `osmo`.
+    pub fn cosmos_address(public_key: &AffinePoint, hrp: &str) -> Result<String, ProtocolError> {
+        let hrp = Hrp::parse(hrp)
+            .map_err(|err| ProtocolError::Other(format!("invalid prefix: {err}").into()))?;
+        bech32::encode::<Bech32>(hrp, &cosmos_address_bytes(public_key))
+            .map_err(|err| ProtocolError::Other(err.to_string().into()))
+    }
+
+    impl KeygenOutput<Secp256k1> {
+        /// The checksummed Ethereum address of the public key.
+        pub fn eth_address(&self) -> String {
+            eth_address(&self.public_key)
+        }
+
+        /// The bech32 Cosmos address of the public key, with prefix `hrp`.
+        pub fn cosmos_address(&self, hrp: &str) -> Result<String, ProtocolError> {
+            cosmos_address(&self.public_key, hrp)
+        }
+    }
+}
@@ -67,6 +67,43 @@ pub mod k256_impl {
     }
 }
 
+#[cfg(any(feature = "p256", test))]
+pub mod p256_impl {
+    use super::*;
+    use elliptic_curve::bigint::{Bounded, U256};
+    use p256::{NistP256, Scalar};
+
+    impl CSCurve for NistP256 {
+        const NAME: &'static [u8] = b"NistP256";
+        const BITS: usize = <Self::Uint as Bounded>::BITS;
+
+        fn serialize_point<S: Serializer>(
+            point: &Self::AffinePoint,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            point.serialize(serializer)
+        }
+
+        fn deserialize_point<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Self::AffinePoint, D::Error> {
+            Self::AffinePoint::deserialize(deserializer)
+        }
+
+        fn sample_scalar_constant_time<R: CryptoRngCore>(r: &mut R) -> Self::Scalar {
+            // p256 only reduces 256 bit integers, so we split 512 bits of
+            // randomness into hi || lo, and compute hi * 2^256 + lo mod n.
+            let mut hi = [0u8; 32];
+            let mut lo = [0u8; 32];
+            r.fill_bytes(&mut hi);
+            r.fill_bytes(&mut lo);
+            let two_pow_256 = <Scalar as Reduce<U256>>::reduce(U256::MAX) + Scalar::ONE;
+            <Scalar as Reduce<U256>>::reduce_bytes(&hi.into()) * two_pow_256
+                + <Scalar as Reduce<U256>>::reduce_bytes(&lo.into())
+        }
+    }
+}
+
 #[cfg(feature = "k256")]
 pub fn scalar_hash(msg: &[u8]) -> k256::Scalar {
     use digest::{Digest, FixedOutput};
@@ -80,6 +117,20 @@ pub fn scalar_hash(msg: &[u8]) -> k256::Scalar {
     <Scalar as Reduce<<Secp256k1 as Curve>::Uint>>::reduce_bytes(&m_bytes)
 }
 
+/// The equivalent of [`scalar_hash`] for P-256, using SHA-256.
+#[cfg(any(feature = "p256", test))]
+pub fn scalar_hash_p256(msg: &[u8]) -> p256::Scalar {
+    use digest::{Digest, FixedOutput};
+    use ecdsa::hazmat::DigestPrimitive;
+    use elliptic_curve::ops::Reduce;
+    use p256::{FieldBytes, NistP256, Scalar};
+
+    let digest = <NistP256 as DigestPrimitive>::Digest::new_with_prefix(msg);
+    let m_bytes: FieldBytes = digest.finalize_fixed();
+
+    <Scalar as Reduce<<NistP256 as Curve>::Uint>>::reduce_bytes(&m_bytes)
+}
+
 pub fn x_coordinate<C: CSCurve>(point: &C::AffinePoint) -> C::Scalar {
     <C::Scalar as Reduce<<C as Curve>::Uint>>::reduce_bytes(&point.x())
 }
@@ -0,0 +1,518 @@
+//! A C ABI over the [`protocols`](crate::tecdsa_cli_srv::protocols) adapters, over secp256k1.
+//!
+//! A session is an opaque handle running one flow for one party. It is created
+//! with `csk_keygen_new`, `csk_triples_new`, `csk_presign_new` or `csk_sign_new`,
+//! then driven like any [`Protocol`]: messages from the other parties are fed in
+//! with `csk_session_message`, and `csk_session_poke` is called until it returns
+//! a wait action, sending along any messages it produces. Once it returns
+//! `CSK_ACTION_KIND_RETURN`, the output can be read with `csk_session_output`.
+//!
+//! The config is a NUL terminated JSON string, e.g.
+//! `{"participants":[0,1],"me":0,"threshold":2,"session_id":"...","key_id":"..."}`,
+//! where `key_id` is only required to presign and sign. Messages are sealed in an
+//! [`Envelope`](crate::tecdsa_cli_srv::envelope::Envelope), so those of another
+//! session are rejected. The outputs of keygen, triples and presign are the
+//! msgpack encodings the Rust API uses, to be passed back in to the later flows.
+//! The output of signing is the 65 byte `r || s || v` encoding, with `v` being
+//! 27 or 28.
+//!
+//! Every function returns a [`CskStatus`]. On failure, `csk_session_last_error`
+//! describes what went wrong. Apart from a null pointer, or asking for the output
+//! too early, a failure poisons the session, so later calls on it fail with
+//! `CSK_STATUS_POISONED`. Buffers handed out are owned by the caller, and must be
+//! released with `csk_buffer_free`, which zeroes them first as outputs hold key
+//! shares, and sessions with `csk_session_free`.
+//!
+//! The header is generated with `cbindgen --config cbindgen.toml --output include/cait_sith_keplr.h`.
+use std::ffi::{c_char, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::{ptr, slice};
+
+use k256::Secp256k1;
+use serde::de::DeserializeOwned;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::protocol::{Action, MessageData, Participant, Protocol, ProtocolError};
+use crate::tecdsa::triples_2::TriplesGenManyResult;
+use crate::tecdsa_cli_srv::bindings::{self, BindingError, ErrorKind};
+use crate::tecdsa_cli_srv::protocols;
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::{KeygenOutput, PresignOutput};
+
+/// The result of a call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CskStatus {
+    Ok = 0,
+    /// A required pointer was null.
+    NullPointer = 1,
+    /// The config was not valid UTF-8 or JSON, or did not suit the flow.
+    InvalidConfig = 2,
+    /// An input could not be decoded.
+    DecodeError = 3,
+    /// The protocol failed.
+    ProtocolError = 4,
+    /// Another party sent a bad share, and should be considered malicious.
+    BadShare = 5,
+    /// The session has not produced its output yet.
+    NotFinished = 6,
+    /// The session failed earlier, and can't be used anymore.
+    Poisoned = 7,
+    /// An internal error, which should never happen.
+    Panic = 8,
+    /// Another party sent a proof which failed to verify, and should be considered malicious.
+    BadProof = 9,
+    /// A message from another party never arrived.
+    MissingMessage = 10,
+    /// A step was run before the steps it depends on.
+    OutOfOrder = 11,
+}
+
+/// What to do after poking a session.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CskActionKind {
+    /// Wait for more messages.
+    Wait = 0,
+    /// Send `data` to every other participant.
+    SendMany = 1,
+    /// Send `data` to `to` only, over a private channel.
+    SendPrivate = 2,
+    /// The session is done, and `csk_session_output` returns its output.
+    Return = 3,
+}
+
+/// A buffer owned by the caller, to be released with `csk_buffer_free`.
+#[repr(C)]
+pub struct CskBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl CskBuffer {
+    fn empty() -> Self {
+        CskBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    fn from_vec(v: Vec<u8>) -> Self {
+        let len = v.len();
+        let data = Box::into_raw(v.into_boxed_slice()) as *mut u8;
+        CskBuffer { data, len }
+    }
+}
+
+/// The action produced by `csk_session_poke`.
+#[repr(C)]
+pub struct CskAction {
+    pub kind: CskActionKind,
+    /// The recipient, for `CSK_ACTION_KIND_SEND_PRIVATE`.
+    pub to: u32,
+    /// The message, for `CSK_ACTION_KIND_SEND_MANY` and `CSK_ACTION_KIND_SEND_PRIVATE`.
+    pub data: CskBuffer,
+}
+
+/// A session running one flow for one party.
+pub struct CskSession {
+    protocol: Box<dyn Protocol<Output = Vec<u8>>>,
+    /// The encoded output, which holds a key share for all but signing.
+    output: Option<Zeroizing<Vec<u8>>>,
+    last_error: Option<String>,
+    poisoned: bool,
+}
+
+/// Wraps a protocol, encoding its output for the caller.
+struct EncodeOutput<P, F> {
+    protocol: P,
+    encode: F,
+}
+
+impl<P: Protocol, F: FnMut(P::Output) -> Result<Vec<u8>, ProtocolError>> Protocol
+    for EncodeOutput<P, F>
+{
+    type Output = Vec<u8>;
+
+    fn poke(&mut self) -> Result<Action<Self::Output>, ProtocolError> {
+        Ok(match self.protocol.poke()? {
+            Action::Wait => Action::Wait,
+            Action::SendMany(data) => Action::SendMany(data),
+            Action::SendPrivate(to, data) => Action::SendPrivate(to, data),
+            Action::Return(out) => Action::Return((self.encode)(out)?),
+        })
+    }
+
+    fn message(&mut self, from: Participant, data: MessageData) {
+        self.protocol.message(from, data);
+    }
+}
+
+/// A failure, with the status to report and the message for `csk_session_last_error`.
+struct Error(CskStatus, String);
+
+impl From<ErrorKind> for CskStatus {
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::Config => CskStatus::InvalidConfig,
+            ErrorKind::Decode => CskStatus::DecodeError,
+            ErrorKind::Protocol => CskStatus::ProtocolError,
+            ErrorKind::BadShare => CskStatus::BadShare,
+            ErrorKind::BadProof => CskStatus::BadProof,
+            ErrorKind::MissingMessage => CskStatus::MissingMessage,
+            ErrorKind::OutOfOrder => CskStatus::OutOfOrder,
+        }
+    }
+}
+
+impl From<BindingError> for Error {
+    fn from(e: BindingError) -> Self {
+        Error(e.kind().into(), e.to_string())
+    }
+}
+
+impl From<ProtocolError> for Error {
+    fn from(e: ProtocolError) -> Self {
+        Error(ErrorKind::from(&e).into(), e.to_string())
+    }
+}
+
+/// Run `f`, turning panics into [`CskStatus::Panic`], as they must not unwind into C.
+fn guard(f: impl FnOnce() -> CskStatus) -> CskStatus {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(CskStatus::Panic)
+}
+
+/// Borrow `len` bytes at `data`, which may only be null if `len` is 0.
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Result<&'a [u8], Error> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err(Error(CskStatus::NullPointer, "null buffer".to_string()));
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+unsafe fn parse_config(config: *const c_char) -> Result<SessionConfig, Error> {
+    if config.is_null() {
+        return Err(Error(CskStatus::NullPointer, "null config".to_string()));
+    }
+    let config = CStr::from_ptr(config)
+        .to_str()
+        .map_err(|e| Error(CskStatus::InvalidConfig, e.to_string()))?;
+    Ok(bindings::parse_session_config(config)?)
+}
+
+fn decode<T: DeserializeOwned>(what: &str, bytes: &[u8]) -> Result<T, Error> {
+    Ok(bindings::decode(what, bytes)?)
+}
+
+/// Create a session with the protocol built by `f`, storing it in `out`.
+///
+/// There's no session to hold the error on failure, so it is only reported by status.
+unsafe fn new_session(
+    out: *mut *mut CskSession,
+    f: impl FnOnce() -> Result<Box<dyn Protocol<Output = Vec<u8>>>, Error>,
+) -> CskStatus {
+    guard(|| {
+        if out.is_null() {
+            return CskStatus::NullPointer;
+        }
+        match f() {
+            Ok(protocol) => {
+                let session = CskSession {
+                    protocol,
+                    output: None,
+                    last_error: None,
+                    poisoned: false,
+                };
+                *out = Box::into_raw(Box::new(session));
+                CskStatus::Ok
+            }
+            Err(Error(status, _)) => status,
+        }
+    })
+}
+
+fn invalid_config(e: impl ToString) -> Error {
+    Error(CskStatus::InvalidConfig, e.to_string())
+}
+
+/// Start key generation.
+///
+/// # Safety
+///
+/// `config` must be a NUL terminated string, and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn csk_keygen_new(
+    config: *const c_char,
+    out: *mut *mut CskSession,
+) -> CskStatus {
+    new_session(out, || {
+        let config = parse_config(config)?;
+        let protocol = protocols::keygen::<Secp256k1>(config).map_err(invalid_config)?;
+        Ok(Box::new(EncodeOutput {
+            protocol,
+            encode: |out: KeygenOutput<Secp256k1>| Ok(crate::serde::encode(&out)),
+        }))
+    })
+}
+
+/// Start generating the two triples a presignature uses.
+///
+/// # Safety
+///
+/// `config` must be a NUL terminated string, and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn csk_triples_new(
+    config: *const c_char,
+    out: *mut *mut CskSession,
+) -> CskStatus {
+    new_session(out, || {
+        let config = parse_config(config)?;
+        let protocol = protocols::triples::<Secp256k1>(config).map_err(invalid_config)?;
+        Ok(Box::new(EncodeOutput {
+            protocol,
+            encode: |out: TriplesGenManyResult<Secp256k1>| Ok(crate::serde::encode(&out)),
+        }))
+    })
+}
+
+/// Start presigning with the outputs of a triples and a keygen session.
+///
+/// # Safety
+///
+/// `config` must be a NUL terminated string, the buffers valid for their
+/// lengths, and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn csk_presign_new(
+    config: *const c_char,
+    triples: *const u8,
+    triples_len: usize,
+    keygen: *const u8,
+    keygen_len: usize,
+    out: *mut *mut CskSession,
+) -> CskStatus {
+    new_session(out, || {
+        let config = parse_config(config)?;
+        let triples: TriplesGenManyResult<Secp256k1> =
+            decode("triples", bytes(triples, triples_len)?)?;
+        let keygen: KeygenOutput<Secp256k1> = decode("keygen", bytes(keygen, keygen_len)?)?;
+
+        let (Some(pub_0), Some(pub_1)) = (triples.pub_v.first(), triples.pub_v.get(1)) else {
+            return Err(Error(
+                CskStatus::DecodeError,
+                "triples: expected two triples".to_string(),
+            ));
+        };
+        let (Some(share_0), Some(share_1)) = (triples.share_v.first(), triples.share_v.get(1))
+        else {
+            return Err(Error(
+                CskStatus::DecodeError,
+                "triples: expected two shares".to_string(),
+            ));
+        };
+
+        let protocol = protocols::presign(
+            config,
+            pub_0.clone(),
+            pub_1.clone(),
+            share_0.clone(),
+            share_1.clone(),
+            keygen,
+        )
+        .map_err(invalid_config)?;
+        Ok(Box::new(EncodeOutput {
+            protocol,
+            encode: |out: PresignOutput<Secp256k1>| Ok(crate::serde::encode(&out)),
+        }))
+    })
+}
+
+/// Start signing the 32 byte `msg_hash` with the output of a presign session.
+///
+/// # Safety
+///
+/// `config` must be a NUL terminated string, `msg_hash` point to 32 bytes,
+/// `presign` be valid for `presign_len` bytes, and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn csk_sign_new(
+    config: *const c_char,
+    msg_hash: *const u8,
+    presign: *const u8,
+    presign_len: usize,
+    out: *mut *mut CskSession,
+) -> CskStatus {
+    new_session(out, || {
+        let config = parse_config(config)?;
+        if msg_hash.is_null() {
+            return Err(Error(CskStatus::NullPointer, "null msg_hash".to_string()));
+        }
+        let msg_hash = crate::payload::hash_to_scalar(&*(msg_hash as *const [u8; 32]));
+        let presign: PresignOutput<Secp256k1> = decode("presign", bytes(presign, presign_len)?)?;
+
+        let protocol = protocols::sign(config, presign, msg_hash).map_err(invalid_config)?;
+        Ok(Box::new(EncodeOutput {
+            protocol,
+            encode: |out: crate::sign::SignOutput<Secp256k1>| out.recoverable().to_bytes(),
+        }))
+    })
+}
+
+/// Run `f` against the session, recording any error it returns.
+unsafe fn with_session(
+    session: *mut CskSession,
+    f: impl FnOnce(&mut CskSession) -> Result<(), Error>,
+) -> CskStatus {
+    let Some(session) = session.as_mut() else {
+        return CskStatus::NullPointer;
+    };
+    if session.poisoned {
+        return CskStatus::Poisoned;
+    }
+    let status = guard(|| match f(session) {
+        Ok(()) => CskStatus::Ok,
+        Err(Error(status, message)) => {
+            session.last_error = Some(message);
+            status
+        }
+    });
+    if status == CskStatus::Panic {
+        session.last_error = Some("internal error".to_string());
+    }
+    // Only a missing pointer, or asking for the output too early, leaves the session usable.
+    if !matches!(
+        status,
+        CskStatus::Ok | CskStatus::NullPointer | CskStatus::NotFinished
+    ) {
+        session.poisoned = true;
+    }
+    status
+}
+
+/// Feed in a message sent by participant `from`.
+///
+/// # Safety
+///
+/// `session` must come from one of the `_new` functions, and `data` be valid
+/// for `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn csk_session_message(
+    session: *mut CskSession,
+    from: u32,
+    data: *const u8,
+    len: usize,
+) -> CskStatus {
+    with_session(session, |session| {
+        let data = bytes(data, len)?.to_vec();
+        session.protocol.message(Participant::from(from), data);
+        Ok(())
+    })
+}
+
+/// Advance the session, storing what to do next in `action`.
+///
+/// # Safety
+///
+/// `session` must come from one of the `_new` functions, and `action` be a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn csk_session_poke(
+    session: *mut CskSession,
+    action: *mut CskAction,
+) -> CskStatus {
+    if action.is_null() {
+        return CskStatus::NullPointer;
+    }
+    with_session(session, |session| {
+        let (kind, to, data) = match session.protocol.poke()? {
+            Action::Wait => (CskActionKind::Wait, 0, CskBuffer::empty()),
+            Action::SendMany(data) => (CskActionKind::SendMany, 0, CskBuffer::from_vec(data)),
+            Action::SendPrivate(to, data) => (
+                CskActionKind::SendPrivate,
+                u32::from(to),
+                CskBuffer::from_vec(data),
+            ),
+            Action::Return(out) => {
+                session.output = Some(Zeroizing::new(out));
+                (CskActionKind::Return, 0, CskBuffer::empty())
+            }
+        };
+        *action = CskAction { kind, to, data };
+        Ok(())
+    })
+}
+
+/// Copy the output of a finished session into `out`.
+///
+/// # Safety
+///
+/// `session` must come from one of the `_new` functions, and `out` be a valid
+/// pointer.
+#[no_mangle]
+pub unsafe extern "C" fn csk_session_output(
+    session: *mut CskSession,
+    out: *mut CskBuffer,
+) -> CskStatus {
+    if out.is_null() {
+        return CskStatus::NullPointer;
+    }
+    with_session(session, |session| {
+        let Some(output) = &session.output else {
+            return Err(Error(
+                CskStatus::NotFinished,
+                "the session has not returned yet".to_string(),
+            ));
+        };
+        *out = CskBuffer::from_vec(output.to_vec());
+        Ok(())
+    })
+}
+
+/// Copy the message of the last error into `out`, as UTF-8 without a NUL terminator.
+///
+/// `out` is left empty if no call on this session has failed.
+///
+/// # Safety
+///
+/// `session` must come from one of the `_new` functions, and `out` be a valid
+/// pointer.
+#[no_mangle]
+pub unsafe extern "C" fn csk_session_last_error(
+    session: *const CskSession,
+    out: *mut CskBuffer,
+) -> CskStatus {
+    let (Some(session), Some(out)) = (session.as_ref(), out.as_mut()) else {
+        return CskStatus::NullPointer;
+    };
+    *out = match &session.last_error {
+        Some(message) => CskBuffer::from_vec(message.clone().into_bytes()),
+        None => CskBuffer::empty(),
+    };
+    CskStatus::Ok
+}
+
+/// Free a session. Passing null is allowed, and does nothing.
+///
+/// # Safety
+///
+/// `session` must come from one of the `_new` functions, and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn csk_session_free(session: *mut CskSession) {
+    if !session.is_null() {
+        drop(Box::from_raw(session));
+    }
+}
+
+/// Zero and free a buffer. Passing an empty buffer is allowed, and does nothing.
+///
+/// # Safety
+///
+/// `buffer` must have been handed out by this library, and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn csk_buffer_free(buffer: CskBuffer) {
+    if !buffer.data.is_null() {
+        let mut data = Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len));
+        data.zeroize();
+    }
+}
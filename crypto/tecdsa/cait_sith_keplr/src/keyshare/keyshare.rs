@@ -1,8 +1,8 @@
 use elliptic_curve::{Field, Group, ScalarPrimitive};
 use magikitten::Transcript;
 use rand_core::OsRng;
-use serde::{Deserialize, Serialize};
 
+use super::{KeygenOutput, LABEL};
 use crate::compat::CSCurve;
 use crate::crypto::{commit, hash, Digest};
 use crate::math::{GroupPolynomial, Polynomial};
@@ -12,8 +12,6 @@ use crate::protocol::internal::{make_protocol, Context, SharedChannel};
 use crate::protocol::{InitializationError, Participant, Protocol, ProtocolError};
 use crate::serde::encode;
 
-const LABEL: &[u8] = b"cait-sith v0.8.0 keygen";
-
 async fn do_keyshare<C: CSCurve>(
     mut chan: SharedChannel,
     participants: ParticipantList,
@@ -21,7 +19,7 @@ async fn do_keyshare<C: CSCurve>(
     threshold: usize,
     s_i: C::Scalar,
     big_s: Option<C::ProjectivePoint>,
-) -> Result<(C::Scalar, C::AffinePoint), ProtocolError> {
+) -> Result<(C::Scalar, C::AffinePoint, GroupPolynomial<C>), ProtocolError> {
     let mut rng = OsRng;
     let mut transcript = Transcript::new(LABEL);
 
@@ -174,16 +172,7 @@ async fn do_keyshare<C: CSCurve>(
     };
 
     // Spec 3.9
-    Ok((x_i, big_x.into()))
-}
-
-/// Represents the output of the key generation protocol.
-///
-/// This contains our share of the private key, along with the public key.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct KeygenOutput<C: CSCurve> {
-    pub private_share: C::Scalar,
-    pub public_key: C::AffinePoint,
+    Ok((x_i, big_x.into(), big_f))
 }
 
 async fn do_keygen<C: CSCurve>(
@@ -193,12 +182,9 @@ async fn do_keygen<C: CSCurve>(
     threshold: usize,
 ) -> Result<KeygenOutput<C>, ProtocolError> {
     let s_i = C::Scalar::random(&mut OsRng);
-    let (private_share, public_key) =
+    let (private_share, public_key, big_f) =
         do_keyshare::<C>(chan, participants, me, threshold, s_i, None).await?;
-    Ok(KeygenOutput {
-        private_share,
-        public_key,
-    })
+    Ok(KeygenOutput::new(private_share, public_key, big_f))
 }
 
 /// The key generation protocol, with a given threshold.
@@ -255,7 +241,7 @@ async fn do_reshare<C: CSCurve>(
         .map(|x_i| old_subset.lagrange::<C>(me) * x_i)
         .unwrap_or(C::Scalar::ZERO);
     let big_s: C::ProjectivePoint = public_key.into();
-    let (private_share, _) =
+    let (private_share, _, _) =
         do_keyshare::<C>(chan, participants, me, threshold, s_i, Some(big_s)).await?;
     Ok(private_share)
 }
@@ -1,26 +1,109 @@
+#[cfg(feature = "async")]
+mod keyshare;
 mod state;
 mod state_2;
 pub mod steps;
 pub mod steps_2;
 
+#[cfg(feature = "async")]
+pub use keyshare::{keygen, refresh, reshare};
 pub use state::*;
 pub use state_2::*;
 
+use std::fmt;
+
+use elliptic_curve::group::Curve;
 use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::compat::CSCurve;
+use crate::math::GroupPolynomial;
+use crate::protocol::Participant;
 
 const LABEL: &[u8] = b"cait-sith v0.8.0 keygen";
 
+/// The current version of [`KeygenOutput`].
+///
+/// Version 1 outputs only contain the private share and the public key.
+pub const KEYGEN_OUTPUT_VERSION: u32 = 2;
+
+fn keygen_output_v1() -> u32 {
+    1
+}
+
 /// Represents the output of the key generation protocol.
 ///
 /// This contains our share of the private key, along with the public key.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The private share is wiped when the output is dropped, and left out of its
+/// `Debug` output.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "
+    C::Scalar: Serialize + for<'a> Deserialize<'a>,
+    C::AffinePoint: Serialize + for<'a> Deserialize<'a>
+")]
 pub struct KeygenOutput<C: CSCurve> {
     pub private_share: C::Scalar,
     pub public_key: C::AffinePoint,
+    /// Outputs serialized before the version was added read as version 1.
+    ///
+    /// New fields go after the existing ones, so that older encodings stay readable.
+    #[serde(default = "keygen_output_v1")]
+    pub version: u32,
+    /// The Feldman commitments to the polynomial sharing the private key.
+    ///
+    /// This is `None` for version 1 outputs.
+    #[serde(default)]
+    pub big_f: Option<GroupPolynomial<C>>,
 }
 
+impl<C: CSCurve> KeygenOutput<C> {
+    pub fn new(
+        private_share: C::Scalar,
+        public_key: C::AffinePoint,
+        big_f: GroupPolynomial<C>,
+    ) -> Self {
+        KeygenOutput {
+            private_share,
+            public_key,
+            version: KEYGEN_OUTPUT_VERSION,
+            big_f: Some(big_f),
+        }
+    }
+
+    /// The public share `x_j * G` of participant `p`, if the commitments are known.
+    pub fn public_share(&self, p: Participant) -> Option<C::AffinePoint> {
+        self.big_f
+            .as_ref()
+            .map(|big_f| big_f.evaluate(&p.scalar::<C>()).to_affine())
+    }
+}
+
+impl<C: CSCurve> fmt::Debug for KeygenOutput<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeygenOutput")
+            .field("private_share", &format_args!("<redacted>"))
+            .field("public_key", &self.public_key)
+            .field("version", &self.version)
+            .field("big_f", &self.big_f)
+            .finish()
+    }
+}
+
+impl<C: CSCurve> Zeroize for KeygenOutput<C> {
+    fn zeroize(&mut self) {
+        self.private_share.zeroize();
+    }
+}
+
+impl<C: CSCurve> Drop for KeygenOutput<C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<C: CSCurve> ZeroizeOnDrop for KeygenOutput<C> {}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound = "
     C::Scalar: Serialize + for<'a> Deserialize<'a>,
@@ -3,6 +3,8 @@ use magikitten::Transcript;
 use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::crypto::Commitment;
 use crate::crypto::Digest;
@@ -67,7 +69,7 @@ impl<'a, C: CSCurve> KeyshareState<'a, C> {
 }
 
 type From = Participant;
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 #[serde(bound = "
     C::Scalar: Serialize + for<'a> Deserialize<'a>,
     C::AffinePoint: Serialize + for<'a> Deserialize<'a>,
@@ -94,4 +96,44 @@ impl<C: CSCurve> RcvdKeyshareMessages<C> {
             public_key: None,
         }
     }
+
+    /// Merge messages received from another participant into this buffer.
+    pub fn extend(&mut self, mut other: RcvdKeyshareMessages<C>) {
+        self.wait_0.extend(std::mem::take(&mut other.wait_0));
+        self.wait_1.extend(std::mem::take(&mut other.wait_1));
+        self.wait_2.extend(std::mem::take(&mut other.wait_2));
+        self.wait_3.extend(std::mem::take(&mut other.wait_3));
+        if other.public_key.is_some() {
+            self.public_key = other.public_key;
+        }
+    }
+}
+
+/// The shares in `wait_3` are secret, so they are left out.
+impl<C: CSCurve> fmt::Debug for RcvdKeyshareMessages<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RcvdKeyshareMessages")
+            .field("wait_0", &self.wait_0)
+            .field("wait_1", &self.wait_1)
+            .field("wait_2", &self.wait_2)
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: CSCurve> Zeroize for RcvdKeyshareMessages<C> {
+    fn zeroize(&mut self) {
+        for share in self.wait_3.values_mut() {
+            share.zeroize();
+        }
+        self.wait_3.clear();
+    }
 }
+
+impl<C: CSCurve> Drop for RcvdKeyshareMessages<C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<C: CSCurve> ZeroizeOnDrop for RcvdKeyshareMessages<C> {}
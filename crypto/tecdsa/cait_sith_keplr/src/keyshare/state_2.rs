@@ -1,6 +1,8 @@
 use elliptic_curve::ScalarPrimitive;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use super::steps_2::KeyshareTranscriptKeys;
 use crate::crypto::Commitment;
@@ -19,13 +21,19 @@ pub struct TranscriptFeed {
     confirmation: Vec<u8>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+/// The state of a participant between the steps of key generation.
+///
+/// The coefficients of the polynomial and the private share are wiped when the
+/// state is dropped, and never show up in `Debug` output.
+#[derive(Deserialize, Serialize, Clone)]
 #[serde(bound = "
     C::Scalar: Serialize + for<'a> Deserialize<'a>
 ")]
 pub struct KeyshareState2<C: CSCurve> {
     pub participants: Vec<Participant>,
     pub threshold: usize,
+    /// Binds the transcript and commitments to a single session.
+    pub session_id: Vec<u8>,
     pub transcript_feed: BTreeMap<KeyshareTranscriptKeys, Vec<u8>>,
 
     pub f_coefficients: Option<Vec<ScalarPrimitive<C>>>,
@@ -42,12 +50,17 @@ pub struct KeyshareState2<C: CSCurve> {
 }
 
 impl<'a, C: CSCurve> KeyshareState2<C> {
-    pub fn new(participants: Vec<Participant>, threshold: usize) -> KeyshareState2<C> {
+    pub fn new(
+        participants: Vec<Participant>,
+        threshold: usize,
+        session_id: Vec<u8>,
+    ) -> KeyshareState2<C> {
         let transcript_feed = BTreeMap::new();
 
         KeyshareState2 {
             participants,
             threshold,
+            session_id,
             transcript_feed,
             f_coefficients: None,
             big_f: None,
@@ -59,3 +72,31 @@ impl<'a, C: CSCurve> KeyshareState2<C> {
         }
     }
 }
+
+impl<C: CSCurve> fmt::Debug for KeyshareState2<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyshareState2")
+            .field("participants", &self.participants)
+            .field("threshold", &self.threshold)
+            .field("session_id", &self.session_id)
+            .field("big_f", &self.big_f)
+            .field("commitment", &self.commitment)
+            .field("confirmation", &self.confirmation)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: CSCurve> Zeroize for KeyshareState2<C> {
+    fn zeroize(&mut self) {
+        self.f_coefficients.zeroize();
+        self.x_i.zeroize();
+    }
+}
+
+impl<C: CSCurve> Drop for KeyshareState2<C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<C: CSCurve> ZeroizeOnDrop for KeyshareState2<C> {}
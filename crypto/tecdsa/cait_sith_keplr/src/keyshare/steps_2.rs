@@ -3,6 +3,7 @@ use magikitten::Transcript;
 use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
+use zeroize::Zeroize;
 
 use super::steps::Step4Output;
 use super::{KeyshareState2, RcvdKeyshareMessages, LABEL};
@@ -15,6 +16,7 @@ use crate::protocol::{Participant, ProtocolError};
 use crate::serde::encode;
 
 // transcript labels, order matters!
+const SESSION_ID: &[u8] = b"session id";
 const GROUP: &'static [u8] = b"group";
 const PARTICIPANTS: &[u8] = b"participants";
 const THRESHOLD: &[u8] = b"participants";
@@ -29,6 +31,7 @@ pub enum KeyshareTranscriptKeys {
     Threshold,
     Confirmation,
     DLog0,
+    SessionId,
 }
 
 pub struct Step2Output {
@@ -43,24 +46,85 @@ pub struct WaitPoint3 {}
 
 pub struct WaitPoint4 {}
 
-pub fn step_1<C: CSCurve>(
+pub fn step_1<C: CSCurve>(state: &mut KeyshareState2<C>) -> Result<(), ProtocolError> {
+    let s_i = C::Scalar::random(OsRng);
+
+    share_secret(state, s_i)
+}
+
+/// The first step of refreshing an existing key.
+///
+/// Instead of a random secret, each participant shares its Lagrange weighted
+/// share of the existing private key, so that the new shares add up to the same key.
+/// The remaining steps are the same as for key generation, with the old public key
+/// passed to [`step_5`] as `big_s`.
+pub fn refresh_step_1<C: CSCurve>(
     state: &mut KeyshareState2<C>,
-    _big_s: Option<C::ProjectivePoint>,
+    me: Participant,
+    my_share: C::Scalar,
 ) -> Result<(), ProtocolError> {
-    let mut rng = OsRng;
+    let participant_list = ParticipantList::from_state(&state.participants)?;
+
+    reshare_step_1(state, &participant_list, me, Some(my_share))
+}
+
+/// The first step of resharing an existing key to the participants of `state`.
+///
+/// `old_subset` are the old participants which are also part of the new set,
+/// and must be enough to reconstruct the key. Participants outside of it have
+/// no share, and deal a sharing of zero, receiving fresh shares in the end.
+pub fn reshare_step_1<C: CSCurve>(
+    state: &mut KeyshareState2<C>,
+    old_subset: &ParticipantList,
+    me: Participant,
+    my_share: Option<C::Scalar>,
+) -> Result<(), ProtocolError> {
+    let s_i = match my_share {
+        Some(x_i) if old_subset.contains(me) => old_subset.lagrange::<C>(me) * x_i,
+        None if old_subset.contains(me) => {
+            return Err(ProtocolError::KeyMismatch(
+                "this party is present in the old participant list but provided no share",
+            ))
+        }
+        _ => C::Scalar::ZERO,
+    };
+
+    share_secret(state, s_i)
+}
+
+/// The first step of importing an existing private key.
+///
+/// Only `importer` knows the key, and shares it as its secret, while the other
+/// participants deal a sharing of zero. The remaining steps are the same as for
+/// key generation, with the key's public key passed to [`step_5`] as `big_s`.
+pub fn import_step_1<C: CSCurve>(
+    state: &mut KeyshareState2<C>,
+    me: Participant,
+    importer: Participant,
+    secret: Option<C::Scalar>,
+) -> Result<(), ProtocolError> {
+    if me != importer && secret.is_some() {
+        return Err(ProtocolError::KeyMismatch(
+            "only the importing participant can provide a secret",
+        ));
+    }
 
-    let participant_list = ParticipantList::new(&state.participants)
-        .ok_or_else(|| {
-            ProtocolError::Other(
-                "participant list cannot contain duplicates"
-                    .to_string()
-                    .into(),
-            )
-        })
-        .unwrap();
+    let importer = ParticipantList::from_state(&[importer])?;
 
-    let s_i = C::Scalar::random(rng);
+    reshare_step_1(state, &importer, me, secret)
+}
+
+fn share_secret<C: CSCurve>(
+    state: &mut KeyshareState2<C>,
+    s_i: C::Scalar,
+) -> Result<(), ProtocolError> {
+    let mut rng = OsRng;
+
+    let participant_list = ParticipantList::from_state(&state.participants)?;
 
+    state
+        .transcript_feed
+        .insert(KeyshareTranscriptKeys::SessionId, state.session_id.clone());
     state
         .transcript_feed
         .insert(KeyshareTranscriptKeys::Group, C::NAME.to_vec());
@@ -70,10 +134,7 @@ pub fn step_1<C: CSCurve>(
     );
     state.transcript_feed.insert(
         KeyshareTranscriptKeys::Threshold,
-        u64::try_from(state.threshold)
-            .unwrap()
-            .to_be_bytes()
-            .to_vec(),
+        (state.threshold as u64).to_be_bytes().to_vec(),
     );
 
     // Spec 1.3
@@ -83,7 +144,7 @@ pub fn step_1<C: CSCurve>(
     let big_f = f.commit();
 
     // // Spec 1.5
-    let (my_commitment, my_randomizer) = commit(&mut rng, &big_f);
+    let (my_commitment, my_randomizer) = commit(&mut rng, &(&state.session_id, &big_f));
 
     let f_coefficients: Vec<ScalarPrimitive<C>> =
         f.coefficients.iter().map(|c| (*c).into()).collect();
@@ -101,22 +162,23 @@ pub fn step_2<C: CSCurve>(
     msgs: &RcvdKeyshareMessages<C>,
     me: Participant,
 ) -> Result<(), ProtocolError> {
-    let participant_list = ParticipantList::new(&state.participants)
-        .ok_or_else(|| {
-            ProtocolError::Other(
-                "participant list cannot contain duplicates"
-                    .to_string()
-                    .into(),
-            )
-        })
-        .unwrap();
+    let participant_list = ParticipantList::from_state(&state.participants)?;
 
     let mut all_commitments: ParticipantMap<'_, Commitment> =
         ParticipantMap::new(&participant_list);
 
-    all_commitments.put(me, state.commitment.unwrap());
-    for (p, commitment) in &msgs.wait_0 {
-        all_commitments.put(*p, *commitment);
+    all_commitments.put(
+        me,
+        state
+            .commitment
+            .ok_or(ProtocolError::OutOfOrder("commitment"))?,
+    );
+    for p in participant_list.others(me) {
+        let commitment = msgs.wait_0.get(&p).ok_or(ProtocolError::MissingMessage {
+            from: p,
+            wait: "wait_0",
+        })?;
+        all_commitments.put(p, *commitment);
     }
 
     let my_confirmation = hash(&all_commitments);
@@ -135,11 +197,19 @@ pub fn step_3<C: CSCurve>(
     state: &mut KeyshareState2<C>,
     me: Participant,
 ) -> Result<(), ProtocolError> {
-    let f_coefficients = state.f_coefficients.clone().unwrap();
-    let coefficients: Vec<C::Scalar> = f_coefficients.iter().map(|c| (*c).into()).collect();
+    let coefficients: Vec<C::Scalar> = state
+        .f_coefficients
+        .as_ref()
+        .ok_or(ProtocolError::OutOfOrder("f_coefficients"))?
+        .iter()
+        .map(|c| (*c).into())
+        .collect();
     let f: Polynomial<C> = Polynomial { coefficients };
 
-    let big_f = state.big_f.clone().unwrap();
+    let big_f = state
+        .big_f
+        .clone()
+        .ok_or(ProtocolError::OutOfOrder("big_f"))?;
 
     // // Spec 2.5
     let statement = dlog::Statement::<C> {
@@ -150,7 +220,7 @@ pub fn step_3<C: CSCurve>(
         x: &f.evaluate_zero(),
     };
 
-    let transcript = make_transcript(state.transcript_feed.clone());
+    let transcript = make_transcript(&state.transcript_feed)?;
 
     let my_phi_proof = dlog::prove(
         &mut OsRng,
@@ -168,23 +238,20 @@ pub fn step_4<C: CSCurve>(
     state: &mut KeyshareState2<C>,
     me: Participant,
 ) -> Result<Step4Output<C>, ProtocolError> {
-    let f_coefficients = state.f_coefficients.clone().unwrap();
+    // This is the last use of the polynomial, so it is wiped from the state.
+    let mut f_coefficients = state
+        .f_coefficients
+        .take()
+        .ok_or(ProtocolError::OutOfOrder("f_coefficients"))?;
     let coefficients: Vec<C::Scalar> = f_coefficients.iter().map(|c| (*c).into()).collect();
+    f_coefficients.zeroize();
     let f: Polynomial<C> = Polynomial { coefficients };
 
     let mut output = Step4Output {
         x_i_js: HashMap::new(),
     };
 
-    let participant_list = ParticipantList::new(&state.participants)
-        .ok_or_else(|| {
-            ProtocolError::Other(
-                "participant list cannot contain duplicates"
-                    .to_string()
-                    .into(),
-            )
-        })
-        .unwrap();
+    let participant_list = ParticipantList::from_state(&state.participants)?;
 
     for p in participant_list.others(me) {
         let x_i_j: ScalarPrimitive<C> = f.evaluate(&p.scalar::<C>()).into();
@@ -203,76 +270,98 @@ pub fn step_5<C: CSCurve>(
     me: Participant,
     big_s: Option<C::ProjectivePoint>,
 ) -> Result<(C::Scalar, C::AffinePoint), ProtocolError> {
-    let confirmation = state.confirmation.unwrap();
+    let confirmation = state
+        .confirmation
+        .ok_or(ProtocolError::OutOfOrder("confirmation"))?;
 
-    for (p, conf) in &msgs.wait_1 {
+    let participant_list = ParticipantList::from_state(&state.participants)?;
+
+    for p in participant_list.others(me) {
+        let conf = msgs.wait_1.get(&p).ok_or(ProtocolError::MissingMessage {
+            from: p,
+            wait: "wait_1",
+        })?;
         if confirmation != *conf {
-            return Err(ProtocolError::AssertionFailed(format!(
-                "confirmation from {p:?} did not match expectation"
-            )));
+            return Err(ProtocolError::BadShare(
+                p,
+                "confirmation did not match expectation".to_string(),
+            ));
         }
     }
 
-    let participant_list = ParticipantList::new(&state.participants)
-        .ok_or_else(|| {
-            ProtocolError::Other(
-                "participant list cannot contain duplicates"
-                    .to_string()
-                    .into(),
-            )
-        })
-        .unwrap();
-
     let mut all_commitments: ParticipantMap<'_, Commitment> =
         ParticipantMap::new(&participant_list);
 
-    all_commitments.put(me, state.commitment.unwrap());
-    for (p, commitment) in &msgs.wait_0 {
-        all_commitments.put(*p, *commitment);
+    all_commitments.put(
+        me,
+        state
+            .commitment
+            .ok_or(ProtocolError::OutOfOrder("commitment"))?,
+    );
+    for p in participant_list.others(me) {
+        let commitment = msgs.wait_0.get(&p).ok_or(ProtocolError::MissingMessage {
+            from: p,
+            wait: "wait_0",
+        })?;
+        all_commitments.put(p, *commitment);
     }
 
-    for (p, (their_big_f, their_randomizer, their_phi_proof)) in &msgs.wait_2 {
+    let mut big_f = state
+        .big_f
+        .clone()
+        .ok_or(ProtocolError::OutOfOrder("big_f"))?;
+    for p in participant_list.others(me) {
+        let (their_big_f, their_randomizer, their_phi_proof) =
+            msgs.wait_2.get(&p).ok_or(ProtocolError::MissingMessage {
+                from: p,
+                wait: "wait_2",
+            })?;
         if their_big_f.len() != state.threshold {
-            return Err(ProtocolError::AssertionFailed(format!(
-                "polynomial from {p:?} has the wrong length"
-            )));
+            return Err(ProtocolError::BadShare(
+                p,
+                "polynomial has the wrong length".to_string(),
+            ));
         }
 
-        if !all_commitments[*p].check(&their_big_f, &their_randomizer) {
-            return Err(ProtocolError::AssertionFailed(format!(
-                "commitment from {p:?} did not match revealed F"
-            )));
+        if !all_commitments[p].check(&(&state.session_id, their_big_f), their_randomizer) {
+            return Err(ProtocolError::BadShare(
+                p,
+                "commitment did not match revealed F".to_string(),
+            ));
         }
 
         let statement = dlog::Statement::<C> {
             public: &their_big_f.evaluate_zero(),
         };
 
-        let transcript = make_transcript(state.transcript_feed.clone());
+        let transcript = make_transcript(&state.transcript_feed)?;
 
         if !dlog::verify(
             &mut transcript.forked(DLOG0, &p.bytes()),
             statement,
             &their_phi_proof,
         ) {
-            return Err(ProtocolError::AssertionFailed(format!(
-                "dlog proof from {p:?} failed to verify"
-            )));
+            return Err(ProtocolError::BadProof(
+                p,
+                "dlog proof failed to verify".to_string(),
+            ));
         }
 
-        let mut big_f = state.big_f.clone().unwrap();
         big_f += &their_big_f;
-        state.big_f = Some(big_f);
     }
+    state.big_f = Some(big_f.clone());
 
-    let x_i = state.x_i.unwrap();
+    let x_i = state.x_i.ok_or(ProtocolError::OutOfOrder("x_i"))?;
     let mut x_i: C::Scalar = C::Scalar::from(x_i);
-    for (_p, x_j_i) in &msgs.wait_3 {
+    for p in participant_list.others(me) {
+        let x_j_i = msgs.wait_3.get(&p).ok_or(ProtocolError::MissingMessage {
+            from: p,
+            wait: "wait_3",
+        })?;
         x_i += C::Scalar::from(*x_j_i);
     }
     state.x_i = Some(x_i.into());
 
-    let big_f = state.big_f.clone().unwrap();
     // Spec 3.7
     if big_f.evaluate(&me.scalar::<C>()) != C::ProjectivePoint::generator() * x_i {
         return Err(ProtocolError::AssertionFailed(
@@ -295,18 +384,26 @@ pub fn step_5<C: CSCurve>(
     Ok((x_i, big_x.into()))
 }
 
-fn make_transcript(transcript_feed: BTreeMap<KeyshareTranscriptKeys, Vec<u8>>) -> Transcript {
+fn make_transcript(
+    transcript_feed: &BTreeMap<KeyshareTranscriptKeys, Vec<u8>>,
+) -> Result<Transcript, ProtocolError> {
+    let get = |key, what| {
+        transcript_feed
+            .get(&key)
+            .ok_or(ProtocolError::OutOfOrder(what))
+    };
+
     let mut transcript = Transcript::new(LABEL);
-    let group = transcript_feed.get(&KeyshareTranscriptKeys::Group).unwrap();
-    let participants = transcript_feed
-        .get(&KeyshareTranscriptKeys::Participants)
-        .unwrap();
-    let threshold = transcript_feed
-        .get(&KeyshareTranscriptKeys::Threshold)
-        .unwrap();
-    let confirmation = transcript_feed
-        .get(&KeyshareTranscriptKeys::Confirmation)
-        .unwrap();
+    let session_id = get(KeyshareTranscriptKeys::SessionId, "transcript session id")?;
+    let group = get(KeyshareTranscriptKeys::Group, "transcript group")?;
+    let participants = get(
+        KeyshareTranscriptKeys::Participants,
+        "transcript participants",
+    )?;
+    let threshold = get(KeyshareTranscriptKeys::Threshold, "transcript threshold")?;
+    let confirmation = get(KeyshareTranscriptKeys::Confirmation, "confirmation")?;
+
+    transcript.message(SESSION_ID, session_id);
 
     transcript.message(GROUP, group);
 
@@ -316,5 +413,5 @@ fn make_transcript(transcript_feed: BTreeMap<KeyshareTranscriptKeys, Vec<u8>>) -
 
     transcript.message(CONFIRMATION, confirmation);
 
-    transcript
+    Ok(transcript)
 }
@@ -55,6 +55,9 @@
 //! - Once they know this message, they can use the presignature to create a complete signature.
 //!
 //! It's important that presignatures and triples are **never** reused.
+//! The pools in [`tecdsa_cli_srv::pool`] hand each of them out at most once.
+//! Key shares, triple shares, presignatures and step states wipe their secrets
+//! when dropped, and leave them out of their `Debug` output.
 //!
 //! ## API Design
 //!
@@ -100,6 +103,7 @@
 //! | Curve | Feature |
 //! |-------|---------|
 //! |Secp256k1|`k256`|
+//! |NistP256|`p256`|
 //!
 //! For supporting any message hash, the API requires the user to supply
 //! the hash of a message when signing as a scalar directly.
@@ -126,16 +130,25 @@ mod serde;
 pub mod triples;
 
 // keplr
+pub mod address;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod keyshare;
+pub mod payload;
 pub mod presign;
 pub mod sign;
 pub mod tecdsa;
 pub mod tecdsa_cli_srv;
 #[cfg(test)]
 mod tests;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub use compat::CSCurve;
+#[cfg(feature = "k256")]
 pub use k256::Secp256k1;
 pub use keyshare::KeygenOutput;
+#[cfg(feature = "p256")]
+pub use p256::NistP256;
 pub use presign::PresignOutput;
 pub use sign::FullSignature;
@@ -1,8 +1,10 @@
+use std::fmt;
 use std::ops::{Add, AddAssign, Index, Mul, MulAssign};
 
 use elliptic_curve::{Field, Group};
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::{
     compat::CSCurve,
@@ -10,7 +12,10 @@ use crate::{
 };
 
 /// Represents a polynomial with coefficients in the scalar field of the curve.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// The coefficients are usually secret, so they are wiped when dropped, and
+/// left out of the `Debug` output.
+#[derive(Clone, PartialEq, Eq)]
 pub struct Polynomial<C: CSCurve> {
     /// The coefficients of our polynomial, from 0..size-1.
     pub coefficients: Vec<C::Scalar>,
@@ -106,6 +111,26 @@ impl<C: CSCurve> Polynomial<C> {
     }
 }
 
+impl<C: CSCurve> fmt::Debug for Polynomial<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Polynomial").finish_non_exhaustive()
+    }
+}
+
+impl<C: CSCurve> Zeroize for Polynomial<C> {
+    fn zeroize(&mut self) {
+        self.coefficients.zeroize();
+    }
+}
+
+impl<C: CSCurve> Drop for Polynomial<C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<C: CSCurve> ZeroizeOnDrop for Polynomial<C> {}
+
 impl<C: CSCurve> Index<usize> for Polynomial<C> {
     type Output = C::Scalar;
 
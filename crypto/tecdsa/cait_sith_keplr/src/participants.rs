@@ -9,7 +9,10 @@ use std::{collections::HashMap, mem, ops::Index};
 use elliptic_curve::Field;
 use serde::Serialize;
 
-use crate::{compat::CSCurve, protocol::Participant};
+use crate::{
+    compat::CSCurve,
+    protocol::{Participant, ProtocolError},
+};
 
 /// Represents a sorted list of participants.
 ///
@@ -51,6 +54,14 @@ impl ParticipantList {
         Self::new_vec(participants.to_owned())
     }
 
+    /// Like [`ParticipantList::new`], but for participants taken from the state
+    /// of a step, where duplicates mean the state is malformed.
+    pub fn from_state(participants: &[Participant]) -> Result<Self, ProtocolError> {
+        Self::new(participants).ok_or_else(|| {
+            ProtocolError::Malformed("participant list cannot contain duplicates".to_string())
+        })
+    }
+
     pub fn len(&self) -> usize {
         self.participants.len()
     }
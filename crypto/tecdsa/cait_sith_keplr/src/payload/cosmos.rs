@@ -0,0 +1,144 @@
+//! Cosmos payloads: amino JSON sign docs, protobuf `SIGN_MODE_DIRECT` sign docs,
+//! and ADR-036 arbitrary messages, all hashed with SHA-256.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use k256::sha2::{Digest, Sha256};
+use k256::{Scalar, Secp256k1};
+use serde_json::{json, Value};
+
+use super::{hash_to_scalar, payload_error};
+use crate::protocol::ProtocolError;
+use crate::sign::FullSignature;
+
+/// The message type of an ADR-036 arbitrary message.
+const ADR036_MSG_TYPE: &str = "sign/MsgSignData";
+
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+/// Serialize a JSON value with sorted keys and no whitespace.
+///
+/// Like amino, `&`, `<` and `>` are escaped, so the output matches the bytes
+/// signed by other Cosmos wallets.
+pub fn sorted_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_sorted_json(value, &mut out);
+    out.replace('&', "\\u0026")
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+}
+
+fn write_sorted_json(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<_> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::from(key.as_str()).to_string());
+                out.push(':');
+                write_sorted_json(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_sorted_json(item, out);
+            }
+            out.push(']');
+        }
+        _ => out.push_str(&value.to_string()),
+    }
+}
+
+/// The bytes signed for an amino JSON `StdSignDoc`.
+pub fn amino_sign_bytes(sign_doc: &Value) -> Result<Vec<u8>, ProtocolError> {
+    if !sign_doc.is_object() {
+        return Err(payload_error("sign doc is not an object"));
+    }
+    Ok(sorted_json(sign_doc).into_bytes())
+}
+
+/// The scalar to sign for an amino JSON `StdSignDoc`.
+pub fn amino_scalar(sign_doc: &Value) -> Result<Scalar, ProtocolError> {
+    Ok(hash_to_scalar(&sha256(&amino_sign_bytes(sign_doc)?)))
+}
+
+/// Encode a `SIGN_MODE_DIRECT` `SignDoc` as protobuf.
+///
+/// The body and auth info are the already encoded `TxBody` and `AuthInfo`,
+/// exactly as they will be included in the `TxRaw`.
+pub fn direct_sign_bytes(
+    body_bytes: &[u8],
+    auth_info_bytes: &[u8],
+    chain_id: &str,
+    account_number: u64,
+) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_bytes_field(&mut out, 1, body_bytes);
+    write_bytes_field(&mut out, 2, auth_info_bytes);
+    write_bytes_field(&mut out, 3, chain_id.as_bytes());
+    if account_number != 0 {
+        write_varint(&mut out, 4 << 3);
+        write_varint(&mut out, account_number);
+    }
+    out
+}
+
+fn write_bytes_field(out: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    // Default values are left out of the encoding.
+    if bytes.is_empty() {
+        return;
+    }
+    write_varint(out, (field << 3) | 2);
+    write_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// The scalar to sign for an encoded `SIGN_MODE_DIRECT` `SignDoc`.
+pub fn direct_scalar(sign_doc_bytes: &[u8]) -> Scalar {
+    hash_to_scalar(&sha256(sign_doc_bytes))
+}
+
+/// The amino JSON sign doc of an ADR-036 arbitrary message from `signer`.
+pub fn adr036_sign_doc(signer: &str, data: &[u8]) -> Value {
+    json!({
+        "chain_id": "",
+        "account_number": "0",
+        "sequence": "0",
+        "fee": { "gas": "0", "amount": [] },
+        "msgs": [{
+            "type": ADR036_MSG_TYPE,
+            "value": { "signer": signer, "data": STANDARD.encode(data) },
+        }],
+        "memo": "",
+    })
+}
+
+/// The scalar to sign for an ADR-036 arbitrary message from `signer`.
+pub fn adr036_scalar(signer: &str, data: &[u8]) -> Scalar {
+    let sign_bytes = sorted_json(&adr036_sign_doc(signer, data)).into_bytes();
+    hash_to_scalar(&sha256(&sign_bytes))
+}
+
+/// The 64 byte `r || s` encoding of a signature, with a low `s`, as Cosmos expects.
+pub fn compact_signature(sig: &FullSignature<Secp256k1>) -> [u8; 64] {
+    let mut out = [0u8; 64];
+    out.copy_from_slice(&sig.to_compact());
+    out
+}
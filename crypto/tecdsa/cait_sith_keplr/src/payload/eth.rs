@@ -0,0 +1,295 @@
+//! Ethereum payloads: `personal_sign` messages (EIP-191), typed data (EIP-712)
+//! and raw transactions, all hashed with Keccak-256.
+use std::collections::{BTreeMap, BTreeSet};
+
+use elliptic_curve::bigint::{CheckedAdd, CheckedMul, Encoding, U256};
+use k256::Scalar;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+
+use super::{hash_to_scalar, payload_error};
+use crate::protocol::ProtocolError;
+
+const EIP191_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n";
+const EIP712_PREFIX: &[u8] = b"\x19\x01";
+const EIP712_DOMAIN: &str = "EIP712Domain";
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// The EIP-191 hash of a `personal_sign` message.
+pub fn personal_message_hash(msg: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(EIP191_PREFIX);
+    hasher.update(msg.len().to_string().as_bytes());
+    hasher.update(msg);
+    hasher.finalize().into()
+}
+
+/// The scalar to sign for a `personal_sign` message.
+pub fn personal_message_scalar(msg: &[u8]) -> Scalar {
+    hash_to_scalar(&personal_message_hash(msg))
+}
+
+/// The scalar to sign for a serialized transaction.
+///
+/// This is the Keccak-256 hash of the exact bytes being signed, e.g. the RLP
+/// encoding of a legacy transaction, or the type byte followed by the RLP payload
+/// for typed transactions.
+pub fn transaction_scalar(raw_tx: &[u8]) -> Scalar {
+    hash_to_scalar(&keccak256(raw_tx))
+}
+
+/// A member of an EIP-712 struct type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypedDataField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// EIP-712 typed data, in the JSON format of `eth_signTypedData_v4`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+    pub types: BTreeMap<String, Vec<TypedDataField>>,
+    pub primary_type: String,
+    pub domain: Value,
+    pub message: Value,
+}
+
+impl TypedData {
+    pub fn from_json(json: &str) -> Result<Self, ProtocolError> {
+        serde_json::from_str(json)
+            .map_err(|err| payload_error(format!("invalid typed data: {err}")))
+    }
+
+    /// The hash of the domain, as `hashStruct(EIP712Domain, domain)`.
+    pub fn domain_separator(&self) -> Result<[u8; 32], ProtocolError> {
+        self.hash_struct(EIP712_DOMAIN, &self.domain)
+    }
+
+    /// The EIP-712 hash of this typed data.
+    pub fn hash(&self) -> Result<[u8; 32], ProtocolError> {
+        let mut hasher = Keccak256::new();
+        hasher.update(EIP712_PREFIX);
+        hasher.update(self.domain_separator()?);
+        // Signing the domain alone has no message hash.
+        if self.primary_type != EIP712_DOMAIN {
+            hasher.update(self.hash_struct(&self.primary_type, &self.message)?);
+        }
+        Ok(hasher.finalize().into())
+    }
+
+    /// The scalar to sign for this typed data.
+    pub fn scalar(&self) -> Result<Scalar, ProtocolError> {
+        Ok(hash_to_scalar(&self.hash()?))
+    }
+
+    /// `encodeType`, the struct type followed by the types it references, sorted by name.
+    pub fn encode_type(&self, ty: &str) -> Result<String, ProtocolError> {
+        let mut deps = BTreeSet::new();
+        self.find_dependencies(ty, &mut deps);
+        if !deps.remove(ty) {
+            return Err(payload_error(format!("unknown struct type {ty}")));
+        }
+
+        let mut out = String::new();
+        for dep in std::iter::once(ty).chain(deps.iter().map(String::as_str)) {
+            let fields: Vec<_> = self.types[dep]
+                .iter()
+                .map(|field| format!("{} {}", field.ty, field.name))
+                .collect();
+            out.push_str(&format!("{}({})", dep, fields.join(",")));
+        }
+        Ok(out)
+    }
+
+    pub fn type_hash(&self, ty: &str) -> Result<[u8; 32], ProtocolError> {
+        Ok(keccak256(self.encode_type(ty)?.as_bytes()))
+    }
+
+    /// `hashStruct`, the hash of a value of a struct type.
+    pub fn hash_struct(&self, ty: &str, value: &Value) -> Result<[u8; 32], ProtocolError> {
+        let fields = self
+            .types
+            .get(ty)
+            .ok_or_else(|| payload_error(format!("unknown struct type {ty}")))?;
+        let members = value
+            .as_object()
+            .ok_or_else(|| payload_error(format!("value of {ty} is not an object")))?;
+
+        let mut hasher = Keccak256::new();
+        hasher.update(self.type_hash(ty)?);
+        for field in fields {
+            let member = members
+                .get(&field.name)
+                .ok_or_else(|| payload_error(format!("{ty} is missing {}", field.name)))?;
+            hasher.update(self.encode_value(&field.ty, member)?);
+        }
+        Ok(hasher.finalize().into())
+    }
+
+    fn find_dependencies(&self, ty: &str, deps: &mut BTreeSet<String>) {
+        let ty = ty.split('[').next().unwrap_or(ty);
+        if deps.contains(ty) {
+            return;
+        }
+        if let Some(fields) = self.types.get(ty) {
+            deps.insert(ty.to_string());
+            for field in fields {
+                self.find_dependencies(&field.ty, deps);
+            }
+        }
+    }
+
+    fn encode_value(&self, ty: &str, value: &Value) -> Result<[u8; 32], ProtocolError> {
+        if let Some(elem_ty) = ty.strip_suffix(']') {
+            let (elem_ty, len) = elem_ty
+                .rsplit_once('[')
+                .ok_or_else(|| payload_error(format!("invalid array type {ty}")))?;
+            let items = value
+                .as_array()
+                .ok_or_else(|| payload_error(format!("value of {ty} is not an array")))?;
+            if !len.is_empty() && len.parse::<usize>().ok() != Some(items.len()) {
+                return Err(payload_error(format!("value of {ty} has the wrong length")));
+            }
+
+            let mut hasher = Keccak256::new();
+            for item in items {
+                hasher.update(self.encode_value(elem_ty, item)?);
+            }
+            return Ok(hasher.finalize().into());
+        }
+
+        if self.types.contains_key(ty) {
+            return self.hash_struct(ty, value);
+        }
+
+        let mut out = [0u8; 32];
+        match ty {
+            "string" => {
+                let s = value
+                    .as_str()
+                    .ok_or_else(|| payload_error("value of string is not a string"))?;
+                out = keccak256(s.as_bytes());
+            }
+            "bytes" => out = keccak256(&parse_hex(value)?),
+            "bool" => {
+                let b = value
+                    .as_bool()
+                    .ok_or_else(|| payload_error("value of bool is not a boolean"))?;
+                out[31] = u8::from(b);
+            }
+            "address" => {
+                let bytes = parse_hex(value)?;
+                if bytes.len() != 20 {
+                    return Err(payload_error("address must be 20 bytes"));
+                }
+                out[12..].copy_from_slice(&bytes);
+            }
+            _ => {
+                if let Some(size) = ty.strip_prefix("bytes") {
+                    let size = parse_size(ty, size, 32)?;
+                    let bytes = parse_hex(value)?;
+                    if bytes.len() != size {
+                        return Err(payload_error(format!("value of {ty} has the wrong length")));
+                    }
+                    out[..size].copy_from_slice(&bytes);
+                } else if let Some(bits) = ty.strip_prefix("uint") {
+                    out = parse_integer(value, parse_size(ty, bits, 256)?, false)?;
+                } else if let Some(bits) = ty.strip_prefix("int") {
+                    out = parse_integer(value, parse_size(ty, bits, 256)?, true)?;
+                } else {
+                    return Err(payload_error(format!("unknown type {ty}")));
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Parse the size suffix of a type like `bytes8` or `uint64`, defaulting to `max`.
+fn parse_size(ty: &str, size: &str, max: usize) -> Result<usize, ProtocolError> {
+    if size.is_empty() {
+        return Ok(max);
+    }
+    match size.parse::<usize>() {
+        Ok(size) if size > 0 && size <= max => Ok(size),
+        _ => Err(payload_error(format!("unknown type {ty}"))),
+    }
+}
+
+fn parse_hex(value: &Value) -> Result<Vec<u8>, ProtocolError> {
+    let s = value
+        .as_str()
+        .ok_or_else(|| payload_error("expected a hex string"))?;
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if s.len() % 2 != 0 {
+        return Err(payload_error("hex string has an odd length"));
+    }
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let digit = |b: u8| char::from(b).to_digit(16);
+            match (digit(pair[0]), digit(pair[1])) {
+                (Some(hi), Some(lo)) => Ok((hi << 4 | lo) as u8),
+                _ => Err(payload_error("invalid hex string")),
+            }
+        })
+        .collect()
+}
+
+/// Encode an integer given as a JSON number, or a decimal or hex string,
+/// as a 32 byte big-endian two's complement value.
+fn parse_integer(value: &Value, bits: usize, signed: bool) -> Result<[u8; 32], ProtocolError> {
+    let s = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        _ => return Err(payload_error("expected an integer")),
+    };
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s.as_str()),
+    };
+    let (radix, digits) = match digits.strip_prefix("0x") {
+        Some(digits) => (16, digits),
+        None => (10, digits),
+    };
+    if digits.is_empty() {
+        return Err(payload_error(format!("invalid integer {s}")));
+    }
+
+    let overflow = || payload_error(format!("integer {s} is out of range"));
+    let mut magnitude = U256::ZERO;
+    for c in digits.chars() {
+        let digit = c
+            .to_digit(radix)
+            .ok_or_else(|| payload_error(format!("invalid integer {s}")))?;
+        let shifted: Option<U256> = magnitude.checked_mul(&U256::from(radix)).into();
+        let next: Option<U256> = shifted
+            .ok_or_else(overflow)?
+            .checked_add(&U256::from(digit))
+            .into();
+        magnitude = next.ok_or_else(overflow)?;
+    }
+
+    let value_bits = if signed { bits - 1 } else { bits };
+    let in_range = match (negative, signed) {
+        (false, _) => magnitude.bits() <= value_bits,
+        (true, true) => magnitude.wrapping_sub(&U256::ONE).bits() <= value_bits,
+        (true, false) => magnitude == U256::ZERO,
+    };
+    if !in_range {
+        return Err(overflow());
+    }
+
+    let encoded = if negative {
+        magnitude.wrapping_neg()
+    } else {
+        magnitude
+    };
+    Ok(encoded.to_be_bytes())
+}
@@ -0,0 +1,25 @@
+//! Hashing of signing payloads into the `msg_hash` scalar the sign steps take.
+//!
+//! The client and the server should both derive the scalar from the payload
+//! with these functions, rather than trusting a hash computed by the other side.
+#[cfg(feature = "k256")]
+pub mod cosmos;
+#[cfg(feature = "k256")]
+pub mod eth;
+
+#[cfg(feature = "k256")]
+use elliptic_curve::{bigint::U256, ops::Reduce};
+
+#[cfg(feature = "k256")]
+use crate::protocol::ProtocolError;
+
+/// Interpret a 32 byte hash as the scalar to sign.
+#[cfg(feature = "k256")]
+pub fn hash_to_scalar(hash: &[u8; 32]) -> k256::Scalar {
+    <k256::Scalar as Reduce<U256>>::reduce_bytes(k256::FieldBytes::from_slice(hash))
+}
+
+#[cfg(feature = "k256")]
+fn payload_error(msg: impl Into<String>) -> ProtocolError {
+    ProtocolError::Malformed(msg.into())
+}
@@ -1,12 +1,36 @@
+use std::collections::HashMap;
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::compat::CSCurve;
+use crate::protocol::Participant;
+
+/// Public commitments to the presignature shares of a participant.
+///
+/// These let the other participants check each partial signature on its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "C::AffinePoint: Serialize + for<'a> Deserialize<'a>")]
+pub struct PresignCommitment<C: CSCurve> {
+    /// The commitment `k * G` to the share of the nonce.
+    pub big_k: C::AffinePoint,
+    /// The commitment `sigma * G` to the share of sigma.
+    pub big_sigma: C::AffinePoint,
+}
 
 /// The output of the presigning protocol.
 ///
 /// This output is basically all the parts of the signature that we can perform
 /// without knowing the message.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The shares of the nonce and of sigma are wiped when the output is dropped,
+/// and left out of its `Debug` output.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "
+    C::Scalar: Serialize + for<'a> Deserialize<'a>,
+    C::AffinePoint: Serialize + for<'a> Deserialize<'a>
+")]
 pub struct PresignOutput<C: CSCurve> {
     /// The public nonce commitment.
     pub big_r: C::AffinePoint,
@@ -14,4 +38,35 @@ pub struct PresignOutput<C: CSCurve> {
     pub k: C::Scalar,
     /// Our share of the sigma value.
     pub sigma: C::Scalar,
+    /// The commitments to the shares of every participant.
+    ///
+    /// This is empty for presignatures made without them.
+    #[serde(default, with = "crate::protocol::participant_serde")]
+    pub commitments: HashMap<Participant, PresignCommitment<C>>,
+}
+
+impl<C: CSCurve> fmt::Debug for PresignOutput<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PresignOutput")
+            .field("big_r", &self.big_r)
+            .field("k", &format_args!("<redacted>"))
+            .field("sigma", &format_args!("<redacted>"))
+            .field("commitments", &self.commitments)
+            .finish()
+    }
+}
+
+impl<C: CSCurve> Zeroize for PresignOutput<C> {
+    fn zeroize(&mut self) {
+        self.k.zeroize();
+        self.sigma.zeroize();
+    }
 }
+
+impl<C: CSCurve> Drop for PresignOutput<C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<C: CSCurve> ZeroizeOnDrop for PresignOutput<C> {}
@@ -1,15 +1,34 @@
 use elliptic_curve::ScalarPrimitive;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::{
     compat::CSCurve,
+    crypto::Digest,
     protocol::Participant,
     triples::{TriplePub, TripleShare},
     KeygenOutput,
 };
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+/// Commitments to the shares a participant presigns with, sent along with `kd_i`.
+///
+/// The triple shares are scaled the same way as the nonce share is in the output.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(bound = "C::AffinePoint: Serialize + for<'a> Deserialize<'a>")]
+pub struct PresignShareCommitments<C: CSCurve> {
+    pub big_k_i: C::AffinePoint,
+    pub big_a_i: C::AffinePoint,
+    pub big_c_i: C::AffinePoint,
+    pub big_x_i: C::AffinePoint,
+}
+
+/// The state of a participant between the steps of presigning.
+///
+/// Every secret scalar is wiped when the state is dropped, and none of them
+/// show up in `Debug` output.
+#[derive(Deserialize, Serialize, Clone)]
 #[serde(bound = "
     C::Scalar: Serialize + for<'a> Deserialize<'a>,
     C::AffinePoint: Serialize + for<'a> Deserialize<'a>
@@ -18,6 +37,8 @@ pub struct PresignState2<C: CSCurve> {
     pub threshold: usize,
 
     pub participants: Vec<Participant>,
+    /// Binds the presignature to a single session and key.
+    pub session_id: Vec<u8>,
     pub pub_0: TriplePub<C>,
 
     pub pub_1: TriplePub<C>,
@@ -52,6 +73,10 @@ pub struct PresignState2<C: CSCurve> {
     pub ka_i: Option<C::Scalar>,
     pub xb_i: Option<C::Scalar>,
 
+    #[serde(default)]
+    pub share_commitments: Option<PresignShareCommitments<C>>,
+    pub confirmation: Option<Digest>,
+
     pub kd_sum: Option<C::Scalar>,
     pub ka_sum: Option<C::Scalar>,
     pub xb_sum: Option<C::Scalar>,
@@ -62,9 +87,11 @@ pub struct PresignState2<C: CSCurve> {
 }
 
 impl<'a, C: CSCurve> PresignState2<C> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         participants: Vec<Participant>,
         threshold: usize,
+        session_id: Vec<u8>,
         keygen_out: KeygenOutput<C>,
         pub_0: TriplePub<C>,
         pub_1: TriplePub<C>,
@@ -75,6 +102,7 @@ impl<'a, C: CSCurve> PresignState2<C> {
             threshold,
 
             participants,
+            session_id,
 
             keygen_out,
             pub_0,
@@ -105,6 +133,9 @@ impl<'a, C: CSCurve> PresignState2<C> {
             ka_i: None,
             xb_i: None,
 
+            share_commitments: None,
+            confirmation: None,
+
             kd_sum: None,
             ka_sum: None,
             xb_sum: None,
@@ -116,6 +147,59 @@ impl<'a, C: CSCurve> PresignState2<C> {
     }
 }
 
+impl<C: CSCurve> fmt::Debug for PresignState2<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PresignState2")
+            .field("threshold", &self.threshold)
+            .field("participants", &self.participants)
+            .field("session_id", &self.session_id)
+            .field("pub_0", &self.pub_0)
+            .field("pub_1", &self.pub_1)
+            .field("keygen_out", &self.keygen_out)
+            .field("share_commitments", &self.share_commitments)
+            .field("confirmation", &self.confirmation)
+            .field("big_r", &self.big_r)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: CSCurve> Zeroize for PresignState2<C> {
+    fn zeroize(&mut self) {
+        self.share_0_0.zeroize();
+        self.share_1_0.zeroize();
+        self.keygen_out.zeroize();
+        for secret in [
+            &mut self.sk_lambda,
+            &mut self.bt_lambda,
+            &mut self.a_i,
+            &mut self.c_i,
+            &mut self.k_i,
+            &mut self.k_prime_i,
+            &mut self.a_prime_i,
+            &mut self.b_prime_i,
+            &mut self.x_prime_i,
+            &mut self.kd_i,
+            &mut self.ka_i,
+            &mut self.xb_i,
+            &mut self.kd_sum,
+            &mut self.ka_sum,
+            &mut self.xb_sum,
+            &mut self.k_final,
+            &mut self.sigma_i_final,
+        ] {
+            secret.zeroize();
+        }
+    }
+}
+
+impl<C: CSCurve> Drop for PresignState2<C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<C: CSCurve> ZeroizeOnDrop for PresignState2<C> {}
+
 type From = Participant;
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(bound = "
@@ -125,6 +209,9 @@ type From = Participant;
 pub struct RcvdPresignMessages2<C: CSCurve> {
     pub wait_0: HashMap<From, ScalarPrimitive<C>>,
     pub wait_1: HashMap<From, (ScalarPrimitive<C>, ScalarPrimitive<C>)>,
+    #[serde(default)]
+    pub wait_0_commitments: HashMap<From, PresignShareCommitments<C>>,
+    pub wait_0_confirmations: HashMap<From, Digest>,
 }
 
 impl<C: CSCurve> RcvdPresignMessages2<C> {
@@ -132,6 +219,16 @@ impl<C: CSCurve> RcvdPresignMessages2<C> {
         Self {
             wait_0: HashMap::new(),
             wait_1: HashMap::new(),
+            wait_0_commitments: HashMap::new(),
+            wait_0_confirmations: HashMap::new(),
         }
     }
+
+    /// Merge messages received from another participant into this buffer.
+    pub fn extend(&mut self, other: Self) {
+        self.wait_0.extend(other.wait_0);
+        self.wait_1.extend(other.wait_1);
+        self.wait_0_commitments.extend(other.wait_0_commitments);
+        self.wait_0_confirmations.extend(other.wait_0_confirmations);
+    }
 }
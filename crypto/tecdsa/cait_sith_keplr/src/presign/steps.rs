@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use elliptic_curve::{Field, Group};
 use serde::{Deserialize, Serialize};
 
@@ -177,5 +179,6 @@ pub fn step_3<C: CSCurve>(
         big_r,
         k: k_i * lambda_diff,
         sigma: sigma_i,
+        commitments: HashMap::new(),
     })
 }
@@ -1,7 +1,11 @@
-use elliptic_curve::{Field, Group};
+use std::collections::HashMap;
 
-use super::{PresignState2, RcvdPresignMessages2};
-use crate::compat::CSCurve;
+use elliptic_curve::{group::Curve, Field, Group};
+use zeroize::Zeroize;
+
+use super::{PresignCommitment, PresignShareCommitments, PresignState2, RcvdPresignMessages2};
+use crate::compat::{CSCurve, SerializablePoint};
+use crate::crypto::{hash, Digest};
 use crate::participants::ParticipantList;
 use crate::presign::steps::{Step1Output, Step2Output};
 use crate::protocol::{Participant, ProtocolError};
@@ -22,15 +26,7 @@ pub fn step_1<C: CSCurve>(
     let big_a: C::ProjectivePoint = state.pub_1.big_a.into();
     let big_b: C::ProjectivePoint = state.pub_1.big_b.into();
 
-    let participant_list = ParticipantList::new(&state.participants)
-        .ok_or_else(|| {
-            ProtocolError::Other(
-                "participant list cannot contain duplicates"
-                    .to_string()
-                    .into(),
-            )
-        })
-        .unwrap();
+    let participant_list = ParticipantList::from_state(&state.participants)?;
 
     let sk_lambda = participant_list.lagrange::<C>(me);
     let bt_lambda = participant_list.lagrange::<C>(bt_id);
@@ -67,16 +63,37 @@ pub fn step_1<C: CSCurve>(
     state.x_prime_i = Some(x_prime_i);
     state.kd_i = Some(kd_i);
 
+    state.confirmation = Some(confirmation(state, &participant_list));
+
+    // Commit to the shares the output is made of, scaled as in Spec 2.8,
+    // so that partial signatures can be checked one by one.
+    let lambda_diff = bt_lambda * invert_lambda::<C>(sk_lambda)?;
+    let g = C::ProjectivePoint::generator();
+    state.share_commitments = Some(PresignShareCommitments {
+        big_k_i: (g * (k_i * lambda_diff)).to_affine(),
+        big_a_i: (g * (a_i * lambda_diff)).to_affine(),
+        big_c_i: (g * (c_i * lambda_diff)).to_affine(),
+        big_x_i: (g * state.keygen_out.private_share).to_affine(),
+    });
+
     let out = Step1Output { kd_i };
 
     Ok(out)
 }
 
 pub fn step_2<C: CSCurve>(state: &mut PresignState2<C>) -> Result<Step2Output<C>, ProtocolError> {
-    let k_prime_i = state.k_prime_i.unwrap();
-    let a_prime_i = state.a_prime_i.unwrap();
-    let b_prime_i = state.b_prime_i.unwrap();
-    let x_prime_i = state.x_prime_i.unwrap();
+    let k_prime_i = state
+        .k_prime_i
+        .ok_or(ProtocolError::OutOfOrder("k_prime_i"))?;
+    let a_prime_i = state
+        .a_prime_i
+        .ok_or(ProtocolError::OutOfOrder("a_prime_i"))?;
+    let b_prime_i = state
+        .b_prime_i
+        .ok_or(ProtocolError::OutOfOrder("b_prime_i"))?;
+    let x_prime_i = state
+        .x_prime_i
+        .ok_or(ProtocolError::OutOfOrder("x_prime_i"))?;
 
     let ka_i: C::Scalar = k_prime_i + a_prime_i;
     let xb_i: C::Scalar = x_prime_i + b_prime_i;
@@ -84,6 +101,12 @@ pub fn step_2<C: CSCurve>(state: &mut PresignState2<C>) -> Result<Step2Output<C>
     state.ka_i = Some(ka_i);
     state.xb_i = Some(xb_i);
 
+    // The scaled shares are only ever sent masked, as ka_i and xb_i.
+    state.k_prime_i.zeroize();
+    state.a_prime_i.zeroize();
+    state.b_prime_i.zeroize();
+    state.x_prime_i.zeroize();
+
     let out = Step2Output { ka_i, xb_i };
 
     return Ok(out);
@@ -94,33 +117,48 @@ pub fn step_3<C: CSCurve>(
     msgs: &RcvdPresignMessages2<C>,
     me: Participant,
 ) -> Result<PresignOutput<C>, ProtocolError> {
-    let kd_i = state.kd_i.unwrap();
-
-    let participant_list = ParticipantList::new(&state.participants)
-        .ok_or_else(|| {
-            ProtocolError::Other(
-                "participant list cannot contain duplicates"
-                    .to_string()
-                    .into(),
-            )
-        })
-        .unwrap();
+    let kd_i = state.kd_i.ok_or(ProtocolError::OutOfOrder("kd_i"))?;
+
+    let participant_list = ParticipantList::from_state(&state.participants)?;
+
+    let my_confirmation = state
+        .confirmation
+        .ok_or(ProtocolError::OutOfOrder("confirmation"))?;
+    for p in participant_list.others(me) {
+        let their_confirmation =
+            msgs.wait_0_confirmations
+                .get(&p)
+                .ok_or(ProtocolError::MissingMessage {
+                    from: p,
+                    wait: "wait_0_confirmations",
+                })?;
+        if *their_confirmation != my_confirmation {
+            return Err(ProtocolError::BadShare(
+                p,
+                "confirmation did not match expectation".to_string(),
+            ));
+        }
+    }
 
     // Spec 2.1 and 2.2
     let mut kd = kd_i;
     for p in participant_list.others(me) {
-        let kd_j = *msgs.wait_0.get(&p).unwrap();
+        let kd_j = *msgs.wait_0.get(&p).ok_or(ProtocolError::MissingMessage {
+            from: p,
+            wait: "wait_0",
+        })?;
 
         if kd_j.is_zero().into() {
-            return Err(ProtocolError::AssertionFailed(
-                "Received zero share of kd, indicating a triple wasn't available.".to_string(),
+            return Err(ProtocolError::BadShare(
+                p,
+                "zero share of kd, indicating a triple wasn't available".to_string(),
             ));
         }
 
         kd += C::Scalar::from(kd_j);
     }
 
-    let big_kd = state.big_kd.unwrap();
+    let big_kd = state.big_kd.ok_or(ProtocolError::OutOfOrder("big_kd"))?;
 
     // Spec 2.3
     if big_kd != (C::ProjectivePoint::generator() * kd).into() {
@@ -129,24 +167,39 @@ pub fn step_3<C: CSCurve>(
         ));
     }
 
-    let ka_i = state.ka_i.unwrap();
-    let xb_i = state.xb_i.unwrap();
+    let ka_i = state.ka_i.ok_or(ProtocolError::OutOfOrder("ka_i"))?;
+    let xb_i = state.xb_i.ok_or(ProtocolError::OutOfOrder("xb_i"))?;
 
     // Spec 2.4 and 2.5
     let mut ka = ka_i;
     let mut xb = xb_i;
 
     for p in participant_list.others(me) {
-        let (ka_j, xb_j) = msgs.wait_1.get(&p).unwrap();
+        let (ka_j, xb_j) = msgs.wait_1.get(&p).ok_or(ProtocolError::MissingMessage {
+            from: p,
+            wait: "wait_1",
+        })?;
 
         ka += C::Scalar::from(*ka_j);
         xb += C::Scalar::from(*xb_j);
     }
 
-    let big_k: C::ProjectivePoint = state.big_k.unwrap().into();
-    let big_a: C::ProjectivePoint = state.big_a.unwrap().into();
-    let big_x: C::ProjectivePoint = state.big_x.unwrap().into();
-    let big_b: C::ProjectivePoint = state.big_b.unwrap().into();
+    let big_k: C::ProjectivePoint = state
+        .big_k
+        .ok_or(ProtocolError::OutOfOrder("big_k"))?
+        .into();
+    let big_a: C::ProjectivePoint = state
+        .big_a
+        .ok_or(ProtocolError::OutOfOrder("big_a"))?
+        .into();
+    let big_x: C::ProjectivePoint = state
+        .big_x
+        .ok_or(ProtocolError::OutOfOrder("big_x"))?
+        .into();
+    let big_b: C::ProjectivePoint = state
+        .big_b
+        .ok_or(ProtocolError::OutOfOrder("big_b"))?
+        .into();
 
     // Spec 2.6
     if (C::ProjectivePoint::generator() * ka != big_k + big_a)
@@ -157,7 +210,7 @@ pub fn step_3<C: CSCurve>(
         ));
     }
 
-    let big_d = state.big_d.unwrap();
+    let big_d = state.big_d.ok_or(ProtocolError::OutOfOrder("big_d"))?;
 
     // Spec 2.7
     let kd_inv: Option<C::Scalar> = kd.invert().into();
@@ -165,21 +218,135 @@ pub fn step_3<C: CSCurve>(
         kd_inv.ok_or_else(|| ProtocolError::AssertionFailed("failed to invert kd".to_string()))?;
     let big_r = (C::ProjectivePoint::from(big_d) * kd_inv).into();
 
-    let sk_lambda = state.sk_lambda.unwrap();
-    let bt_lambda = state.bt_lambda.unwrap();
+    let sk_lambda = state
+        .sk_lambda
+        .ok_or(ProtocolError::OutOfOrder("sk_lambda"))?;
+    let bt_lambda = state
+        .bt_lambda
+        .ok_or(ProtocolError::OutOfOrder("bt_lambda"))?;
 
-    let a_i = state.a_i.unwrap();
-    let c_i = state.c_i.unwrap();
+    let a_i = state.a_i.ok_or(ProtocolError::OutOfOrder("a_i"))?;
+    let c_i = state.c_i.ok_or(ProtocolError::OutOfOrder("c_i"))?;
 
     // Spec 2.8
-    let lambda_diff = bt_lambda * sk_lambda.invert().expect("to invert sk_lambda");
+    let lambda_diff = bt_lambda * invert_lambda::<C>(sk_lambda)?;
     let sigma_i = ka * state.keygen_out.private_share - (xb * a_i - c_i) * lambda_diff;
 
-    let k_i = state.k_i.unwrap();
+    let k_i = state.k_i.ok_or(ProtocolError::OutOfOrder("k_i"))?;
 
-    Ok(PresignOutput {
+    let commitments = presign_commitments(state, msgs, &participant_list, me, ka, xb)?;
+
+    let output = PresignOutput {
         big_r,
         k: k_i * lambda_diff,
         sigma: sigma_i,
-    })
+        commitments,
+    };
+
+    // Everything secret is in the output now.
+    state.zeroize();
+
+    Ok(output)
+}
+
+/// Hash what every participant must agree on before presigning: the session,
+/// the participants, the key and the triples used.
+fn confirmation<C: CSCurve>(
+    state: &PresignState2<C>,
+    participant_list: &ParticipantList,
+) -> Digest {
+    let points = [
+        state.keygen_out.public_key,
+        state.pub_0.big_a,
+        state.pub_0.big_b,
+        state.pub_0.big_c,
+        state.pub_1.big_a,
+        state.pub_1.big_b,
+        state.pub_1.big_c,
+    ]
+    .map(SerializablePoint::<C>);
+
+    hash(&(&state.session_id, participant_list, &points))
+}
+
+/// Check the share commitments of every participant, and derive the commitments
+/// to their presignature shares from them.
+fn presign_commitments<C: CSCurve>(
+    state: &PresignState2<C>,
+    msgs: &RcvdPresignMessages2<C>,
+    participant_list: &ParticipantList,
+    me: Participant,
+    ka: C::Scalar,
+    xb: C::Scalar,
+) -> Result<HashMap<Participant, PresignCommitment<C>>, ProtocolError> {
+    let mut all_share_commitments = HashMap::new();
+    all_share_commitments.insert(
+        me,
+        state
+            .share_commitments
+            .clone()
+            .ok_or(ProtocolError::OutOfOrder("share_commitments"))?,
+    );
+    for p in participant_list.others(me) {
+        let share_commitments =
+            msgs.wait_0_commitments
+                .get(&p)
+                .ok_or(ProtocolError::MissingMessage {
+                    from: p,
+                    wait: "wait_0_commitments",
+                })?;
+        all_share_commitments.insert(p, share_commitments.clone());
+    }
+
+    let mut big_k = C::ProjectivePoint::identity();
+    let mut big_a = C::ProjectivePoint::identity();
+    let mut big_c = C::ProjectivePoint::identity();
+    let mut big_x = C::ProjectivePoint::identity();
+    for (&p, share_commitments) in &all_share_commitments {
+        if let Some(public_share) = state.keygen_out.public_share(p) {
+            if share_commitments.big_x_i != public_share {
+                return Err(ProtocolError::BadShare(
+                    p,
+                    "commitment to the private share does not match the public share".to_string(),
+                ));
+            }
+        }
+
+        let lambda = participant_list.lagrange::<C>(p);
+        big_k += C::ProjectivePoint::from(share_commitments.big_k_i) * lambda;
+        big_a += C::ProjectivePoint::from(share_commitments.big_a_i) * lambda;
+        big_c += C::ProjectivePoint::from(share_commitments.big_c_i) * lambda;
+        big_x += C::ProjectivePoint::from(share_commitments.big_x_i) * lambda;
+    }
+
+    if big_k != C::ProjectivePoint::from(state.big_k.ok_or(ProtocolError::OutOfOrder("big_k"))?)
+        || big_a != C::ProjectivePoint::from(state.big_a.ok_or(ProtocolError::OutOfOrder("big_a"))?)
+        || big_c != C::ProjectivePoint::from(state.pub_1.big_c)
+        || big_x != C::ProjectivePoint::from(state.big_x.ok_or(ProtocolError::OutOfOrder("big_x"))?)
+    {
+        return Err(ProtocolError::AssertionFailed(
+            "presign share commitments do not match the triples and public key".to_string(),
+        ));
+    }
+
+    // Spec 2.8, in the exponent
+    Ok(all_share_commitments
+        .into_iter()
+        .map(|(p, share_commitments)| {
+            let big_sigma = C::ProjectivePoint::from(share_commitments.big_x_i) * ka
+                - C::ProjectivePoint::from(share_commitments.big_a_i) * xb
+                + C::ProjectivePoint::from(share_commitments.big_c_i);
+            let commitment = PresignCommitment {
+                big_k: share_commitments.big_k_i,
+                big_sigma: big_sigma.to_affine(),
+            };
+            (p, commitment)
+        })
+        .collect())
+}
+
+/// Invert a Lagrange coefficient, which is only zero for a malformed participant list.
+fn invert_lambda<C: CSCurve>(lambda: C::Scalar) -> Result<C::Scalar, ProtocolError> {
+    let inverse: Option<C::Scalar> = lambda.invert().into();
+    inverse.ok_or_else(|| ProtocolError::Malformed("lagrange coefficient is zero".to_string()))
 }
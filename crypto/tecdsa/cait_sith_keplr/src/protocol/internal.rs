@@ -26,32 +26,22 @@
 //! This kind of decomposition can lead to better performance, and better matches what the
 //! dependencies between messages in the protocol actually are.
 //!
-//! We also need a good way to handle concurrent composition of protocols.
-//! This is mainly useful for some more advanced protocols, like triple generation, where we might
-//! want to run multiple two-party protocols in parallel across an entire group of participants.
-//! To do this, we also need some notion of channel in addition to waitpoints, and the ability
-//! to have distinct channels to communicate on.
-//!
-//! We have two basic kinds of channels: channels which are intended to be shared to communicate
-//! to all other participants, and channels which are supposed to be used for two-party
-//! protocols. The two kinds won't conflict with each other. Given a channel, we can
-//! also get new unique *children* channels, whose children will also be unique.
-//!
-//! One paramount thing about the identification system for channels is that both parties
-//! agree on what the identifier for the channels in each part of the protocol is.
-//! This is why we have to take great care that the identifiers a protocol will produce
-//! are deterministic, even in the presence of concurrent tasks.
+//! Messages are sent on a shared channel, which every participant communicates on.
+//! Its tag goes in front of each message, along with the waitpoint, so that messages
+//! can be routed to the point of the protocol waiting for them.
 use ck_meow::Meow;
-use event_listener::Event;
 use serde::{de::DeserializeOwned, Serialize};
-use smol::{
-    block_on,
-    channel::{self, Receiver, Sender},
-    future,
-    lock::Mutex,
-    Executor, Task,
+use std::{
+    collections::{HashMap, VecDeque},
+    error,
+    future::{self, Future},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{self, Poll, Wake, Waker},
 };
-use std::{collections::HashMap, error, future::Future, sync::Arc};
 
 use crate::serde::{decode, encode_with_tag};
 
@@ -77,42 +67,6 @@ impl ChannelTag {
         meow.prf(&mut out, false);
         Self(out)
     }
-
-    /// The channel tag for a private channel.
-    ///
-    /// This will always yield the same tag, and is intended to be the root for private channels.
-    ///
-    /// This tag will depend on the set of participants used; the order they're passed into this
-    /// function does not matter.
-    fn root_private(p0: Participant, p1: Participant) -> Self {
-        // Sort participants, for uniqueness.
-        let (p0, p1) = (p0.min(p1), p0.max(p1));
-        let mut meow = Meow::new(MEOW_DOMAIN);
-        meow.meta_ad(b"root private", false);
-        meow.meta_ad(b"p0", false);
-        meow.ad(&p0.bytes(), false);
-        meow.meta_ad(b"p1", false);
-        meow.ad(&p1.bytes(), false);
-        let mut out = [0u8; Self::SIZE];
-        meow.prf(&mut out, false);
-        Self(out)
-    }
-
-    /// Get the ith child of this tag.
-    ///
-    /// Each child has its own "namespace", with its children being distinct.
-    ///
-    /// Indexed children have a separate namespace from named children.
-    fn child(&self, i: u64) -> Self {
-        let mut meow = Meow::new(MEOW_DOMAIN);
-        meow.meta_ad(b"parent", false);
-        meow.ad(&self.0, false);
-        meow.meta_ad(b"i", false);
-        meow.ad(&i.to_le_bytes(), false);
-        let mut out = [0u8; Self::SIZE];
-        meow.prf(&mut out, false);
-        Self(out)
-    }
 }
 
 /// A waitpoint inside of a channel.
@@ -174,68 +128,66 @@ impl MessageHeader {
         self.waitpoint += 1;
         out
     }
-
-    fn child(&self, i: u64) -> Self {
-        Self {
-            channel: self.channel.child(i),
-            waitpoint: 0,
-        }
-    }
 }
 
 type SubMessageQueue = Vec<(Participant, MessageData)>;
 
-/// A message buffer is a concurrent data structure to buffer messages.
+/// A message buffer is a data structure to buffer messages.
 ///
 /// The idea is that we can put messages, and have them organized according to the
 /// header that addentifies where in the protocol those messages will be needed.
 /// This data structure also provides async functions which allow efficiently
-/// waiting until a particular message is available, by using events to sleep tasks
-/// until a message for that slot has arrived.
+/// waiting until a particular message is available, by registering the waker
+/// of a task until a message for that slot has arrived.
 #[derive(Clone)]
 struct MessageBuffer {
     messages: Arc<Mutex<HashMap<MessageHeader, SubMessageQueue>>>,
-    events: Arc<Mutex<HashMap<MessageHeader, Event>>>,
+    wakers: Arc<Mutex<HashMap<MessageHeader, Vec<Waker>>>>,
 }
 
 impl MessageBuffer {
     fn new() -> Self {
         Self {
             messages: Arc::new(Mutex::new(HashMap::new())),
-            events: Arc::new(Mutex::new(HashMap::new())),
+            wakers: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
     /// Push a message into this buffer.
     ///
     /// We also need the header for the message, and the participant who sent it.
-    async fn push(&self, header: MessageHeader, from: Participant, message: MessageData) {
-        let mut messages_lock = self.messages.as_ref().lock().await;
-        messages_lock
+    fn push(&self, header: MessageHeader, from: Participant, message: MessageData) {
+        self.messages
+            .lock()
+            .unwrap()
             .entry(header)
             .or_default()
             .push((from, message));
-        let mut events_lock = self.events.as_ref().lock().await;
-        events_lock.entry(header).or_default().notify(1);
+        let wakers = self.wakers.lock().unwrap().remove(&header);
+        for waker in wakers.into_iter().flatten() {
+            waker.wake();
+        }
     }
 
     /// Pop a message for a particular header.
     ///
-    /// This will block until a message for that header is available. This will
+    /// This will wait until a message for that header is available. This will
     /// also correctly wake the underlying task when such a message arrives.
     async fn pop(&self, header: MessageHeader) -> (Participant, MessageData) {
-        loop {
-            let listener = {
-                let mut messages_lock = self.messages.as_ref().lock().await;
-                let messages = messages_lock.entry(header).or_default();
-                if let Some(out) = messages.pop() {
-                    return out;
-                }
-                let mut events_lock = self.events.as_ref().lock().await;
-                events_lock.entry(header).or_default().listen()
-            };
-            listener.await;
-        }
+        future::poll_fn(|cx| {
+            let mut messages_lock = self.messages.lock().unwrap();
+            if let Some(out) = messages_lock.entry(header).or_default().pop() {
+                return Poll::Ready(out);
+            }
+            self.wakers
+                .lock()
+                .unwrap()
+                .entry(header)
+                .or_default()
+                .push(cx.waker().clone());
+            Poll::Pending
+        })
+        .await
     }
 }
 
@@ -251,29 +203,22 @@ pub enum Message {
 #[derive(Clone)]
 struct Comms {
     buffer: MessageBuffer,
-    message_s: Sender<Message>,
-    message_r: Receiver<Message>,
+    outgoing: Arc<Mutex<VecDeque<Message>>>,
 }
 
 impl Comms {
     pub fn new() -> Self {
-        let (message_s, message_r) = channel::bounded(1);
-
         Self {
             buffer: MessageBuffer::new(),
-            message_s,
-            message_r,
+            outgoing: Arc::new(Mutex::new(VecDeque::new())),
         }
     }
 
-    async fn outgoing(&self) -> Message {
-        self.message_r
-            .recv()
-            .await
-            .expect("failed to check outgoing messages")
+    fn outgoing(&self) -> Option<Message> {
+        self.outgoing.lock().unwrap().pop_front()
     }
 
-    async fn push_message(&self, from: Participant, message: MessageData) {
+    fn push_message(&self, from: Participant, message: MessageData) {
         if message.len() < MessageHeader::LEN {
             return;
         }
@@ -283,14 +228,11 @@ impl Comms {
             _ => return,
         };
 
-        self.buffer.push(header, from, message).await
+        self.buffer.push(header, from, message)
     }
 
     async fn send_raw(&self, data: Message) {
-        self.message_s
-            .send(data)
-            .await
-            .expect("failed to send message");
+        self.outgoing.lock().unwrap().push_back(data);
     }
 
     /// (Indicate that you want to) send a message to everybody else.
@@ -362,56 +304,107 @@ impl SharedChannel {
     }
 }
 
-/// Represents a private channel.
+/// Set whenever the task it belongs to is woken up.
+struct WakeFlag(AtomicBool);
+
+impl Wake for WakeFlag {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+struct Runnable<'a> {
+    fut: Pin<Box<dyn Future<Output = ()> + Send + 'a>>,
+    woken: Arc<WakeFlag>,
+}
+
+/// A minimal single-threaded executor.
 ///
-/// This can be seen as a separate "namespace" for `SharedChannel`.
-pub struct PrivateChannel {
-    header: MessageHeader,
-    to: Participant,
-    comms: Comms,
+/// Tasks are only ever polled when the executor is ticked, which happens when
+/// the protocol is poked. This means that the executor never blocks, and never
+/// spawns threads, so it behaves the same natively and on wasm.
+struct Executor<'a> {
+    tasks: Mutex<Vec<Runnable<'a>>>,
 }
 
-impl PrivateChannel {
-    fn new(comms: Comms, from: Participant, to: Participant) -> Self {
+impl<'a> Executor<'a> {
+    fn new() -> Self {
         Self {
-            comms,
-            to,
-            header: MessageHeader::new(ChannelTag::root_private(from, to)),
+            tasks: Mutex::new(Vec::new()),
         }
     }
 
-    pub fn child(&self, i: u64) -> Self {
-        Self {
-            comms: self.comms.clone(),
-            to: self.to,
-            header: self.header.child(i),
-        }
+    fn spawn<T: Send + 'a>(&self, fut: impl Future<Output = T> + Send + 'a) -> Task<T> {
+        let slot = Arc::new(Mutex::new(TaskSlot {
+            value: None,
+            waker: None,
+        }));
+        let task_slot = slot.clone();
+        let fut = async move {
+            let value = fut.await;
+            let mut slot = task_slot.lock().unwrap();
+            slot.value = Some(value);
+            if let Some(waker) = slot.waker.take() {
+                waker.wake();
+            }
+        };
+        self.tasks.lock().unwrap().push(Runnable {
+            fut: Box::pin(fut),
+            woken: Arc::new(WakeFlag(AtomicBool::new(true))),
+        });
+        Task { slot }
     }
 
-    pub fn next_waitpoint(&mut self) -> Waitpoint {
-        self.header.next_waitpoint()
+    /// Poll every task which has been woken up since it was last polled.
+    ///
+    /// This returns false if there was no such task, i.e. if every task is asleep.
+    fn try_tick(&self) -> bool {
+        // Tasks may spawn other tasks while being polled, so we can't hold the lock.
+        let mut tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+        let mut ticked = false;
+        tasks.retain_mut(|task| {
+            if !task.woken.0.swap(false, Ordering::SeqCst) {
+                return true;
+            }
+            ticked = true;
+            let waker = Waker::from(task.woken.clone());
+            let mut cx = task::Context::from_waker(&waker);
+            task.fut.as_mut().poll(&mut cx).is_pending()
+        });
+        let mut tasks_lock = self.tasks.lock().unwrap();
+        tasks.append(&mut tasks_lock);
+        *tasks_lock = tasks;
+        ticked
     }
+}
 
-    pub async fn send<T: Serialize>(&self, waitpoint: Waitpoint, data: &T) {
-        self.comms
-            .send_private(self.header.with_waitpoint(waitpoint), self.to, data)
-            .await
-    }
+struct TaskSlot<T> {
+    value: Option<T>,
+    waker: Option<Waker>,
+}
 
-    pub async fn recv<T: DeserializeOwned>(
-        &self,
-        waitpoint: Waitpoint,
-    ) -> Result<T, ProtocolError> {
-        loop {
-            let (from, data) = self
-                .comms
-                .recv(self.header.with_waitpoint(waitpoint))
-                .await?;
-            if from != self.to {
-                future::yield_now().await;
-                continue;
+/// A handle to a spawned task, which can be awaited to get its result.
+///
+/// Unlike the handles of most executors, dropping this does not cancel the task.
+pub struct Task<T> {
+    slot: Arc<Mutex<TaskSlot<T>>>,
+}
+
+impl<T> Future for Task<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<T> {
+        let mut slot = self.slot.lock().unwrap();
+        match slot.value.take() {
+            Some(value) => Poll::Ready(value),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
             }
-            return Ok(data);
         }
     }
 }
@@ -442,28 +435,16 @@ impl<'a> Context<'a> {
         SharedChannel::new(self.comms.clone())
     }
 
-    /// Return *the* private channel for this context.
-    ///
-    /// To get other channels, use the successor function.
-    pub fn private_channel(&self, from: Participant, to: Participant) -> PrivateChannel {
-        PrivateChannel::new(self.comms.clone(), from, to)
-    }
-
     /// Spawn a new task on the executor.
     pub fn spawn<T: Send + 'a>(&self, fut: impl Future<Output = T> + Send + 'a) -> Task<T> {
         self.executor.spawn(fut)
     }
-
-    /// Run a future to completion on this executor.
-    pub async fn run<T>(&self, fut: impl Future<Output = T>) -> T {
-        self.executor.run(fut).await
-    }
 }
 
 /// This struct will convert a future into a protocol.
 struct ProtocolExecutor<'a, T> {
     ctx: Context<'a>,
-    ret_r: channel::Receiver<Result<T, ProtocolError>>,
+    ret: Task<Result<T, ProtocolError>>,
     done: bool,
 }
 
@@ -472,20 +453,11 @@ impl<'a, T: Send + 'a> ProtocolExecutor<'a, T> {
         ctx: Context<'a>,
         fut: impl Future<Output = Result<T, ProtocolError>> + Send + 'a,
     ) -> Self {
-        let (ret_s, ret_r) = smol::channel::bounded(1);
-        let fut = async move {
-            let res = fut.await;
-            ret_s
-                .send(res)
-                .await
-                .expect("failed to return result of protocol");
-        };
-
-        ctx.executor.spawn(fut).detach();
+        let ret = ctx.spawn(fut);
 
         Self {
             ctx,
-            ret_r,
+            ret,
             done: false,
         }
     }
@@ -498,52 +470,29 @@ impl<'a, T> Protocol for ProtocolExecutor<'a, T> {
         if self.done {
             return Ok(Action::Wait);
         }
-        let fut_return = async {
-            let out = self
-                .ret_r
-                .recv()
-                .await
-                .expect("failed to retrieve return value");
-            Ok::<_, ProtocolError>(Action::Return(out?))
-        };
-        let fut_outgoing = async {
-            let action: Action<Self::Output> = match self.ctx.comms.outgoing().await {
-                Message::Many(m) => Action::SendMany(m),
-                Message::Private(to, m) => Action::SendPrivate(to, m),
-            };
-            Ok::<_, ProtocolError>(action)
-        };
-        // This is a future which will keep ticking the executor until
-        // all tasks are asleep, at which point it will indicate that nothing
-        // is left to do, by returning `Action::Wait`.
-        let fut_wait = async {
-            while self.ctx.executor.try_tick() {
-                // Now that we've ticked, we want to yield to allow the executor to poll
-                // the other action sources.
-                future::yield_now().await;
+        loop {
+            // The priority is first to send all outgoing messages before returning,
+            // otherwise we might deadlock other people, by preventing them from receiving the output.
+            if let Some(message) = self.ctx.comms.outgoing() {
+                return Ok(match message {
+                    Message::Many(m) => Action::SendMany(m),
+                    Message::Private(to, m) => Action::SendPrivate(to, m),
+                });
             }
-            Ok(Action::Wait)
-        };
-        // The priority is first to send all outgoing messages before returning,
-        // otherwise we might deadlock other people, by preventing them from receiving the output.
-        let action = block_on(
-            self.ctx
-                .run(future::or(fut_outgoing, future::or(fut_return, fut_wait))),
-        );
-        match action {
-            Err(_) => self.done = true,
-            Ok(Action::Return(_)) => self.done = true,
-            _ => {}
-        };
-        action
+            if let Some(out) = self.ret.slot.lock().unwrap().value.take() {
+                self.done = true;
+                return out.map(Action::Return);
+            }
+            // Keep ticking the executor until all tasks are asleep, at which
+            // point nothing is left to do until a new message arrives.
+            if !self.ctx.executor.try_tick() {
+                return Ok(Action::Wait);
+            }
+        }
     }
 
     fn message(&mut self, from: Participant, data: MessageData) {
-        block_on(
-            self.ctx
-                .executor
-                .run(self.ctx.comms.push_message(from, data)),
-        );
+        self.ctx.comms.push_message(from, data);
     }
 }
 
@@ -12,6 +12,7 @@ use std::{collections::HashMap, error, fmt};
 use ::serde::{Deserialize, Serialize};
 
 use crate::compat::CSCurve;
+use crate::tecdsa_cli_srv::session::ConfigError;
 
 use std::str::FromStr;
 
@@ -20,15 +21,58 @@ use std::str::FromStr;
 pub enum ProtocolError {
     /// Some assertion in the protocol failed.
     AssertionFailed(String),
+    /// A participant sent a share which failed to verify.
+    BadShare(Participant, String),
+    /// A participant sent a proof which failed to verify.
+    BadProof(Participant, String),
+    /// A step needed a message from a participant, which was never received.
+    ///
+    /// `wait` names the wait point, i.e. the field of the received messages.
+    MissingMessage {
+        from: Participant,
+        wait: &'static str,
+    },
+    /// A step was called before the steps it depends on, so the named part of
+    /// its state was never computed.
+    OutOfOrder(&'static str),
+    /// A message or state did not have the expected shape.
+    Malformed(String),
+    /// The share or secret this party provided does not fit its role in the session.
+    KeyMismatch(&'static str),
+    /// The session config is not valid for the flow.
+    Config(ConfigError),
     /// Some generic error happened.
     Other(Box<dyn error::Error + Send + Sync>),
 }
 
+impl ProtocolError {
+    /// The participant at fault, if the error can be blamed on one.
+    pub fn culprit(&self) -> Option<Participant> {
+        match self {
+            ProtocolError::BadShare(p, _)
+            | ProtocolError::BadProof(p, _)
+            | ProtocolError::MissingMessage { from: p, .. } => Some(*p),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for ProtocolError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ProtocolError::Other(e) => write!(f, "{}", e),
             ProtocolError::AssertionFailed(e) => write!(f, "assertion failed {}", e),
+            ProtocolError::BadShare(p, e) => write!(f, "bad share from {:?}: {}", p, e),
+            ProtocolError::BadProof(p, e) => write!(f, "bad proof from {:?}: {}", p, e),
+            ProtocolError::MissingMessage { from, wait } => {
+                write!(f, "missing message from {:?} at {}", from, wait)
+            }
+            ProtocolError::OutOfOrder(what) => {
+                write!(f, "step called out of order: {} was not computed", what)
+            }
+            ProtocolError::Malformed(e) => write!(f, "malformed payload: {}", e),
+            ProtocolError::KeyMismatch(e) => write!(f, "key mismatch: {}", e),
+            ProtocolError::Config(e) => write!(f, "invalid session config: {}", e),
         }
     }
 }
@@ -0,0 +1,171 @@
+use std::ops::Add;
+
+use ecdsa::der::{MaxOverhead, MaxSize};
+use ecdsa::{Signature, SignatureSize};
+use elliptic_curve::generic_array::ArrayLength;
+use elliptic_curve::{point::DecompressPoint, scalar::IsHigh, Field, FieldBytesSize, PrimeField};
+
+use super::{FullSignature, RecoverableSignature};
+use crate::compat::{self, CSCurve};
+use crate::protocol::ProtocolError;
+
+fn encoding_error(msg: &str) -> ProtocolError {
+    ProtocolError::Malformed(msg.to_string())
+}
+
+fn scalar_len<C: CSCurve>() -> usize {
+    <C::Scalar as PrimeField>::Repr::default().as_ref().len()
+}
+
+/// Parse a scalar of a signature, rejecting non canonical encodings and zero.
+fn parse_scalar<C: CSCurve>(bytes: &[u8]) -> Result<C::Scalar, ProtocolError> {
+    let mut repr = <C::Scalar as PrimeField>::Repr::default();
+    repr.as_mut().copy_from_slice(bytes);
+
+    let scalar: Option<C::Scalar> = C::Scalar::from_repr(repr).into();
+    match scalar {
+        Some(scalar) if !bool::from(scalar.is_zero()) => Ok(scalar),
+        Some(_) => Err(encoding_error("signature scalars cannot be zero")),
+        None => Err(encoding_error("signature scalar is out of range")),
+    }
+}
+
+impl<C: CSCurve> FullSignature<C> {
+    /// The first scalar of the signature, derived from the nonce point.
+    pub fn r(&self) -> C::Scalar {
+        compat::x_coordinate::<C>(&self.big_r)
+    }
+
+    /// The second scalar of the signature, negated if needed to be in the lower range.
+    pub fn normalized_s(&self) -> C::Scalar {
+        if self.s.is_high().into() {
+            -self.s
+        } else {
+            self.s
+        }
+    }
+
+    /// Build a signature from its two scalars, rejecting zero and a high `s`.
+    ///
+    /// The encodings without a recovery id don't say which of the two points
+    /// with the x-coordinate `r` is the nonce point, so the one with an even
+    /// y-coordinate is picked. Verification only depends on the x-coordinate.
+    pub fn from_scalars(r: C::Scalar, s: C::Scalar) -> Result<Self, ProtocolError>
+    where
+        C::AffinePoint: DecompressPoint<C>,
+    {
+        Self::from_scalars_with_parity(r, s, false)
+    }
+
+    fn from_scalars_with_parity(
+        r: C::Scalar,
+        s: C::Scalar,
+        y_is_odd: bool,
+    ) -> Result<Self, ProtocolError>
+    where
+        C::AffinePoint: DecompressPoint<C>,
+    {
+        if r.is_zero().into() || s.is_zero().into() {
+            return Err(encoding_error("signature scalars cannot be zero"));
+        }
+        if s.is_high().into() {
+            return Err(encoding_error("signature s must be in the lower range"));
+        }
+
+        let big_r: Option<C::AffinePoint> =
+            C::AffinePoint::decompress(&r.to_repr(), u8::from(y_is_odd).into()).into();
+        let big_r = big_r.ok_or_else(|| encoding_error("r is not the x-coordinate of a point"))?;
+
+        Ok(FullSignature { big_r, s })
+    }
+
+    /// The compact `r || s` encoding, 64 bytes for 256 bit curves.
+    pub fn to_compact(&self) -> Vec<u8> {
+        let mut out = self.r().to_repr().as_ref().to_vec();
+        out.extend_from_slice(self.normalized_s().to_repr().as_ref());
+        out
+    }
+
+    /// Parse the compact `r || s` encoding.
+    pub fn from_compact(bytes: &[u8]) -> Result<Self, ProtocolError>
+    where
+        C::AffinePoint: DecompressPoint<C>,
+    {
+        if bytes.len() != 2 * scalar_len::<C>() {
+            return Err(encoding_error("compact signature has the wrong length"));
+        }
+        let (r, s) = bytes.split_at(scalar_len::<C>());
+        Self::from_scalars(parse_scalar::<C>(r)?, parse_scalar::<C>(s)?)
+    }
+
+    /// The 65 byte `r || s || v` encoding, `v` being 27 or 28.
+    ///
+    /// Since `s` is normalized, the recovery id is that of the point matching it.
+    pub fn to_recoverable_bytes(&self, was_flipped: bool) -> Result<Vec<u8>, ProtocolError> {
+        RecoverableSignature::from_full_signature(self, was_flipped).to_bytes()
+    }
+
+    /// Parse the 65 byte `r || s || v` encoding, with `v` being 0, 1, 27 or 28.
+    ///
+    /// Unlike the other encodings, this keeps the exact nonce point.
+    pub fn from_recoverable_bytes(bytes: &[u8]) -> Result<Self, ProtocolError>
+    where
+        C::AffinePoint: DecompressPoint<C>,
+    {
+        let (v, rs) = bytes
+            .split_last()
+            .ok_or_else(|| encoding_error("recoverable signature is empty"))?;
+        let y_is_odd = match *v {
+            0 | 27 => false,
+            1 | 28 => true,
+            _ => return Err(encoding_error("invalid recovery id")),
+        };
+        if rs.len() != 2 * scalar_len::<C>() {
+            return Err(encoding_error("recoverable signature has the wrong length"));
+        }
+        let (r, s) = rs.split_at(scalar_len::<C>());
+        Self::from_scalars_with_parity(parse_scalar::<C>(r)?, parse_scalar::<C>(s)?, y_is_odd)
+    }
+
+    /// Convert to a RustCrypto signature, for use with `ecdsa` verifiers.
+    pub fn to_ecdsa(&self) -> Result<Signature<C>, ProtocolError>
+    where
+        SignatureSize<C>: ArrayLength<u8>,
+    {
+        Signature::from_scalars(self.r().to_repr(), self.normalized_s().to_repr())
+            .map_err(|_| encoding_error("signature scalars cannot be zero"))
+    }
+
+    /// Convert from a RustCrypto signature, rejecting a high `s`.
+    pub fn from_ecdsa(sig: &Signature<C>) -> Result<Self, ProtocolError>
+    where
+        C::AffinePoint: DecompressPoint<C>,
+        SignatureSize<C>: ArrayLength<u8>,
+    {
+        let (r, s) = sig.split_scalars();
+        Self::from_scalars(*r, *s)
+    }
+
+    /// The ASN.1 DER encoding, as used by X.509 and Bitcoin.
+    pub fn to_der(&self) -> Result<Vec<u8>, ProtocolError>
+    where
+        SignatureSize<C>: ArrayLength<u8>,
+        MaxSize<C>: ArrayLength<u8>,
+        <FieldBytesSize<C> as Add>::Output: Add<MaxOverhead> + ArrayLength<u8>,
+    {
+        Ok(self.to_ecdsa()?.to_der().as_bytes().to_vec())
+    }
+
+    /// Parse a strict ASN.1 DER encoding.
+    pub fn from_der(bytes: &[u8]) -> Result<Self, ProtocolError>
+    where
+        C::AffinePoint: DecompressPoint<C>,
+        SignatureSize<C>: ArrayLength<u8>,
+        MaxSize<C>: ArrayLength<u8>,
+        <FieldBytesSize<C> as Add>::Output: Add<MaxOverhead> + ArrayLength<u8>,
+    {
+        let sig =
+            Signature::<C>::from_der(bytes).map_err(|_| encoding_error("invalid DER signature"))?;
+        Self::from_ecdsa(&sig)
+    }
+}
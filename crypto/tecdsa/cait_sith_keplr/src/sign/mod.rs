@@ -1,9 +1,12 @@
+mod encoding;
+mod recoverable;
 mod sign;
 mod state;
 mod state_2;
 pub mod steps;
 pub mod steps_2;
 
+pub use recoverable::*;
 pub use sign::*;
 pub use state::*;
 pub use state_2::*;
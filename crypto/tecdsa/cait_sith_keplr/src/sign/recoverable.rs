@@ -0,0 +1,116 @@
+use elliptic_curve::{
+    group::Curve,
+    ops::Invert,
+    point::{AffineCoordinates, DecompressPoint},
+    Field, Group, PrimeField,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{FullSignature, SignOutput};
+use crate::compat::{self, CSCurve};
+use crate::protocol::ProtocolError;
+
+/// The offset Ethereum adds to the recovery id in legacy and personal signatures.
+const ETHEREUM_V_OFFSET: u8 = 27;
+
+/// An ECDSA signature along with a recovery id, from which the public key can be recovered.
+///
+/// The lowest bit of the recovery id is the parity of the y-coordinate of the
+/// nonce point matching the low `s`, and the second bit is set if the x-coordinate
+/// of that point was reduced to get `r`. The latter essentially never happens for
+/// secp256k1, and Ethereum has no encoding for it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: Serialize + for<'a> Deserialize<'a>")]
+pub struct RecoverableSignature<C: CSCurve> {
+    pub r: C::Scalar,
+    pub s: C::Scalar,
+    pub recovery_id: u8,
+}
+
+impl<C: CSCurve> RecoverableSignature<C> {
+    /// Build a recoverable signature, `was_flipped` telling if `s` was negated to make it low.
+    pub fn from_full_signature(sig: &FullSignature<C>, was_flipped: bool) -> Self {
+        let r = compat::x_coordinate::<C>(&sig.big_r);
+
+        // Negating s is the same as negating the nonce point, flipping its parity.
+        let y_is_odd = bool::from(sig.big_r.y_is_odd()) ^ was_flipped;
+        let x_is_reduced = Option::<C::Scalar>::from(C::Scalar::from_repr(sig.big_r.x())).is_none();
+
+        RecoverableSignature {
+            r,
+            s: sig.s,
+            recovery_id: u8::from(y_is_odd) | (u8::from(x_is_reduced) << 1),
+        }
+    }
+
+    /// The 65 byte `r || s || v` encoding, with `v` being 27 or 28, as used by Ethereum.
+    ///
+    /// Fails if the x-coordinate of the nonce point was reduced, which `v` can't encode.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProtocolError> {
+        if self.recovery_id > 1 {
+            return Err(ProtocolError::Malformed(
+                "cannot encode a reduced x-coordinate".to_string(),
+            ));
+        }
+
+        let mut out = Vec::with_capacity(65);
+        out.extend_from_slice(&self.r.to_repr());
+        out.extend_from_slice(&self.s.to_repr());
+        out.push(self.recovery_id + ETHEREUM_V_OFFSET);
+        Ok(out)
+    }
+
+    /// The `v` value of an EIP-155 transaction signature for `chain_id`.
+    pub fn eip155_v(&self, chain_id: u64) -> u64 {
+        u64::from(self.recovery_id) + 35 + 2 * chain_id
+    }
+
+    /// Recover the public key which made this signature over `msg_hash`.
+    pub fn recover(&self, msg_hash: &C::Scalar) -> Result<C::AffinePoint, ProtocolError>
+    where
+        C::AffinePoint: DecompressPoint<C>,
+    {
+        if self.recovery_id > 1 {
+            return Err(ProtocolError::Malformed(
+                "cannot recover from a reduced x-coordinate".to_string(),
+            ));
+        }
+        if self.r.is_zero().into() || self.s.is_zero().into() {
+            return Err(ProtocolError::AssertionFailed(
+                "signature scalars cannot be zero".to_string(),
+            ));
+        }
+
+        let y_is_odd = (self.recovery_id & 1).into();
+        let big_r: Option<C::AffinePoint> =
+            C::AffinePoint::decompress(&self.r.to_repr(), y_is_odd).into();
+        let big_r = big_r.ok_or_else(|| {
+            ProtocolError::AssertionFailed("r is not the x-coordinate of a point".to_string())
+        })?;
+
+        // X = r^-1 * (s * R - m * G)
+        let r_inv = self.r.invert_vartime().unwrap();
+        let public_key = (C::ProjectivePoint::from(big_r) * self.s
+            - C::ProjectivePoint::generator() * msg_hash)
+            * r_inv;
+
+        Ok(public_key.to_affine())
+    }
+
+    /// Check that this signature over `msg_hash` recovers to `public_key`.
+    #[must_use]
+    pub fn verify(&self, public_key: &C::AffinePoint, msg_hash: &C::Scalar) -> bool
+    where
+        C::AffinePoint: DecompressPoint<C>,
+    {
+        self.recover(msg_hash)
+            .is_ok_and(|recovered| recovered == *public_key)
+    }
+}
+
+impl<C: CSCurve> SignOutput<C> {
+    /// The signature along with its recovery id.
+    pub fn recoverable(&self) -> RecoverableSignature<C> {
+        RecoverableSignature::from_full_signature(&self.sig, self.is_high)
+    }
+}
@@ -3,8 +3,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::{
-    compat::CSCurve, participants::ParticipantList, protocol::Participant, KeygenOutput,
-    PresignOutput,
+    compat::CSCurve, crypto::Digest, participants::ParticipantList, protocol::Participant,
+    KeygenOutput, PresignOutput,
 };
 
 pub struct SignState<C: CSCurve> {
@@ -42,12 +42,20 @@ type From = Participant;
 ")]
 pub struct RcvdSignMessages<C: CSCurve> {
     pub wait_0: HashMap<From, ScalarPrimitive<C>>,
+    pub wait_0_confirmations: HashMap<From, Digest>,
 }
 
 impl<C: CSCurve> RcvdSignMessages<C> {
     pub fn new() -> RcvdSignMessages<C> {
         RcvdSignMessages {
             wait_0: HashMap::new(),
+            wait_0_confirmations: HashMap::new(),
         }
     }
+
+    /// Merge messages received from another participant into this buffer.
+    pub fn extend(&mut self, other: RcvdSignMessages<C>) {
+        self.wait_0.extend(other.wait_0);
+        self.wait_0_confirmations.extend(other.wait_0_confirmations);
+    }
 }
@@ -1,22 +1,64 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::compat::CSCurve;
+use crate::crypto::Digest;
 use crate::protocol::Participant;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// The state of a participant between the steps of signing.
+///
+/// The partial signature is wiped when the state is dropped, and never shows up
+/// in `Debug` output.
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(bound = "C::Scalar: Serialize + for<'d> Deserialize<'d>")]
 pub struct SignState2<C: CSCurve> {
     pub threshold: usize,
     pub participants: Vec<Participant>,
+    /// Binds the signature to a single session and key.
+    pub session_id: Vec<u8>,
     pub s_i: Option<C::Scalar>,
+    #[serde(default)]
+    pub msg_hash: Option<C::Scalar>,
+    pub confirmation: Option<Digest>,
 }
 
 impl<'a, C: CSCurve> SignState2<C> {
-    pub fn new(participants: Vec<Participant>, threshold: usize) -> Self {
+    pub fn new(participants: Vec<Participant>, threshold: usize, session_id: Vec<u8>) -> Self {
         Self {
             participants,
             threshold,
+            session_id,
             s_i: None,
+            msg_hash: None,
+            confirmation: None,
         }
     }
 }
+
+impl<C: CSCurve> fmt::Debug for SignState2<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignState2")
+            .field("threshold", &self.threshold)
+            .field("participants", &self.participants)
+            .field("session_id", &self.session_id)
+            .field("msg_hash", &self.msg_hash)
+            .field("confirmation", &self.confirmation)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: CSCurve> Zeroize for SignState2<C> {
+    fn zeroize(&mut self) {
+        self.s_i.zeroize();
+    }
+}
+
+impl<C: CSCurve> Drop for SignState2<C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<C: CSCurve> ZeroizeOnDrop for SignState2<C> {}
@@ -1,10 +1,12 @@
-use elliptic_curve::{scalar::IsHigh, ScalarPrimitive};
+use elliptic_curve::{scalar::IsHigh, Group, ScalarPrimitive};
 use serde::{Deserialize, Serialize};
 use subtle::ConditionallySelectable;
+use zeroize::Zeroize;
 
 use super::{state::RcvdSignMessages, FullSignature, SignOutput, SignState2};
 use crate::{
-    compat::{self, CSCurve},
+    compat::{self, CSCurve, SerializablePoint},
+    crypto::hash,
     participants::ParticipantList,
     protocol::{Participant, ProtocolError},
     PresignOutput,
@@ -21,13 +23,7 @@ pub fn step_1<C: CSCurve>(
     msg_hash: C::Scalar,
     presig_0: PresignOutput<C>,
 ) -> Result<Step1Output2<C>, ProtocolError> {
-    let participants = ParticipantList::new(&state.participants).ok_or_else(|| {
-        ProtocolError::Other(
-            "participant list cannot contain duplicates"
-                .to_string()
-                .into(),
-        )
-    })?;
+    let participants = ParticipantList::from_state(&state.participants)?;
 
     // Spec 1.1
     let lambda = participants.lagrange::<C>(me);
@@ -41,64 +37,50 @@ pub fn step_1<C: CSCurve>(
     let s_i: C::Scalar = msg_hash * k_i + r * sigma_i;
 
     state.s_i = Some(s_i);
+    state.msg_hash = Some(msg_hash);
+
+    // Everybody must be signing the same message, with the same presignature,
+    // in the same session.
+    let msg_hash: ScalarPrimitive<C> = msg_hash.into();
+    state.confirmation = Some(hash(&(
+        &state.session_id,
+        &participants,
+        &msg_hash,
+        SerializablePoint::<C>(presig_0.big_r),
+    )));
 
     let out = Step1Output2 { s_i: s_i.into() };
 
     Ok(out)
 }
 
-pub fn step_2_2<C: CSCurve>(
-    state: &mut SignState2<C>,
-    msgs: &RcvdSignMessages<C>,
-    me: Participant,
-    presig: PresignOutput<C>,
-) -> Result<FullSignature<C>, ProtocolError> {
-    let participant_list = ParticipantList::new(&state.participants).ok_or_else(|| {
-        ProtocolError::Other(
-            "participant list cannot contain duplicates"
-                .to_string()
-                .into(),
-        )
-    })?;
-
-    let mut s = state.s_i.unwrap();
-
-    // // Spec 2.1 + 2.2
-    for p in participant_list.others(me) {
-        let s_j = *msgs.wait_0.get(&p).unwrap();
-        s += C::Scalar::from(s_j);
-    }
-
-    // Spec 2.3
-    // Optionally, normalize s
-    s.conditional_assign(&(-s), s.is_high());
-    let sig: FullSignature<C> = FullSignature {
-        big_r: presig.big_r,
-        s,
-    };
-
-    Ok(sig)
-}
-
 pub fn step_2<C: CSCurve>(
     state: &mut SignState2<C>,
     msgs: &RcvdSignMessages<C>,
     me: Participant,
     presig: PresignOutput<C>,
 ) -> Result<SignOutput<C>, ProtocolError> {
-    let participant_list = ParticipantList::new(&state.participants).ok_or_else(|| {
-        ProtocolError::Other(
-            "participant list cannot contain duplicates"
-                .to_string()
-                .into(),
-        )
-    })?;
+    let participant_list = ParticipantList::from_state(&state.participants)?;
 
-    let mut s = state.s_i.unwrap();
+    let mut s = state.s_i.ok_or(ProtocolError::OutOfOrder("s_i"))?;
+
+    check_confirmations(state, msgs, &participant_list, me)?;
+
+    if presig.commitments.is_empty() {
+        return Err(ProtocolError::Malformed(
+            "presignature has no commitments".to_string(),
+        ));
+    }
 
     for p in participant_list.others(me) {
-        let s_j = *msgs.wait_0.get(&p).unwrap();
-        s += C::Scalar::from(s_j);
+        let s_j = *msgs.wait_0.get(&p).ok_or(ProtocolError::MissingMessage {
+            from: p,
+            wait: "wait_0",
+        })?;
+        let s_j = C::Scalar::from(s_j);
+        verify_partial_signature(state, &participant_list, p, s_j, &presig)?;
+
+        s += s_j;
     }
 
     let was_flipped = s.is_high();
@@ -114,5 +96,68 @@ pub fn step_2<C: CSCurve>(
         is_high: bool::from(was_flipped),
     };
 
+    state.s_i.zeroize();
+
     Ok(out)
 }
+
+/// Check that every other participant confirmed the same signing as us.
+fn check_confirmations<C: CSCurve>(
+    state: &SignState2<C>,
+    msgs: &RcvdSignMessages<C>,
+    participant_list: &ParticipantList,
+    me: Participant,
+) -> Result<(), ProtocolError> {
+    let my_confirmation = state
+        .confirmation
+        .ok_or(ProtocolError::OutOfOrder("confirmation"))?;
+
+    for p in participant_list.others(me) {
+        let their_confirmation =
+            msgs.wait_0_confirmations
+                .get(&p)
+                .ok_or(ProtocolError::MissingMessage {
+                    from: p,
+                    wait: "wait_0_confirmations",
+                })?;
+        if *their_confirmation != my_confirmation {
+            return Err(ProtocolError::BadShare(
+                p,
+                "confirmation did not match expectation".to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the partial signature `s_j` of participant `p` against its presign commitments.
+fn verify_partial_signature<C: CSCurve>(
+    state: &SignState2<C>,
+    participant_list: &ParticipantList,
+    p: Participant,
+    s_j: C::Scalar,
+    presig: &PresignOutput<C>,
+) -> Result<(), ProtocolError> {
+    let commitment = presig.commitments.get(&p).ok_or_else(|| {
+        ProtocolError::Malformed(format!("presignature has no commitment for {p:?}"))
+    })?;
+
+    let msg_hash = state
+        .msg_hash
+        .ok_or(ProtocolError::OutOfOrder("msg_hash"))?;
+    let lambda = participant_list.lagrange::<C>(p);
+    let r = compat::x_coordinate::<C>(&presig.big_r);
+
+    // Spec 1.3, in the exponent
+    let big_k: C::ProjectivePoint = commitment.big_k.into();
+    let big_sigma: C::ProjectivePoint = commitment.big_sigma.into();
+    if C::ProjectivePoint::generator() * s_j != (big_k * msg_hash + big_sigma * r) * lambda {
+        return Err(ProtocolError::BadShare(
+            p,
+            "partial signature failed to verify".to_string(),
+        ));
+    }
+
+    Ok(())
+}
@@ -184,28 +184,19 @@ pub fn keygen_2<C: CSCurve>(
     {
         // p0
         let (sk_0, pk_0) = step_5::<C>(&mut st_0, &msgs_0, p_0, None).unwrap();
-        let out_0: KeygenOutput<C> = KeygenOutput {
-            public_key: pk_0,
-            private_share: sk_0,
-        };
+        let out_0: KeygenOutput<C> = KeygenOutput::new(sk_0, pk_0, st_0.big_f.clone().unwrap());
 
         result.push((p_0, out_0));
 
         // p1
         let (sk_1, pk_1) = step_5(&mut st_1, &msgs_1, p_1, None).unwrap();
-        let out_1: KeygenOutput<C> = KeygenOutput {
-            public_key: pk_1,
-            private_share: sk_1,
-        };
+        let out_1: KeygenOutput<C> = KeygenOutput::new(sk_1, pk_1, st_1.big_f.clone().unwrap());
 
         result.push((p_1, out_1));
 
         // p2
         let (sk_2, pk_2) = step_5(&mut st_2, &msgs_2, p_2, None).unwrap();
-        let out_2: KeygenOutput<C> = KeygenOutput {
-            public_key: pk_2,
-            private_share: sk_2,
-        };
+        let out_2: KeygenOutput<C> = KeygenOutput::new(sk_2, pk_2, st_2.big_f.clone().unwrap());
 
         result.push((p_2, out_2));
     }
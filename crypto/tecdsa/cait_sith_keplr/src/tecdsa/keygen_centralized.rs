@@ -33,13 +33,12 @@ pub fn keygen_centralized<C: CSCurve>(
     let big_x_projective = C::ProjectivePoint::generator() * f.evaluate_zero();
     let big_x_affine = big_x_projective.to_affine();
 
+    let big_f = f.commit();
+
     let mut results = Vec::with_capacity(participants.len());
     for &p in participants {
         let share_i = f.evaluate(&p.scalar::<C>());
-        let key_out = KeygenOutput {
-            private_share: share_i,
-            public_key: big_x_affine,
-        };
+        let key_out = KeygenOutput::new(share_i, big_x_affine, big_f.clone());
         results.push(key_out);
     }
 
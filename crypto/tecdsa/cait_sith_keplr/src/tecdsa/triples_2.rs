@@ -1,4 +1,4 @@
-use rand_core::OsRng;
+use rand_core::{OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -74,8 +74,13 @@ pub fn generate_triples_3<C: CSCurve>(
         ));
     }
 
-    let mut st_0: TriplesState<C> = TriplesState::new(&participants, threshold, 3);
-    let mut st_1: TriplesState<C> = TriplesState::new(&participants, threshold, 3);
+    // Both parties run here, so any fresh id binds them to this run.
+    let mut session_id = vec![0u8; 32];
+    OsRng.fill_bytes(&mut session_id);
+
+    let mut st_0: TriplesState<C> =
+        TriplesState::new(&participants, threshold, 3, session_id.clone());
+    let mut st_1: TriplesState<C> = TriplesState::new(&participants, threshold, 3, session_id);
 
     let mut msgs_0: RcvdTriplesMessages<C> = RcvdTriplesMessages::new();
     let mut msgs_1: RcvdTriplesMessages<C> = RcvdTriplesMessages::new();
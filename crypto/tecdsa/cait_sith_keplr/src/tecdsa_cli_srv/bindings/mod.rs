@@ -0,0 +1,193 @@
+//! What the wasm, N-API and C bindings share, over secp256k1.
+//!
+//! Each binding takes the session config as JSON, and reports failures as one
+//! of the [`ErrorKind`]s, in the terms of its own language. The step bindings
+//! also hand the caller a [`StepState`] between steps, which keeps the messages
+//! received so far along with the step state.
+//!
+//! The server side of the step API lives in [`server`], so the N-API bindings
+//! only have to convert to and from JS values.
+pub mod server;
+
+use std::fmt;
+
+use k256::{Scalar, Secp256k1};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::envelope::{Envelope, ProtocolKind};
+use super::protocols::RcvdMessages;
+use super::session::{ConfigError, SessionConfig};
+use crate::protocol::{Participant, ProtocolError};
+
+/// The kind of failure a binding reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The config was not valid JSON, or did not describe a valid session.
+    Config,
+    /// An input could not be decoded.
+    Decode,
+    /// The protocol failed.
+    Protocol,
+    /// Another party sent a bad share.
+    BadShare,
+    /// Another party sent a proof which failed to verify.
+    BadProof,
+    /// A message from another party never arrived.
+    MissingMessage,
+    /// A step was run before the steps it depends on.
+    OutOfOrder,
+}
+
+impl ErrorKind {
+    /// The name of the error, e.g. `BadShareError`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::Config => "ConfigError",
+            ErrorKind::Decode => "DecodeError",
+            ErrorKind::Protocol => "ProtocolError",
+            ErrorKind::BadShare => "BadShareError",
+            ErrorKind::BadProof => "BadProofError",
+            ErrorKind::MissingMessage => "MissingMessageError",
+            ErrorKind::OutOfOrder => "OutOfOrderError",
+        }
+    }
+}
+
+impl From<&ProtocolError> for ErrorKind {
+    fn from(e: &ProtocolError) -> Self {
+        match e {
+            ProtocolError::BadShare(_, _) => ErrorKind::BadShare,
+            ProtocolError::BadProof(_, _) => ErrorKind::BadProof,
+            ProtocolError::MissingMessage { .. } => ErrorKind::MissingMessage,
+            ProtocolError::OutOfOrder(_) => ErrorKind::OutOfOrder,
+            ProtocolError::Malformed(_) => ErrorKind::Decode,
+            ProtocolError::Config(_) => ErrorKind::Config,
+            _ => ErrorKind::Protocol,
+        }
+    }
+}
+
+/// An error from a binding, converted into the error of its language at the boundary.
+#[derive(Debug)]
+pub enum BindingError {
+    Config(String),
+    Decode(String),
+    Protocol(ProtocolError),
+}
+
+impl BindingError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            BindingError::Config(_) => ErrorKind::Config,
+            BindingError::Decode(_) => ErrorKind::Decode,
+            BindingError::Protocol(e) => e.into(),
+        }
+    }
+
+    /// The party to blame for this error, if any.
+    pub fn culprit(&self) -> Option<Participant> {
+        match self {
+            BindingError::Protocol(e) => e.culprit(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindingError::Config(e) => write!(f, "invalid config: {}", e),
+            BindingError::Decode(e) => write!(f, "failed to decode: {}", e),
+            BindingError::Protocol(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<ProtocolError> for BindingError {
+    fn from(e: ProtocolError) -> Self {
+        BindingError::Protocol(e)
+    }
+}
+
+/// Parse the session config of the step bindings, and check that it describes a valid session.
+///
+/// The step bindings only run between two parties, the client and the server,
+/// as each step takes the messages of a single sender without naming it.
+pub fn parse_config(config: &str) -> Result<SessionConfig, BindingError> {
+    let config = parse_session_config(config)?;
+    config.validate_two_party()?;
+    Ok(config)
+}
+
+/// Parse a session config with any number of participants, and check that it describes a valid session.
+pub fn parse_session_config(config: &str) -> Result<SessionConfig, BindingError> {
+    let config: SessionConfig =
+        serde_json::from_str(config).map_err(|e| BindingError::Config(e.to_string()))?;
+    config.validate()?;
+    Ok(config)
+}
+
+/// The envelope of the messages sent by `step` of the `kind` flow.
+pub fn envelope(config: &SessionConfig, kind: ProtocolKind, step: u32) -> Envelope {
+    Envelope::new::<Secp256k1>(kind, step, &config.session_id)
+}
+
+pub fn encode<T: Serialize>(val: &T) -> Vec<u8> {
+    crate::serde::encode(val)
+}
+
+pub fn decode<T: DeserializeOwned>(what: &str, bytes: &[u8]) -> Result<T, BindingError> {
+    crate::serde::decode(bytes).map_err(|e| BindingError::Decode(format!("{}: {}", what, e)))
+}
+
+/// Interpret the 32 byte message hash, as computed by [`crate::payload`].
+pub fn decode_msg_hash(msg_hash: &[u8]) -> Result<Scalar, BindingError> {
+    let msg_hash: &[u8; 32] = msg_hash
+        .try_into()
+        .map_err(|_| BindingError::Decode("message hash must be 32 bytes".to_string()))?;
+    Ok(crate::payload::hash_to_scalar(msg_hash))
+}
+
+/// The state handed to the caller between steps: the step state, and the messages received so far.
+#[derive(Serialize, Deserialize)]
+pub struct StepState<S, M> {
+    pub st: S,
+    pub rcvd: M,
+}
+
+impl<S: DeserializeOwned, M: RcvdMessages> StepState<S, M> {
+    /// Decode the state of the previous step, and add the newly received `msgs` to it.
+    ///
+    /// `msgs` must have been sent by `step` of the `kind` flow.
+    pub fn receive(
+        config: &SessionConfig,
+        kind: ProtocolKind,
+        step: u32,
+        state: &[u8],
+        msgs: &[u8],
+    ) -> Result<Self, BindingError> {
+        let mut state: Self = decode("state", state)?;
+        receive(config, kind, step, &mut state.rcvd, msgs)?;
+        Ok(state)
+    }
+}
+
+/// Open the `msgs` sent by `step` of the `kind` flow, and add them to `rcvd` as sent by the other party.
+pub fn receive<M: RcvdMessages>(
+    config: &SessionConfig,
+    kind: ProtocolKind,
+    step: u32,
+    rcvd: &mut M,
+    msgs: &[u8],
+) -> Result<(), BindingError> {
+    let msgs: M = envelope(config, kind, step)
+        .open(msgs)
+        .map_err(|e| BindingError::Decode(format!("msgs: {}", e)))?;
+    let [from] = config.others()[..] else {
+        return Err(
+            ProtocolError::from(ConfigError::NotTwoParties(config.participants.len())).into(),
+        );
+    };
+    rcvd.merge_from(from, msgs);
+    Ok(())
+}
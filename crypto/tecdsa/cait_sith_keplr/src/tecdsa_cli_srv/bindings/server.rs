@@ -0,0 +1,284 @@
+//! The server side of the step API, over bytes.
+//!
+//! Each step takes the session config as JSON, naming the client and the
+//! server as its two participants, and the state and messages as the bytes a
+//! previous step returned. The messages a step takes are those the
+//! client sent in the round before it, except for triples, where the server
+//! runs behind the client: server step N takes the messages of client step N,
+//! including the first, which `triples_step_1` takes.
+use std::collections::HashMap;
+
+use k256::Secp256k1;
+use serde::Serialize;
+
+use super::{
+    decode, decode_msg_hash, encode, envelope, parse_config, receive, BindingError, StepState,
+};
+use crate::keyshare::{KeyshareState2, RcvdKeyshareMessages};
+use crate::presign::{PresignState2, RcvdPresignMessages2};
+use crate::protocol::Participant;
+use crate::sign::{RcvdSignMessages, SignOutput, SignState2};
+use crate::tecdsa::triples_2::TriplesGenManyResult;
+use crate::tecdsa_cli_srv::envelope::Envelope;
+use crate::tecdsa_cli_srv::envelope::ProtocolKind::{Keygen, Presign, Sign, Triples};
+use crate::tecdsa_cli_srv::srv_keygen::KeygenServer;
+use crate::tecdsa_cli_srv::srv_presign::PresignServer;
+use crate::tecdsa_cli_srv::srv_sign::SignServer;
+use crate::tecdsa_cli_srv::srv_triples::TriplesServer2;
+use crate::triples::{RcvdTriplesMessages, TriplesState};
+use crate::{KeygenOutput, PresignOutput};
+
+type KeygenServerState = StepState<KeyshareState2<Secp256k1>, RcvdKeyshareMessages<Secp256k1>>;
+type TriplesServerState = StepState<TriplesState<Secp256k1>, RcvdTriplesMessages<Secp256k1>>;
+type PresignServerState = StepState<PresignState2<Secp256k1>, RcvdPresignMessages2<Secp256k1>>;
+type SignServerState = StepState<SignState2<Secp256k1>, RcvdSignMessages<Secp256k1>>;
+
+/// The output of a step: the state to pass to the next step, and the messages to send.
+pub struct StepBytes {
+    pub state: Vec<u8>,
+    pub msgs: Vec<u8>,
+}
+
+impl StepBytes {
+    fn new<S: Serialize, M: Serialize>(state: &S, envelope: &Envelope, msgs: &M) -> Self {
+        StepBytes {
+            state: encode(state),
+            msgs: envelope.seal(msgs),
+        }
+    }
+}
+
+/// The output of a step sending a different message to each participant.
+pub struct PrivateStepBytes {
+    pub state: Vec<u8>,
+    /// The messages to send, keyed by recipient.
+    pub msgs: HashMap<Participant, Vec<u8>>,
+}
+
+impl PrivateStepBytes {
+    fn new<S: Serialize, M: Serialize>(
+        state: &S,
+        envelope: &Envelope,
+        msgs: &HashMap<Participant, M>,
+    ) -> Self {
+        PrivateStepBytes {
+            state: encode(state),
+            msgs: msgs.iter().map(|(p, m)| (*p, envelope.seal(m))).collect(),
+        }
+    }
+}
+
+pub fn keygen_step_1(config: &str) -> Result<StepBytes, BindingError> {
+    let config = parse_config(config)?;
+    let out = KeygenServer::srv_keygen_step_1::<Secp256k1>(&config)?;
+    let state = KeygenServerState {
+        st: out.st_1,
+        rcvd: RcvdKeyshareMessages::new(),
+    };
+    Ok(StepBytes::new(
+        &state,
+        &envelope(&config, Keygen, 1),
+        &out.msgs_0,
+    ))
+}
+
+/// The steps in between all take the state and messages, and return the same.
+macro_rules! impl_keygen_steps {
+    ($($step:ident => $srv_step:ident, $n:literal),* $(,)?) => {
+        $(
+            pub fn $step(config: &str, state: &[u8], msgs: &[u8]) -> Result<StepBytes, BindingError> {
+                let config = parse_config(config)?;
+                let KeygenServerState { st, rcvd } =
+                    KeygenServerState::receive(&config, Keygen, $n - 1, state, msgs)?;
+                let out = KeygenServer::$srv_step(&config, st, &rcvd)?;
+                Ok(StepBytes::new(
+                    &KeygenServerState { st: out.st_1, rcvd },
+                    &envelope(&config, Keygen, $n),
+                    &out.msgs_0,
+                ))
+            }
+        )*
+    };
+}
+
+impl_keygen_steps!(
+    keygen_step_2 => srv_keygen_step_2, 2,
+    keygen_step_3 => srv_keygen_step_3, 3,
+);
+
+/// Returns private messages, keyed by recipient.
+pub fn keygen_step_4(
+    config: &str,
+    state: &[u8],
+    msgs: &[u8],
+) -> Result<PrivateStepBytes, BindingError> {
+    let config = parse_config(config)?;
+    let KeygenServerState { st, rcvd } =
+        KeygenServerState::receive(&config, Keygen, 3, state, msgs)?;
+    let out = KeygenServer::srv_keygen_step_4(&config, st, &rcvd)?;
+    Ok(PrivateStepBytes::new(
+        &KeygenServerState { st: out.st_1, rcvd },
+        &envelope(&config, Keygen, 4),
+        &out.msgs_0,
+    ))
+}
+
+/// Returns the encoded `KeygenOutput`.
+pub fn keygen_step_5(config: &str, state: &[u8], msgs: &[u8]) -> Result<Vec<u8>, BindingError> {
+    let config = parse_config(config)?;
+    let KeygenServerState { st, rcvd } =
+        KeygenServerState::receive(&config, Keygen, 4, state, msgs)?;
+    let keygen_1 = KeygenServer::srv_keygen_step_5(&config, st, &rcvd)?;
+    Ok(encode(&keygen_1))
+}
+
+/// Takes the messages of the client's first step, which the second step needs.
+pub fn triples_step_1(config: &str, msgs: &[u8]) -> Result<StepBytes, BindingError> {
+    let config = parse_config(config)?;
+    let mut rcvd = RcvdTriplesMessages::new();
+    receive(&config, Triples, 1, &mut rcvd, msgs)?;
+    let out = TriplesServer2::triples_step_1::<Secp256k1>(&config)?;
+    Ok(StepBytes::new(
+        &TriplesServerState { st: out.st_1, rcvd },
+        &envelope(&config, Triples, 1),
+        &out.msgs_0,
+    ))
+}
+
+/// The steps in between all take the state and messages, and return the same.
+macro_rules! impl_triples_steps {
+    ($($step:ident => $n:literal),* $(,)?) => {
+        $(
+            pub fn $step(config: &str, state: &[u8], msgs: &[u8]) -> Result<StepBytes, BindingError> {
+                let config = parse_config(config)?;
+                let TriplesServerState { st, rcvd } =
+                    TriplesServerState::receive(&config, Triples, $n, state, msgs)?;
+                let out = TriplesServer2::$step(&config, st, rcvd.clone())?;
+                Ok(StepBytes::new(
+                    &TriplesServerState { st: out.st_1, rcvd },
+                    &envelope(&config, Triples, $n),
+                    &out.msgs_0,
+                ))
+            }
+        )*
+    };
+}
+
+impl_triples_steps!(
+    triples_step_2 => 2,
+    triples_step_3 => 3,
+    triples_step_4 => 4,
+    triples_step_5 => 5,
+    triples_step_6 => 6,
+    triples_step_7 => 7,
+    triples_step_8 => 8,
+    triples_step_9 => 9,
+    triples_step_10 => 10,
+);
+
+/// Takes the messages of the client's tenth step again, as the client has no eleventh to send.
+///
+/// Returns the encoded `TriplesGenManyResult`, holding the two triples a presignature uses.
+pub fn triples_step_11(config: &str, state: &[u8], msgs: &[u8]) -> Result<Vec<u8>, BindingError> {
+    let config = parse_config(config)?;
+    let TriplesServerState { st, rcvd } =
+        TriplesServerState::receive(&config, Triples, 10, state, msgs)?;
+    let triples_1 = TriplesServer2::triples_step_11(&config, st, rcvd)?;
+    Ok(encode(&triples_1))
+}
+
+/// Start presigning with the output of `triples_step_11` and `keygen_step_5`.
+pub fn presign_step_1(
+    config: &str,
+    triples: &[u8],
+    keygen: &[u8],
+) -> Result<StepBytes, BindingError> {
+    let config = parse_config(config)?;
+    let triples_1: TriplesGenManyResult<Secp256k1> = decode("triples", triples)?;
+    let keygen_1: KeygenOutput<Secp256k1> = decode("keygen", keygen)?;
+
+    let (Some(pub_0), Some(pub_1)) = (triples_1.pub_v.first(), triples_1.pub_v.get(1)) else {
+        return Err(BindingError::Decode(
+            "triples: expected two triples".to_string(),
+        ));
+    };
+    let (Some(share_0), Some(share_1)) = (triples_1.share_v.first(), triples_1.share_v.get(1))
+    else {
+        return Err(BindingError::Decode(
+            "triples: expected two shares".to_string(),
+        ));
+    };
+
+    let (st_1, msgs_0) = PresignServer::presign_step_1(
+        &config,
+        pub_0.clone(),
+        pub_1.clone(),
+        share_0.clone(),
+        share_1.clone(),
+        keygen_1,
+    )?;
+    let state = PresignServerState {
+        st: st_1,
+        rcvd: RcvdPresignMessages2::new(),
+    };
+    Ok(StepBytes::new(
+        &state,
+        &envelope(&config, Presign, 1),
+        &msgs_0,
+    ))
+}
+
+/// The step itself needs no messages, but those of the first round are kept for `presign_step_3`.
+pub fn presign_step_2(config: &str, state: &[u8], msgs: &[u8]) -> Result<StepBytes, BindingError> {
+    let config = parse_config(config)?;
+    let PresignServerState { st, rcvd } =
+        PresignServerState::receive(&config, Presign, 1, state, msgs)?;
+    let (st_1, msgs_0) = PresignServer::presign_step_2(&config, st)?;
+    Ok(StepBytes::new(
+        &PresignServerState { st: st_1, rcvd },
+        &envelope(&config, Presign, 2),
+        &msgs_0,
+    ))
+}
+
+/// Returns the encoded `PresignOutput`.
+pub fn presign_step_3(config: &str, state: &[u8], msgs: &[u8]) -> Result<Vec<u8>, BindingError> {
+    let config = parse_config(config)?;
+    let PresignServerState { st, rcvd } =
+        PresignServerState::receive(&config, Presign, 2, state, msgs)?;
+    let presig_1 = PresignServer::presign_step_3(&config, st, &rcvd)?;
+    Ok(encode(&presig_1))
+}
+
+/// Start signing the 32 byte `msg_hash` with the output of `presign_step_3`.
+pub fn sign_step_1(
+    config: &str,
+    msg_hash: &[u8],
+    presign: &[u8],
+) -> Result<StepBytes, BindingError> {
+    let config = parse_config(config)?;
+    let msg_hash = decode_msg_hash(msg_hash)?;
+    let presig_1: PresignOutput<Secp256k1> = decode("presign", presign)?;
+    let out = SignServer::sign_step_1(&config, msg_hash, presig_1)?;
+    let state = SignServerState {
+        st: out.st_1,
+        rcvd: RcvdSignMessages::new(),
+    };
+    Ok(StepBytes::new(
+        &state,
+        &envelope(&config, Sign, 1),
+        &out.msgs_0,
+    ))
+}
+
+pub fn sign_step_2(
+    config: &str,
+    state: &[u8],
+    msgs: &[u8],
+    presign: &[u8],
+) -> Result<SignOutput<Secp256k1>, BindingError> {
+    let config = parse_config(config)?;
+    let SignServerState { mut st, rcvd } = SignServerState::receive(&config, Sign, 1, state, msgs)?;
+    let presig_1: PresignOutput<Secp256k1> = decode("presign", presign)?;
+    Ok(SignServer::sign_step_2(&config, &mut st, &rcvd, presig_1)?)
+}
@@ -1,13 +1,13 @@
 use std::collections::HashMap;
 
 use elliptic_curve::CurveArithmetic;
-use k256::Secp256k1;
 use serde::{Deserialize, Serialize};
 
 use crate::keyshare::steps_2;
 use crate::keyshare::RcvdKeyshareMessages;
 use crate::keyshare::{CentralizedKeygenOutput, KeyshareState2};
 use crate::protocol::ProtocolError;
+use crate::tecdsa_cli_srv::session::{ConfigError, SessionConfig};
 use crate::{
     protocol::Participant,
     tecdsa::{keygen_centralized::combine_shares, keygen_centralized::keygen_centralized},
@@ -24,6 +24,18 @@ pub struct ClientKeygenStepOutput<C: CSCurve> {
     pub msgs_1: RcvdKeyshareMessages<C>,
 }
 
+/// Output of a step sending private messages, keyed by recipient.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(bound = "
+    C::Scalar: Serialize + for<'a> Deserialize<'a>,
+    C::AffinePoint: Serialize + for<'a> Deserialize<'a>
+")]
+pub struct ClientKeygenPrivateStepOutput<C: CSCurve> {
+    pub st_0: KeyshareState2<C>,
+    #[serde(with = "crate::protocol::participant_serde")]
+    pub msgs_1: HashMap<Participant, RcvdKeyshareMessages<C>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound = "C::Scalar: Serialize + for<'a> Deserialize<'a>")]
 pub struct KeyCombineInput<C: CSCurve> {
@@ -33,112 +45,290 @@ pub struct KeyCombineInput<C: CSCurve> {
 pub struct KeygenClient {}
 
 impl KeygenClient {
-    pub fn cli_keygen_centralized() -> Result<CentralizedKeygenOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let threshold = 2;
+    pub fn cli_keygen_centralized<C: CSCurve>(
+        config: &SessionConfig,
+    ) -> Result<CentralizedKeygenOutput<C>, ProtocolError> {
+        config.validate()?;
 
-        keygen_centralized::<Secp256k1>(&participants, threshold)
+        keygen_centralized::<C>(&config.participants, config.threshold)
     }
 
-    pub fn cli_combine_shares(
-        key_combine_input: KeyCombineInput<Secp256k1>,
-    ) -> Result<<Secp256k1 as CurveArithmetic>::Scalar, ProtocolError> {
-        let shares: Vec<(Participant, <Secp256k1 as CurveArithmetic>::Scalar)> =
+    pub fn cli_combine_shares<C: CSCurve>(
+        key_combine_input: KeyCombineInput<C>,
+    ) -> Result<<C as CurveArithmetic>::Scalar, ProtocolError> {
+        let shares: Vec<(Participant, <C as CurveArithmetic>::Scalar)> =
             key_combine_input.shares.into_iter().collect();
 
-        combine_shares::<Secp256k1>(&shares)
+        combine_shares::<C>(&shares)
     }
 
     // interface
-    pub fn cli_keygen_step_1() -> Result<ClientKeygenStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let threshold = 2;
-
-        let mut st_0 = KeyshareState2::<Secp256k1>::new(participants.clone(), threshold);
+    pub fn cli_keygen_step_1<C: CSCurve>(
+        config: &SessionConfig,
+    ) -> Result<ClientKeygenStepOutput<C>, ProtocolError> {
+        config.validate()?;
+
+        let mut st_0 = KeyshareState2::<C>::new(
+            config.participants.clone(),
+            config.threshold,
+            config.transcript_id(),
+        );
 
-        let mut msgs_1 = RcvdKeyshareMessages::<Secp256k1>::new();
+        let mut msgs_1 = RcvdKeyshareMessages::<C>::new();
 
-        steps_2::step_1::<Secp256k1>(&mut st_0, None).unwrap();
+        steps_2::step_1::<C>(&mut st_0)?;
 
-        let p_0 = st_0.participants[0];
+        let p_0 = config.me;
 
-        // Send {p0} => p1
-        msgs_1.wait_0.insert(p_0, st_0.commitment.unwrap());
+        // Send {p0} => others
+        msgs_1.wait_0.insert(
+            p_0,
+            st_0.commitment
+                .ok_or(ProtocolError::OutOfOrder("commitment"))?,
+        );
 
         Ok(ClientKeygenStepOutput { st_0, msgs_1 })
     }
 
-    pub fn cli_keygen_step_2(
-        mut st_0: KeyshareState2<Secp256k1>,
-        msgs_0: &RcvdKeyshareMessages<Secp256k1>,
-    ) -> Result<ClientKeygenStepOutput<Secp256k1>, ProtocolError> {
-        let p_0 = st_0.participants[0];
+    pub fn cli_keygen_step_2<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: KeyshareState2<C>,
+        msgs_0: &RcvdKeyshareMessages<C>,
+    ) -> Result<ClientKeygenStepOutput<C>, ProtocolError> {
+        let p_0 = config.me;
 
-        steps_2::step_2::<Secp256k1>(&mut st_0, &msgs_0, p_0).unwrap();
+        steps_2::step_2::<C>(&mut st_0, &msgs_0, p_0)?;
 
-        let mut msgs_1 = RcvdKeyshareMessages::<Secp256k1>::new();
+        let mut msgs_1 = RcvdKeyshareMessages::<C>::new();
 
-        msgs_1.wait_1.insert(p_0, st_0.confirmation.unwrap());
+        msgs_1.wait_1.insert(
+            p_0,
+            st_0.confirmation
+                .ok_or(ProtocolError::OutOfOrder("confirmation"))?,
+        );
 
         Ok(ClientKeygenStepOutput { st_0, msgs_1 })
     }
 
-    pub fn cli_keygen_step_3(
-        mut st_0: KeyshareState2<Secp256k1>,
-        _msgs_0: &RcvdKeyshareMessages<Secp256k1>,
-    ) -> Result<ClientKeygenStepOutput<Secp256k1>, ProtocolError> {
-        let p_0 = st_0.participants[0];
+    pub fn cli_keygen_step_3<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: KeyshareState2<C>,
+        _msgs_0: &RcvdKeyshareMessages<C>,
+    ) -> Result<ClientKeygenStepOutput<C>, ProtocolError> {
+        let p_0 = config.me;
 
-        steps_2::step_3::<Secp256k1>(&mut st_0, p_0).unwrap();
+        steps_2::step_3::<C>(&mut st_0, p_0)?;
 
-        let mut msgs_1 = RcvdKeyshareMessages::<Secp256k1>::new();
+        let mut msgs_1 = RcvdKeyshareMessages::<C>::new();
 
-        // 0 => 1
+        // 0 => others
         msgs_1.wait_2.insert(
             p_0,
             (
-                st_0.big_f.clone().unwrap(),
-                st_0.randomizer.clone().unwrap(),
-                st_0.phi_proof.clone().unwrap(),
+                st_0.big_f
+                    .clone()
+                    .ok_or(ProtocolError::OutOfOrder("big_f"))?,
+                st_0.randomizer
+                    .clone()
+                    .ok_or(ProtocolError::OutOfOrder("randomizer"))?,
+                st_0.phi_proof
+                    .clone()
+                    .ok_or(ProtocolError::OutOfOrder("phi_proof"))?,
             ),
         );
 
         Ok(ClientKeygenStepOutput { st_0, msgs_1 })
     }
 
-    pub fn cli_keygen_step_4(
-        mut st_0: KeyshareState2<Secp256k1>,
-        _msgs_0: &RcvdKeyshareMessages<Secp256k1>,
-    ) -> Result<ClientKeygenStepOutput<Secp256k1>, ProtocolError> {
-        let p_0 = st_0.participants[0];
-        let p_1 = st_0.participants[1];
+    pub fn cli_keygen_step_4<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: KeyshareState2<C>,
+        _msgs_0: &RcvdKeyshareMessages<C>,
+    ) -> Result<ClientKeygenPrivateStepOutput<C>, ProtocolError> {
+        let p_0 = config.me;
+
+        let out_0 = steps_2::step_4(&mut st_0, p_0)?;
+
+        let mut msgs_1 = HashMap::new();
 
-        let out_0 = steps_2::step_4(&mut st_0, p_0).unwrap();
+        // 0 => {p} (send private)
+        for p in config.others() {
+            let x_i_j_for_p = out_0.x_i_js.get(&p).ok_or_else(|| {
+                ProtocolError::AssertionFailed(format!("no share computed for {p:?}"))
+            })?;
+
+            let mut msgs_p = RcvdKeyshareMessages::<C>::new();
+            msgs_p.wait_3.insert(p_0, x_i_j_for_p.clone());
+            msgs_1.insert(p, msgs_p);
+        }
+
+        Ok(ClientKeygenPrivateStepOutput { st_0, msgs_1 })
+    }
 
-        let mut msgs_1 = RcvdKeyshareMessages::<Secp256k1>::new();
+    pub fn cli_keygen_step_5<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: KeyshareState2<C>,
+        msgs_0: &RcvdKeyshareMessages<C>,
+    ) -> Result<KeygenOutput<C>, ProtocolError> {
+        let p_0 = config.me;
+
+        let (sk_0, pk_0) = steps_2::step_5(&mut st_0, &msgs_0, p_0, None)?;
+
+        let keygen_0: KeygenOutput<C> = KeygenOutput::new(
+            sk_0,
+            pk_0,
+            st_0.big_f
+                .take()
+                .ok_or(ProtocolError::OutOfOrder("big_f"))?,
+        );
+
+        Ok(keygen_0)
+    }
+
+    /// Start refreshing the shares of an existing key.
+    ///
+    /// The refresh then continues with `cli_keygen_step_2` through `cli_keygen_step_4`,
+    /// and finishes with [`Self::cli_refresh_step_5`]. The participants and threshold
+    /// must be the same as those the key was generated with.
+    pub fn cli_refresh_step_1<C: CSCurve>(
+        config: &SessionConfig,
+        keygen_0: KeygenOutput<C>,
+    ) -> Result<ClientKeygenStepOutput<C>, ProtocolError> {
+        config.validate()?;
+
+        let p_0 = config.me;
+
+        let mut st_0 = KeyshareState2::<C>::new(
+            config.participants.clone(),
+            config.threshold,
+            config.transcript_id(),
+        );
 
-        // 0 => {1} (send private)
-        let x_i_j_for_1 = out_0.x_i_js.get(&p_1).unwrap();
-        msgs_1.wait_3.insert(p_0, x_i_j_for_1.clone());
+        steps_2::refresh_step_1::<C>(&mut st_0, p_0, keygen_0.private_share)?;
+
+        let mut msgs_1 = RcvdKeyshareMessages::<C>::new();
+
+        msgs_1.wait_0.insert(
+            p_0,
+            st_0.commitment
+                .ok_or(ProtocolError::OutOfOrder("commitment"))?,
+        );
 
         Ok(ClientKeygenStepOutput { st_0, msgs_1 })
     }
 
-    pub fn cli_keygen_step_5(
-        mut st_0: KeyshareState2<Secp256k1>,
-        msgs_0: &RcvdKeyshareMessages<Secp256k1>,
-    ) -> Result<KeygenOutput<Secp256k1>, ProtocolError> {
-        let p_0 = st_0.participants[0];
+    /// Finish a refresh, checking that the key is still `public_key`.
+    pub fn cli_refresh_step_5<C: CSCurve>(
+        config: &SessionConfig,
+        st_0: KeyshareState2<C>,
+        msgs_0: &RcvdKeyshareMessages<C>,
+        public_key: C::AffinePoint,
+    ) -> Result<KeygenOutput<C>, ProtocolError> {
+        Self::cli_reshare_step_5(config, st_0, msgs_0, public_key)
+    }
 
-        let (sk_0, pk_0) = steps_2::step_5(&mut st_0, &msgs_0, p_0, None).unwrap();
+    /// Start resharing an existing key to the participants and threshold of `config`.
+    ///
+    /// The key was shared among `old_participants` with `old_threshold`. Parties
+    /// joining with no old share pass `None`, and receive a fresh share.
+    /// The reshare then continues with `cli_keygen_step_2` through `cli_keygen_step_4`,
+    /// and finishes with [`Self::cli_reshare_step_5`].
+    pub fn cli_reshare_step_1<C: CSCurve>(
+        config: &SessionConfig,
+        old_participants: &[Participant],
+        old_threshold: usize,
+        my_share: Option<C::Scalar>,
+    ) -> Result<ClientKeygenStepOutput<C>, ProtocolError> {
+        config.validate()?;
+
+        let old_subset = config.old_subset(old_participants, old_threshold)?;
+
+        let p_0 = config.me;
+
+        let mut st_0 = KeyshareState2::<C>::new(
+            config.participants.clone(),
+            config.threshold,
+            config.transcript_id(),
+        );
 
-        let keygen_0: KeygenOutput<Secp256k1> = KeygenOutput {
-            public_key: pk_0,
-            private_share: sk_0,
-        };
+        steps_2::reshare_step_1::<C>(&mut st_0, &old_subset, p_0, my_share)?;
 
-        Ok(keygen_0)
+        let mut msgs_1 = RcvdKeyshareMessages::<C>::new();
+
+        msgs_1.wait_0.insert(
+            p_0,
+            st_0.commitment
+                .ok_or(ProtocolError::OutOfOrder("commitment"))?,
+        );
+
+        Ok(ClientKeygenStepOutput { st_0, msgs_1 })
+    }
+
+    /// Finish a reshare, checking that the key is still `public_key`.
+    pub fn cli_reshare_step_5<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: KeyshareState2<C>,
+        msgs_0: &RcvdKeyshareMessages<C>,
+        public_key: C::AffinePoint,
+    ) -> Result<KeygenOutput<C>, ProtocolError> {
+        let p_0 = config.me;
+
+        let (private_share, public_key) =
+            steps_2::step_5(&mut st_0, msgs_0, p_0, Some(public_key.into()))?;
+
+        Ok(KeygenOutput::new(
+            private_share,
+            public_key,
+            st_0.big_f
+                .take()
+                .ok_or(ProtocolError::OutOfOrder("big_f"))?,
+        ))
+    }
+
+    /// Start importing an existing private key, known only to `importer`.
+    ///
+    /// The importing party passes its key as `secret`, everyone else passes `None`.
+    /// The import then continues with `cli_keygen_step_2` through `cli_keygen_step_4`,
+    /// and finishes with [`Self::cli_import_step_5`].
+    pub fn cli_import_step_1<C: CSCurve>(
+        config: &SessionConfig,
+        importer: Participant,
+        secret: Option<C::Scalar>,
+    ) -> Result<ClientKeygenStepOutput<C>, ProtocolError> {
+        config.validate()?;
+
+        if !config.participant_list()?.contains(importer) {
+            return Err(ConfigError::NotAParticipant(importer).into());
+        }
+
+        let p_0 = config.me;
+
+        let mut st_0 = KeyshareState2::<C>::new(
+            config.participants.clone(),
+            config.threshold,
+            config.transcript_id(),
+        );
+
+        steps_2::import_step_1::<C>(&mut st_0, p_0, importer, secret)?;
+
+        let mut msgs_1 = RcvdKeyshareMessages::<C>::new();
+
+        msgs_1.wait_0.insert(
+            p_0,
+            st_0.commitment
+                .ok_or(ProtocolError::OutOfOrder("commitment"))?,
+        );
+
+        Ok(ClientKeygenStepOutput { st_0, msgs_1 })
+    }
+
+    /// Finish an import, checking that the shared key matches `public_key`.
+    pub fn cli_import_step_5<C: CSCurve>(
+        config: &SessionConfig,
+        st_0: KeyshareState2<C>,
+        msgs_0: &RcvdKeyshareMessages<C>,
+        public_key: C::AffinePoint,
+    ) -> Result<KeygenOutput<C>, ProtocolError> {
+        Self::cli_reshare_step_5(config, st_0, msgs_0, public_key)
     }
 }
@@ -1,10 +1,9 @@
 use elliptic_curve::ScalarPrimitive;
-use k256::Secp256k1;
 use serde::{Deserialize, Serialize};
 
 use crate::presign::{steps_2, PresignState2, RcvdPresignMessages2};
-use crate::protocol::Participant;
 use crate::protocol::ProtocolError;
+use crate::tecdsa_cli_srv::session::SessionConfig;
 use crate::triples::{TriplePub, TripleShare};
 use crate::{CSCurve, KeygenOutput, PresignOutput};
 
@@ -21,33 +20,15 @@ pub struct PresignStepOutput<C: CSCurve> {
 }
 
 impl PresignClient {
-    pub fn presign_step_1(
-        pub_0: TriplePub<Secp256k1>,
-        pub_1: TriplePub<Secp256k1>,
-        share_0_0: TripleShare<Secp256k1>,
-        share_1_0: TripleShare<Secp256k1>,
-        keygen_0: KeygenOutput<Secp256k1>,
-    ) -> Result<PresignStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let threshold = 2;
-
-        if participants.len() < 2 {
-            return Err(ProtocolError::Other(
-                format!(
-                    "participant count cannot be < 2, found: {}",
-                    participants.len()
-                )
-                .into(),
-            ));
-        };
-
-        // Spec 1.1
-        if threshold > participants.len() {
-            return Err(ProtocolError::Other(
-                "threshold must be <= participant count".to_string().into(),
-            ));
-        }
+    pub fn presign_step_1<C: CSCurve>(
+        config: &SessionConfig,
+        pub_0: TriplePub<C>,
+        pub_1: TriplePub<C>,
+        share_0_0: TripleShare<C>,
+        share_1_0: TripleShare<C>,
+        keygen_0: KeygenOutput<C>,
+    ) -> Result<PresignStepOutput<C>, ProtocolError> {
+        config.validate_with_key()?;
 
         // NOTE: We omit the check that the new participant set was present for
         // the triple generation, because presumably they need to have been present
@@ -63,8 +44,9 @@ impl PresignClient {
         // }
 
         let mut state_0 = PresignState2::new(
-            participants.clone(),
-            threshold,
+            config.participants.clone(),
+            config.threshold,
+            config.transcript_id(),
             keygen_0,
             pub_0,
             pub_1,
@@ -72,13 +54,26 @@ impl PresignClient {
             share_1_0,
         );
 
-        let mut msgs_1 = RcvdPresignMessages2::<Secp256k1>::new();
+        let mut msgs_1 = RcvdPresignMessages2::<C>::new();
 
-        let p_0 = participants.get(0).unwrap();
+        let p_0 = config.me;
 
-        let out = steps_2::step_1(&mut state_0, *p_0, *p_0).unwrap();
+        let out = steps_2::step_1(&mut state_0, p_0, p_0)?;
 
-        msgs_1.wait_0.insert(*p_0, out.kd_i.into());
+        msgs_1.wait_0.insert(p_0, out.kd_i.into());
+        msgs_1.wait_0_commitments.insert(
+            p_0,
+            state_0
+                .share_commitments
+                .clone()
+                .ok_or(ProtocolError::OutOfOrder("share_commitments"))?,
+        );
+        msgs_1.wait_0_confirmations.insert(
+            p_0,
+            state_0
+                .confirmation
+                .ok_or(ProtocolError::OutOfOrder("confirmation"))?,
+        );
 
         Ok(PresignStepOutput {
             st_0: state_0,
@@ -86,33 +81,31 @@ impl PresignClient {
         })
     }
 
-    pub fn presign_step_2(
-        mut st_0: PresignState2<Secp256k1>,
-    ) -> Result<PresignStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    pub fn presign_step_2<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: PresignState2<C>,
+    ) -> Result<PresignStepOutput<C>, ProtocolError> {
+        let p_0 = config.me;
 
-        let p_0 = participants.get(0).unwrap();
+        let mut msgs_1 = RcvdPresignMessages2::<C>::new();
 
-        let mut msgs_1 = RcvdPresignMessages2::<Secp256k1>::new();
+        let out = steps_2::step_2(&mut st_0)?;
 
-        let out = steps_2::step_2(&mut st_0).unwrap();
-
-        let ka_i: ScalarPrimitive<Secp256k1> = out.ka_i.into();
-        let xb_i: ScalarPrimitive<Secp256k1> = out.xb_i.into();
-        msgs_1.wait_1.insert(*p_0, (ka_i, xb_i));
+        let ka_i: ScalarPrimitive<C> = out.ka_i.into();
+        let xb_i: ScalarPrimitive<C> = out.xb_i.into();
+        msgs_1.wait_1.insert(p_0, (ka_i, xb_i));
 
         Ok(PresignStepOutput { st_0, msgs_1 })
     }
 
-    pub fn presign_step_3(
-        mut st_0: PresignState2<Secp256k1>,
-        msgs_0: &RcvdPresignMessages2<Secp256k1>,
-    ) -> Result<PresignOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_0 = participants.get(0).unwrap();
+    pub fn presign_step_3<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: PresignState2<C>,
+        msgs_0: &RcvdPresignMessages2<C>,
+    ) -> Result<PresignOutput<C>, ProtocolError> {
+        let p_0 = config.me;
 
-        let out = steps_2::step_3(&mut st_0, &msgs_0, *p_0).unwrap();
+        let out = steps_2::step_3(&mut st_0, msgs_0, p_0)?;
 
         Ok(out)
     }
@@ -1,11 +1,10 @@
 use elliptic_curve::CurveArithmetic;
-use k256::Secp256k1;
 use serde::{Deserialize, Serialize};
 
-use crate::protocol::Participant;
 use crate::protocol::ProtocolError;
 use crate::sign::SignOutput;
 use crate::sign::{steps_2, RcvdSignMessages, SignState2};
+use crate::tecdsa_cli_srv::session::SessionConfig;
 use crate::{CSCurve, PresignOutput};
 
 pub struct SignClient {}
@@ -21,57 +20,44 @@ pub struct ClientSignStepOutput<C: CSCurve> {
 }
 
 impl SignClient {
-    pub fn sign_step_1(
-        msg_hash: <Secp256k1 as CurveArithmetic>::Scalar,
-        presig_0: PresignOutput<Secp256k1>,
-    ) -> Result<ClientSignStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    pub fn sign_step_1<C: CSCurve>(
+        config: &SessionConfig,
+        msg_hash: <C as CurveArithmetic>::Scalar,
+        presig_0: PresignOutput<C>,
+    ) -> Result<ClientSignStepOutput<C>, ProtocolError> {
+        config.validate_with_key()?;
 
-        let threshold = 2;
+        let p_0 = config.me;
 
-        if participants.len() < 2 {
-            return Err(ProtocolError::Other(
-                format!(
-                    "participant count cannot be < 2, found: {}",
-                    participants.len()
-                )
-                .into(),
-            ));
-        };
+        let mut st_0 = SignState2::<C>::new(
+            config.participants.clone(),
+            config.threshold,
+            config.transcript_id(),
+        );
 
-        let p_0 = participants.get(0).unwrap();
+        let mut msgs_1 = RcvdSignMessages::<C>::new();
 
-        let mut st_0 = SignState2::<Secp256k1>::new(participants.clone(), threshold);
+        let out = steps_2::step_1(&mut st_0, p_0, msg_hash, presig_0)?;
 
-        let mut msgs_1 = RcvdSignMessages::<Secp256k1>::new();
-
-        let out = steps_2::step_1(&mut st_0, *p_0, msg_hash, presig_0).unwrap();
-
-        msgs_1.wait_0.insert(*p_0, out.s_i);
+        msgs_1.wait_0.insert(p_0, out.s_i);
+        msgs_1.wait_0_confirmations.insert(
+            p_0,
+            st_0.confirmation
+                .ok_or(ProtocolError::OutOfOrder("confirmation"))?,
+        );
 
         Ok(ClientSignStepOutput { st_0, msgs_1 })
     }
 
-    pub fn sign_step_2(
-        st_0: &mut SignState2<Secp256k1>,
-        msgs_0: &RcvdSignMessages<Secp256k1>,
-        presig_0: PresignOutput<Secp256k1>,
-    ) -> Result<SignOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        if participants.len() < 2 {
-            return Err(ProtocolError::Other(
-                format!(
-                    "participant count cannot be < 2, found: {}",
-                    participants.len()
-                )
-                .into(),
-            ));
-        };
-
-        let p_0 = participants.get(0).unwrap();
+    pub fn sign_step_2<C: CSCurve>(
+        config: &SessionConfig,
+        st_0: &mut SignState2<C>,
+        msgs_0: &RcvdSignMessages<C>,
+        presig_0: PresignOutput<C>,
+    ) -> Result<SignOutput<C>, ProtocolError> {
+        let p_0 = config.me;
 
-        let out = steps_2::step_2(st_0, &msgs_0, *p_0, presig_0).unwrap();
+        let out = steps_2::step_2(st_0, msgs_0, p_0, presig_0)?;
 
         Ok(out)
     }
@@ -1,13 +1,20 @@
-use k256::Secp256k1;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     protocol::{Participant, ProtocolError},
     tecdsa::triples_2::TriplesGenManyResult,
-    triples::{mul_steps, steps, RcvdTriplesMessages, TriplesState, Wait2Payload},
+    tecdsa_cli_srv::session::SessionConfig,
+    triples::{mul_steps, steps, RcvdTriplesMessages, TriplesState, Wait2Payload, Wait3Payload},
     CSCurve,
 };
 
+/// The client side of the step-based triple generation.
+///
+/// Triple generation only runs between exactly two parties: the client runs the
+/// receiving side of each multiplication, and [`TriplesServer2`](super::srv_triples::TriplesServer2)
+/// the sending side. `triples_step_1` rejects a config with any other number of
+/// participants. In a larger setup, such as 2-of-3, the two parties which will
+/// presign generate the triples together.
 pub struct TriplesClient {}
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -21,55 +28,53 @@ pub struct TriplesCliStepOutput<C: CSCurve> {
 }
 
 impl TriplesClient {
-    pub fn triples_step_1() -> Result<TriplesCliStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-        let threshold = 2;
-
-        let mut st_0 = TriplesState::<Secp256k1>::new(&participants.clone(), threshold, 2);
+    pub fn triples_step_1<C: CSCurve>(
+        config: &SessionConfig,
+    ) -> Result<TriplesCliStepOutput<C>, ProtocolError> {
+        config.validate_two_party()?;
+
+        let mut st_0 = TriplesState::<C>::new(
+            &config.participants,
+            config.threshold,
+            2,
+            config.transcript_id(),
+        );
 
-        steps::step_1::<Secp256k1>(&mut st_0)?;
+        steps::step_1::<C>(&mut st_0)?;
 
-        let mut msgs_1 = RcvdTriplesMessages::<Secp256k1>::new();
-        let p_0 = participants
-            .get(0)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
+        let mut msgs_1 = RcvdTriplesMessages::<C>::new();
+        let p_0 = config.me;
 
-        msgs_1.wait_0.insert(*p_0, st_0.my_commitments.clone());
+        msgs_1.wait_0.insert(p_0, st_0.my_commitments.clone());
 
         Ok(TriplesCliStepOutput { st_0, msgs_1 })
     }
 
-    pub fn triples_step_2(
-        mut st_0: TriplesState<Secp256k1>,
-        msgs_0: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesCliStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    pub fn triples_step_2<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: TriplesState<C>,
+        msgs_0: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesCliStepOutput<C>, ProtocolError> {
+        let p_0 = config.me;
+        steps::step_2::<C>(&mut st_0, &msgs_0, p_0)?;
 
-        let p_0 = participants
-            .get(0)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
-        steps::step_2::<Secp256k1>(&mut st_0, &msgs_0, *p_0)?;
-
-        let mut msgs_1 = RcvdTriplesMessages::<Secp256k1>::new();
-        msgs_1.wait_1.insert(*p_0, st_0.my_confirmations.clone());
+        let mut msgs_1 = RcvdTriplesMessages::<C>::new();
+        msgs_1.wait_1.insert(p_0, st_0.my_confirmations.clone());
 
         Ok(TriplesCliStepOutput { st_0, msgs_1 })
     }
 
-    pub fn triples_step_3(
-        mut st_0: TriplesState<Secp256k1>,
-        msgs_0: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesCliStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_0 = participants
-            .get(0)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
-        steps::step_3::<Secp256k1>(&mut st_0, &msgs_0, *p_0)?;
+    pub fn triples_step_3<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: TriplesState<C>,
+        msgs_0: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesCliStepOutput<C>, ProtocolError> {
+        let p_0 = config.me;
+        steps::step_3::<C>(&mut st_0, &msgs_0, p_0)?;
 
-        let mut msgs_1 = RcvdTriplesMessages::<Secp256k1>::new();
+        let mut msgs_1 = RcvdTriplesMessages::<C>::new();
         msgs_1.wait_2.insert(
-            *p_0,
+            p_0,
             Wait2Payload {
                 big_e_i_v: st_0.big_e_i_v.clone(),
                 big_f_i_v: st_0.big_f_i_v.clone(),
@@ -83,152 +88,139 @@ impl TriplesClient {
         Ok(TriplesCliStepOutput { st_0, msgs_1 })
     }
 
-    pub fn triples_step_4(
-        mut st_0: TriplesState<Secp256k1>,
-        msgs_0: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesCliStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    pub fn triples_step_4<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: TriplesState<C>,
+        msgs_0: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesCliStepOutput<C>, ProtocolError> {
+        let p_0 = config.me;
+        let msgs_to_send = steps::step_4::<C>(&mut st_0, &msgs_0, p_0)?;
 
-        let p_0 = participants
-            .get(0)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
-        let msgs_to_send = steps::step_4::<Secp256k1>(&mut st_0, &msgs_0, *p_0)?;
-
-        let mut msgs_1 = RcvdTriplesMessages::<Secp256k1>::new();
-
-        // Dangerous! cardinality 1
-        for (_p, msg) in msgs_to_send {
-            msgs_1.wait_3.insert(*p_0, msg.clone());
-        }
+        let mut msgs_1 = RcvdTriplesMessages::<C>::new();
+        msgs_1
+            .wait_3
+            .insert(p_0, wait_3_payload(config, msgs_to_send)?);
 
         Ok(TriplesCliStepOutput { st_0, msgs_1 })
     }
 
-    pub fn triples_step_5(
-        mut st_0: TriplesState<Secp256k1>,
-        msgs_0: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesCliStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    pub fn triples_step_5<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: TriplesState<C>,
+        msgs_0: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesCliStepOutput<C>, ProtocolError> {
+        let p_0 = config.me;
+        let wait_4 = steps::step_5::<C>(&mut st_0, &msgs_0, p_0)?;
 
-        let p_0 = participants
-            .get(0)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
-        let wait_4 = steps::step_5::<Secp256k1>(&mut st_0, &msgs_0, *p_0)?;
-
-        let mut msgs_1 = RcvdTriplesMessages::<Secp256k1>::new();
-        msgs_1.wait_4.insert(*p_0, wait_4);
+        let mut msgs_1 = RcvdTriplesMessages::<C>::new();
+        msgs_1.wait_4.insert(p_0, wait_4);
 
         Ok(TriplesCliStepOutput { st_0, msgs_1 })
     }
 
-    pub fn triples_step_6(
-        mut st_0: TriplesState<Secp256k1>,
-        msgs_0: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesCliStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_0 = participants
-            .get(0)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
+    pub fn triples_step_6<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: TriplesState<C>,
+        msgs_0: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesCliStepOutput<C>, ProtocolError> {
+        let p_0 = config.me;
 
-        steps::step_6::<Secp256k1>(&mut st_0, &msgs_0, *p_0)?;
-        mul_steps::step_1(&mut st_0, &msgs_0, *p_0)?;
+        steps::step_6::<C>(&mut st_0, &msgs_0, p_0)?;
+        mul_steps::step_1(&mut st_0, &msgs_0, p_0)?;
 
         let big_y_affine_v_v =
-            mul_steps::multiplication_receiver_many_step_1(&mut st_0, &msgs_0, *p_0)?;
+            mul_steps::multiplication_receiver_many_step_1(&mut st_0, &msgs_0, p_0)?;
 
-        let mut msgs_1 = RcvdTriplesMessages::<Secp256k1>::new();
-        msgs_1.batch_random_ot_wait_0.insert(*p_0, big_y_affine_v_v);
+        let mut msgs_1 = RcvdTriplesMessages::<C>::new();
+        msgs_1.batch_random_ot_wait_0.insert(p_0, big_y_affine_v_v);
 
         Ok(TriplesCliStepOutput { st_0, msgs_1 })
     }
 
-    pub fn triples_step_7(
-        mut st_0: TriplesState<Secp256k1>,
-        msgs_0: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesCliStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    pub fn triples_step_7<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: TriplesState<C>,
+        msgs_0: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesCliStepOutput<C>, ProtocolError> {
+        let p_0 = config.me;
 
-        let p_0 = participants
-            .get(0)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
+        let u_v = mul_steps::multiplication_receiver_many_step_2(&mut st_0, &msgs_0, p_0)?;
 
-        let u_v = mul_steps::multiplication_receiver_many_step_2(&mut st_0, &msgs_0, *p_0)?;
-
-        let mut msgs_1 = RcvdTriplesMessages::<Secp256k1>::new();
-        msgs_1.correlated_ot_wait_0.insert(*p_0, u_v);
+        let mut msgs_1 = RcvdTriplesMessages::<C>::new();
+        msgs_1.correlated_ot_wait_0.insert(p_0, u_v);
 
         Ok(TriplesCliStepOutput { st_0, msgs_1 })
     }
 
-    pub fn triples_step_8(
-        mut st_0: TriplesState<Secp256k1>,
-        msgs_0: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesCliStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_0 = participants
-            .get(0)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
+    pub fn triples_step_8<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: TriplesState<C>,
+        msgs_0: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesCliStepOutput<C>, ProtocolError> {
+        let p_0 = config.me;
 
-        let small_x_t_v = mul_steps::multiplication_receiver_many_step_3(&mut st_0, &msgs_0, *p_0)?;
+        let small_x_t_v = mul_steps::multiplication_receiver_many_step_3(&mut st_0, &msgs_0, p_0)?;
 
-        let mut msgs_1 = RcvdTriplesMessages::<Secp256k1>::new();
-        msgs_1.random_ot_extension_wait_1.insert(*p_0, small_x_t_v);
+        let mut msgs_1 = RcvdTriplesMessages::<C>::new();
+        msgs_1.random_ot_extension_wait_1.insert(p_0, small_x_t_v);
 
         Ok(TriplesCliStepOutput { st_0, msgs_1 })
     }
 
-    pub fn triples_step_9(
-        mut st_0: TriplesState<Secp256k1>,
-        msgs_0: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesCliStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_0 = participants
-            .get(0)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
+    pub fn triples_step_9<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: TriplesState<C>,
+        msgs_0: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesCliStepOutput<C>, ProtocolError> {
+        let p_0 = config.me;
 
         let mta_wait_1_payload =
-            mul_steps::multiplication_receiver_many_step_4(&mut st_0, &msgs_0, *p_0)?;
+            mul_steps::multiplication_receiver_many_step_4(&mut st_0, &msgs_0, p_0)?;
 
-        let mut msgs_1 = RcvdTriplesMessages::<Secp256k1>::new();
-        msgs_1.mta_wait_1.insert(*p_0, mta_wait_1_payload);
+        let mut msgs_1 = RcvdTriplesMessages::<C>::new();
+        msgs_1.mta_wait_1.insert(p_0, mta_wait_1_payload);
 
         Ok(TriplesCliStepOutput { st_0, msgs_1 })
     }
 
-    pub fn triples_step_10(
-        mut st_0: TriplesState<Secp256k1>,
-        msgs_0: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesCliStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_0 = participants
-            .get(0)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
+    pub fn triples_step_10<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: TriplesState<C>,
+        msgs_0: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesCliStepOutput<C>, ProtocolError> {
+        let p_0 = config.me;
 
-        let (wait_5_payload, wait_6_payload) = steps::step_7(&mut st_0, &msgs_0, *p_0)?;
+        let (wait_5_payload, wait_6_payload) = steps::step_7(&mut st_0, &msgs_0, p_0)?;
 
-        let mut msgs_1 = RcvdTriplesMessages::<Secp256k1>::new();
-        msgs_1.wait_5.insert(*p_0, wait_5_payload);
-        msgs_1.wait_6.insert(*p_0, wait_6_payload);
+        let mut msgs_1 = RcvdTriplesMessages::<C>::new();
+        msgs_1.wait_5.insert(p_0, wait_5_payload);
+        msgs_1.wait_6.insert(p_0, wait_6_payload);
 
         Ok(TriplesCliStepOutput { st_0, msgs_1 })
     }
 
-    pub fn triples_step_11(
-        mut st_0: TriplesState<Secp256k1>,
-        msgs_0: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesGenManyResult<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    pub fn triples_step_11<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_0: TriplesState<C>,
+        msgs_0: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesGenManyResult<C>, ProtocolError> {
+        let p_0 = config.me;
 
-        let p_0 = participants
-            .get(0)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
-
-        let triples_gen_result_0 = steps::step_8(&mut st_0, &msgs_0, *p_0)?;
+        let triples_gen_result_0 = steps::step_8(&mut st_0, &msgs_0, p_0)?;
 
         Ok(triples_gen_result_0)
     }
 }
+
+/// The private message of step 4, for the only other party.
+pub(super) fn wait_3_payload<C: CSCurve>(
+    config: &SessionConfig,
+    msgs_to_send: Vec<(Participant, Wait3Payload<C>)>,
+) -> Result<Wait3Payload<C>, ProtocolError> {
+    match <[_; 1]>::try_from(msgs_to_send) {
+        Ok([(p, msg)]) if config.others() == [p] => Ok(msg),
+        _ => Err(ProtocolError::AssertionFailed(
+            "triple generation must have exactly one other party".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,232 @@
+//! The wire format of the messages exchanged between the steps.
+//!
+//! The `Rcvd*Messages` structs carry no information about what they belong to,
+//! so a message from another flow, curve or version of this library would either
+//! fail to parse with some obscure error, or worse, parse into garbage. Each
+//! message is thus sealed in an [`Envelope`] before being sent, and opened
+//! against the envelope the receiver expects, rejecting any mismatch before the
+//! message itself is decoded.
+//!
+//! A sealed message is the msgpack encoding of the version, then of the rest
+//! of the envelope, then of the message.
+use std::{error, fmt};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::protocol::ProtocolError;
+use crate::CSCurve;
+
+/// The version of the wire format, bumped whenever any message changes shape.
+pub const WIRE_VERSION: u16 = 1;
+
+/// The flow a message belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProtocolKind {
+    Keygen,
+    Triples,
+    Presign,
+    Sign,
+}
+
+/// Everything a message is tagged with, apart from the version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Header {
+    curve: String,
+    kind: ProtocolKind,
+    step: u32,
+    session_id: String,
+}
+
+/// Describes a message: the flow and step which produced it, and for which session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    pub version: u16,
+    /// The [`CSCurve::NAME`] of the curve.
+    pub curve: String,
+    pub kind: ProtocolKind,
+    pub step: u32,
+    pub session_id: String,
+}
+
+/// An error from opening an envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvelopeError {
+    /// The bytes are not a sealed message at all.
+    Malformed(String),
+    /// The message comes from an incompatible version of this library.
+    Version {
+        expected: u16,
+        found: u16,
+    },
+    Curve {
+        expected: String,
+        found: String,
+    },
+    Kind {
+        expected: ProtocolKind,
+        found: ProtocolKind,
+    },
+    Step {
+        expected: u32,
+        found: u32,
+    },
+    Session {
+        expected: String,
+        found: String,
+    },
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::Malformed(e) => write!(f, "malformed message: {}", e),
+            EnvelopeError::Version { expected, found } => write!(
+                f,
+                "unsupported wire version {}, expected {}",
+                found, expected
+            ),
+            EnvelopeError::Curve { expected, found } => {
+                write!(f, "message for curve {}, expected {}", found, expected)
+            }
+            EnvelopeError::Kind { expected, found } => {
+                write!(f, "message for {:?}, expected {:?}", found, expected)
+            }
+            EnvelopeError::Step { expected, found } => {
+                write!(f, "message from step {}, expected {}", found, expected)
+            }
+            EnvelopeError::Session { expected, found } => {
+                write!(
+                    f,
+                    "message for session {:?}, expected {:?}",
+                    found, expected
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for EnvelopeError {}
+
+impl From<EnvelopeError> for ProtocolError {
+    fn from(e: EnvelopeError) -> Self {
+        ProtocolError::Other(Box::new(e))
+    }
+}
+
+fn malformed(e: impl fmt::Display) -> EnvelopeError {
+    EnvelopeError::Malformed(e.to_string())
+}
+
+impl Envelope {
+    /// The envelope of the current version, for curve `C`.
+    pub fn new<C: CSCurve>(kind: ProtocolKind, step: u32, session_id: &str) -> Self {
+        Envelope {
+            version: WIRE_VERSION,
+            curve: String::from_utf8_lossy(C::NAME).into_owned(),
+            kind,
+            step,
+            session_id: session_id.to_string(),
+        }
+    }
+
+    /// The same envelope, for another step.
+    pub fn with_step(&self, step: u32) -> Self {
+        Envelope {
+            step,
+            ..self.clone()
+        }
+    }
+
+    fn header(&self) -> Header {
+        Header {
+            curve: self.curve.clone(),
+            kind: self.kind,
+            step: self.step,
+            session_id: self.session_id.clone(),
+        }
+    }
+
+    /// Seal `msgs` in this envelope.
+    pub fn seal<M: Serialize>(&self, msgs: &M) -> Vec<u8> {
+        let mut out = crate::serde::encode(&self.version);
+        crate::serde::encode_writer(&mut out, &self.header());
+        crate::serde::encode_writer(&mut out, msgs);
+        out
+    }
+
+    /// Read the envelope of a sealed message, returning the rest of it.
+    ///
+    /// The version is checked first, since nothing after it can be trusted to
+    /// parse otherwise.
+    pub fn read(bytes: &[u8]) -> Result<(Envelope, &[u8]), EnvelopeError> {
+        let mut rest = bytes;
+        let version: u16 = rmp_serde::decode::from_read(&mut rest).map_err(malformed)?;
+        if version != WIRE_VERSION {
+            return Err(EnvelopeError::Version {
+                expected: WIRE_VERSION,
+                found: version,
+            });
+        }
+        let header: Header = rmp_serde::decode::from_read(&mut rest).map_err(malformed)?;
+        let envelope = Envelope {
+            version,
+            curve: header.curve,
+            kind: header.kind,
+            step: header.step,
+            session_id: header.session_id,
+        };
+        Ok((envelope, rest))
+    }
+
+    /// Check that `other` matches this envelope, apart from the step.
+    fn check_flow(&self, other: &Envelope) -> Result<(), EnvelopeError> {
+        if other.version != self.version {
+            return Err(EnvelopeError::Version {
+                expected: self.version,
+                found: other.version,
+            });
+        }
+        if other.curve != self.curve {
+            return Err(EnvelopeError::Curve {
+                expected: self.curve.clone(),
+                found: other.curve.clone(),
+            });
+        }
+        if other.kind != self.kind {
+            return Err(EnvelopeError::Kind {
+                expected: self.kind,
+                found: other.kind,
+            });
+        }
+        if other.session_id != self.session_id {
+            return Err(EnvelopeError::Session {
+                expected: self.session_id.clone(),
+                found: other.session_id.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Open a message sealed in this same envelope.
+    pub fn open<M: DeserializeOwned>(&self, bytes: &[u8]) -> Result<M, EnvelopeError> {
+        let (step, msgs) = self.open_any_step(bytes)?;
+        if step != self.step {
+            return Err(EnvelopeError::Step {
+                expected: self.step,
+                found: step,
+            });
+        }
+        Ok(msgs)
+    }
+
+    /// Like [`Envelope::open`], but accepting a message from any step, and returning that step.
+    pub fn open_any_step<M: DeserializeOwned>(
+        &self,
+        bytes: &[u8],
+    ) -> Result<(u32, M), EnvelopeError> {
+        let (envelope, rest) = Envelope::read(bytes)?;
+        self.check_flow(&envelope)?;
+        let msgs = crate::serde::decode(rest).map_err(malformed)?;
+        Ok((envelope.step, msgs))
+    }
+}
@@ -1,10 +1,19 @@
+#[cfg(feature = "k256")]
+pub mod bindings;
 pub mod cli_keygen;
 pub mod cli_presign;
 pub mod cli_sign;
 pub mod cli_triples;
+pub mod envelope;
 pub mod participant_key;
+pub mod phases;
+pub mod pool;
+pub mod protocols;
+pub mod session;
 pub mod srv_keygen;
 pub mod srv_presign;
 pub mod srv_sign;
 pub mod srv_triples;
+#[cfg(feature = "store")]
+pub mod store;
 pub mod verifier;
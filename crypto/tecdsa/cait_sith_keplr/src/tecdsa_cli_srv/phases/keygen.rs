@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use crate::keyshare::{KeyshareState2, RcvdKeyshareMessages};
+use crate::protocol::{Participant, ProtocolError};
+use crate::tecdsa_cli_srv::cli_keygen::KeygenClient;
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::{CSCurve, KeygenOutput};
+
+use super::{phase, PhaseTag};
+
+/// The shares sent by a key generation, keyed by recipient.
+pub type KeygenShares<C> = HashMap<Participant, RcvdKeyshareMessages<C>>;
+
+phase!(
+    /// A key generation which has sent its commitment.
+    KeygenRound1,
+    KeyshareState2,
+    RcvdKeyshareMessages
+);
+phase!(
+    /// A key generation which has sent its confirmation.
+    KeygenRound2,
+    KeyshareState2,
+    RcvdKeyshareMessages
+);
+phase!(
+    /// A key generation which has revealed its polynomial commitment.
+    KeygenRound3,
+    KeyshareState2,
+    RcvdKeyshareMessages
+);
+phase!(
+    /// A key generation which has sent a share to every other participant.
+    KeygenRound4,
+    KeyshareState2,
+    RcvdKeyshareMessages
+);
+
+impl<C: CSCurve> KeygenRound1<C> {
+    /// Start a key generation, returning the messages to send to everybody else.
+    pub fn start(config: SessionConfig) -> Result<(Self, RcvdKeyshareMessages<C>), ProtocolError> {
+        let out = KeygenClient::cli_keygen_step_1::<C>(&config)?;
+        let phase = KeygenRound1 {
+            phase: PhaseTag::new(),
+            config,
+            state: out.st_0,
+            msgs: RcvdKeyshareMessages::new(),
+        };
+        Ok((phase, out.msgs_1))
+    }
+
+    pub fn step(
+        mut self,
+        msgs: RcvdKeyshareMessages<C>,
+    ) -> Result<(KeygenRound2<C>, RcvdKeyshareMessages<C>), ProtocolError> {
+        self.msgs.extend(msgs);
+        let out = KeygenClient::cli_keygen_step_2(&self.config, self.state, &self.msgs)?;
+        let phase = KeygenRound2 {
+            phase: PhaseTag::new(),
+            config: self.config,
+            state: out.st_0,
+            msgs: self.msgs,
+        };
+        Ok((phase, out.msgs_1))
+    }
+}
+
+impl<C: CSCurve> KeygenRound2<C> {
+    pub fn step(
+        mut self,
+        msgs: RcvdKeyshareMessages<C>,
+    ) -> Result<(KeygenRound3<C>, RcvdKeyshareMessages<C>), ProtocolError> {
+        self.msgs.extend(msgs);
+        let out = KeygenClient::cli_keygen_step_3(&self.config, self.state, &self.msgs)?;
+        let phase = KeygenRound3 {
+            phase: PhaseTag::new(),
+            config: self.config,
+            state: out.st_0,
+            msgs: self.msgs,
+        };
+        Ok((phase, out.msgs_1))
+    }
+}
+
+impl<C: CSCurve> KeygenRound3<C> {
+    /// The messages of this step are private, and keyed by recipient.
+    pub fn step(
+        mut self,
+        msgs: RcvdKeyshareMessages<C>,
+    ) -> Result<(KeygenRound4<C>, KeygenShares<C>), ProtocolError> {
+        self.msgs.extend(msgs);
+        let out = KeygenClient::cli_keygen_step_4(&self.config, self.state, &self.msgs)?;
+        let phase = KeygenRound4 {
+            phase: PhaseTag::new(),
+            config: self.config,
+            state: out.st_0,
+            msgs: self.msgs,
+        };
+        Ok((phase, out.msgs_1))
+    }
+}
+
+impl<C: CSCurve> KeygenRound4<C> {
+    pub fn finish(
+        mut self,
+        msgs: RcvdKeyshareMessages<C>,
+    ) -> Result<KeygenOutput<C>, ProtocolError> {
+        self.msgs.extend(msgs);
+        KeygenClient::cli_keygen_step_5(&self.config, self.state, &self.msgs)
+    }
+}
@@ -0,0 +1,106 @@
+//! A typed phase API over the client/server steps.
+//!
+//! The step functions take one big state struct, and nothing stops a caller
+//! from running them out of order. Here, each phase of a flow is its own type
+//! instead: a step consumes the phase, and returns the next phase along with the
+//! messages to send to the other participants. The last step returns the output
+//! of the flow.
+//!
+//! A phase keeps the messages received so far, so each step only needs the
+//! messages which arrived since the previous one. Every phase can be serialized,
+//! so that it can be stored between the rounds of a client/server session. The
+//! name of the phase is stored along with it, so it only loads back as the same phase.
+mod keygen;
+mod presign;
+mod sign;
+mod triples;
+
+pub use keygen::*;
+pub use presign::*;
+pub use sign::*;
+pub use triples::*;
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A phase, named so that it can't be loaded as another one.
+pub(crate) trait Phase {
+    const NAME: &'static str;
+}
+
+/// The name of phase `P`, stored first and checked when the phase is loaded back.
+///
+/// Phases of a flow share the same state and messages, so without it the
+/// state of one round could be loaded as any other round.
+pub(crate) struct PhaseTag<P>(PhantomData<fn() -> P>);
+
+impl<P> PhaseTag<P> {
+    pub(crate) fn new() -> Self {
+        PhaseTag(PhantomData)
+    }
+}
+
+impl<P> Clone for PhaseTag<P> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Phase> fmt::Debug for PhaseTag<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(P::NAME)
+    }
+}
+
+impl<P: Phase> Serialize for PhaseTag<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(P::NAME)
+    }
+}
+
+impl<'de, P: Phase> Deserialize<'de> for PhaseTag<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        if name != P::NAME {
+            return Err(de::Error::custom(format!(
+                "expected the {} phase, found {}",
+                P::NAME,
+                name
+            )));
+        }
+        Ok(Self::new())
+    }
+}
+
+/// Define a phase, holding the state of a flow along with the messages received so far.
+macro_rules! phase {
+    ($(#[$attr:meta])* $name:ident, $state:ident, $msgs:ident $(, $field:ident: $ty:ty)* $(,)?) => {
+        $(#[$attr])*
+        #[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+        #[serde(bound = "
+            C::Scalar: serde::Serialize + for<'a> serde::Deserialize<'a>,
+            C::AffinePoint: serde::Serialize + for<'a> serde::Deserialize<'a>
+        ")]
+        pub struct $name<C: $crate::CSCurve> {
+            phase: $crate::tecdsa_cli_srv::phases::PhaseTag<$name<C>>,
+            config: $crate::tecdsa_cli_srv::session::SessionConfig,
+            state: $state<C>,
+            msgs: $msgs<C>,
+            $($field: $ty,)*
+        }
+
+        impl<C: $crate::CSCurve> $crate::tecdsa_cli_srv::phases::Phase for $name<C> {
+            const NAME: &'static str = stringify!($name);
+        }
+
+        impl<C: $crate::CSCurve> $name<C> {
+            /// The config of the session this phase belongs to.
+            pub fn config(&self) -> &$crate::tecdsa_cli_srv::session::SessionConfig {
+                &self.config
+            }
+        }
+    };
+}
+
+pub(crate) use phase;
@@ -0,0 +1,71 @@
+use crate::presign::{PresignState2, RcvdPresignMessages2};
+use crate::protocol::ProtocolError;
+use crate::tecdsa_cli_srv::cli_presign::PresignClient;
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::triples::{TriplePub, TripleShare};
+use crate::{CSCurve, KeygenOutput, PresignOutput};
+
+use super::{phase, PhaseTag};
+
+phase!(
+    /// A presigning which has sent its share of `k * d`.
+    PresignRound1,
+    PresignState2,
+    RcvdPresignMessages2
+);
+phase!(
+    /// A presigning which has sent its shares of `k * a` and `x * b`.
+    PresignRound2,
+    PresignState2,
+    RcvdPresignMessages2
+);
+
+impl<C: CSCurve> PresignRound1<C> {
+    /// Start a presigning, returning the messages to send to everybody else.
+    ///
+    /// The triples and their shares must come from a triple generation over the
+    /// same participants.
+    pub fn start(
+        config: SessionConfig,
+        pub_0: TriplePub<C>,
+        pub_1: TriplePub<C>,
+        share_0: TripleShare<C>,
+        share_1: TripleShare<C>,
+        keygen_out: KeygenOutput<C>,
+    ) -> Result<(Self, RcvdPresignMessages2<C>), ProtocolError> {
+        let out =
+            PresignClient::presign_step_1(&config, pub_0, pub_1, share_0, share_1, keygen_out)?;
+        let phase = PresignRound1 {
+            phase: PhaseTag::new(),
+            config,
+            state: out.st_0,
+            msgs: RcvdPresignMessages2::new(),
+        };
+        Ok((phase, out.msgs_1))
+    }
+
+    pub fn step(
+        mut self,
+        msgs: RcvdPresignMessages2<C>,
+    ) -> Result<(PresignRound2<C>, RcvdPresignMessages2<C>), ProtocolError> {
+        self.msgs.extend(msgs);
+        let out = PresignClient::presign_step_2(&self.config, self.state)?;
+        let phase = PresignRound2 {
+            phase: PhaseTag::new(),
+            config: self.config,
+            state: out.st_0,
+            msgs: self.msgs,
+        };
+        Ok((phase, out.msgs_1))
+    }
+}
+
+impl<C: CSCurve> PresignRound2<C> {
+    pub fn finish(
+        mut self,
+        msgs: RcvdPresignMessages2<C>,
+    ) -> Result<PresignOutput<C>, ProtocolError> {
+        self.msgs.extend(msgs);
+        PresignClient::presign_step_3(&self.config, self.state, &self.msgs)
+    }
+}
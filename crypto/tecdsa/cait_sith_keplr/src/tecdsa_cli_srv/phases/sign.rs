@@ -0,0 +1,41 @@
+use elliptic_curve::CurveArithmetic;
+
+use crate::protocol::ProtocolError;
+use crate::sign::{RcvdSignMessages, SignOutput, SignState2};
+use crate::tecdsa_cli_srv::cli_sign::SignClient;
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::{CSCurve, PresignOutput};
+
+use super::{phase, PhaseTag};
+
+phase!(
+    /// A signing which has sent its partial signature.
+    SignRound1,
+    SignState2,
+    RcvdSignMessages,
+    presign_out: PresignOutput<C>,
+);
+
+impl<C: CSCurve> SignRound1<C> {
+    /// Start signing `msg_hash`, returning the messages to send to everybody else.
+    pub fn start(
+        config: SessionConfig,
+        presign_out: PresignOutput<C>,
+        msg_hash: <C as CurveArithmetic>::Scalar,
+    ) -> Result<(Self, RcvdSignMessages<C>), ProtocolError> {
+        let out = SignClient::sign_step_1(&config, msg_hash, presign_out.clone())?;
+        let phase = SignRound1 {
+            phase: PhaseTag::new(),
+            config,
+            state: out.st_0,
+            msgs: RcvdSignMessages::new(),
+            presign_out,
+        };
+        Ok((phase, out.msgs_1))
+    }
+
+    pub fn finish(mut self, msgs: RcvdSignMessages<C>) -> Result<SignOutput<C>, ProtocolError> {
+        self.msgs.extend(msgs);
+        SignClient::sign_step_2(&self.config, &mut self.state, &self.msgs, self.presign_out)
+    }
+}
@@ -0,0 +1,120 @@
+use crate::protocol::ProtocolError;
+use crate::tecdsa::triples_2::TriplesGenManyResult;
+use crate::tecdsa_cli_srv::cli_triples::TriplesClient;
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::tecdsa_cli_srv::srv_triples::TriplesServer2;
+use crate::triples::{RcvdTriplesMessages, TriplesState};
+use crate::CSCurve;
+
+use super::{phase, PhaseTag};
+
+/// Define the phases after each of the first ten steps of the triple generation.
+macro_rules! triples_phases {
+    ($($name:ident = $n:literal),* $(,)?) => {
+        $(
+            phase!(
+                #[doc = concat!("A triple generation which has run step ", $n, " of 11.")]
+                $name,
+                TriplesState,
+                RcvdTriplesMessages,
+                is_sender: bool,
+            );
+        )*
+    };
+}
+
+triples_phases!(
+    TriplesRound1 = 1,
+    TriplesRound2 = 2,
+    TriplesRound3 = 3,
+    TriplesRound4 = 4,
+    TriplesRound5 = 5,
+    TriplesRound6 = 6,
+    TriplesRound7 = 7,
+    TriplesRound8 = 8,
+    TriplesRound9 = 9,
+    TriplesRound10 = 10,
+);
+
+impl<C: CSCurve> TriplesRound1<C> {
+    /// Start a triple generation, returning the messages to send to the other party.
+    ///
+    /// This is a two party flow. The participant with the smaller id runs the
+    /// client steps, and the other one the server steps. The server's step n
+    /// needs the client's messages from the same step, so the client goes first.
+    pub fn start(config: SessionConfig) -> Result<(Self, RcvdTriplesMessages<C>), ProtocolError> {
+        let is_sender = config.others().iter().all(|p| *p < config.me);
+        let (state, msgs) = if is_sender {
+            let out = TriplesServer2::triples_step_1::<C>(&config)?;
+            (out.st_1, out.msgs_0)
+        } else {
+            let out = TriplesClient::triples_step_1::<C>(&config)?;
+            (out.st_0, out.msgs_1)
+        };
+        let phase = TriplesRound1 {
+            phase: PhaseTag::new(),
+            config,
+            state,
+            msgs: RcvdTriplesMessages::new(),
+            is_sender,
+        };
+        Ok((phase, msgs))
+    }
+}
+
+/// Move from one phase to the next by running `$step` on the side of this party.
+macro_rules! triples_step {
+    ($($from:ident => $to:ident: $step:ident),* $(,)?) => {
+        $(
+            impl<C: CSCurve> $from<C> {
+                pub fn step(
+                    mut self,
+                    msgs: RcvdTriplesMessages<C>,
+                ) -> Result<($to<C>, RcvdTriplesMessages<C>), ProtocolError> {
+                    self.msgs.extend(msgs);
+                    let (state, msgs) = if self.is_sender {
+                        let out = TriplesServer2::$step(&self.config, self.state, self.msgs.clone())?;
+                        (out.st_1, out.msgs_0)
+                    } else {
+                        let out = TriplesClient::$step(&self.config, self.state, self.msgs.clone())?;
+                        (out.st_0, out.msgs_1)
+                    };
+                    let phase = $to {
+                        phase: PhaseTag::new(),
+                        config: self.config,
+                        state,
+                        msgs: self.msgs,
+                        is_sender: self.is_sender,
+                    };
+                    Ok((phase, msgs))
+                }
+            }
+        )*
+    };
+}
+
+triples_step!(
+    TriplesRound1 => TriplesRound2: triples_step_2,
+    TriplesRound2 => TriplesRound3: triples_step_3,
+    TriplesRound3 => TriplesRound4: triples_step_4,
+    TriplesRound4 => TriplesRound5: triples_step_5,
+    TriplesRound5 => TriplesRound6: triples_step_6,
+    TriplesRound6 => TriplesRound7: triples_step_7,
+    TriplesRound7 => TriplesRound8: triples_step_8,
+    TriplesRound8 => TriplesRound9: triples_step_9,
+    TriplesRound9 => TriplesRound10: triples_step_10,
+);
+
+impl<C: CSCurve> TriplesRound10<C> {
+    pub fn finish(
+        mut self,
+        msgs: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesGenManyResult<C>, ProtocolError> {
+        self.msgs.extend(msgs);
+        if self.is_sender {
+            TriplesServer2::triples_step_11(&self.config, self.state, self.msgs)
+        } else {
+            TriplesClient::triples_step_11(&self.config, self.state, self.msgs)
+        }
+    }
+}
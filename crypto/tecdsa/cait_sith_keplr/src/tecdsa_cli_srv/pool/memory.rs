@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use super::{PoolError, PoolState, PoolStore};
+
+/// An item, which is only kept until it is handed out.
+#[derive(Debug)]
+enum Slot<T> {
+    Generated(T),
+    Reserved,
+    Consumed,
+}
+
+impl<T> Slot<T> {
+    fn state(&self) -> PoolState {
+        match self {
+            Slot::Generated(_) => PoolState::Generated,
+            Slot::Reserved => PoolState::Reserved,
+            Slot::Consumed => PoolState::Consumed,
+        }
+    }
+}
+
+/// A [`PoolStore`] keeping its items in memory.
+///
+/// Items are handed out in the order of their ids. Wrap the pool in a `Mutex`
+/// to share it between threads.
+#[derive(Debug)]
+pub struct MemoryPoolStore<T> {
+    slots: BTreeMap<String, Slot<T>>,
+}
+
+impl<T> MemoryPoolStore<T> {
+    pub fn new() -> Self {
+        MemoryPoolStore {
+            slots: BTreeMap::new(),
+        }
+    }
+
+    /// Check that every item of `ids` is in the `expected` state, and named once.
+    fn check(&self, ids: &[&str], expected: PoolState) -> Result<(), PoolError> {
+        for (i, id) in ids.iter().enumerate() {
+            let found = self
+                .slots
+                .get(*id)
+                .ok_or_else(|| PoolError::Unknown(id.to_string()))?
+                .state();
+            if found != expected {
+                return Err(PoolError::Unavailable {
+                    id: id.to_string(),
+                    expected,
+                    found,
+                });
+            }
+            if ids[..i].contains(id) {
+                return Err(PoolError::Duplicate(id.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    fn take(&mut self, id: &str) -> Option<T> {
+        let slot = self.slots.get_mut(id)?;
+        match std::mem::replace(slot, Slot::Reserved) {
+            Slot::Generated(item) => Some(item),
+            other => {
+                *slot = other;
+                None
+            }
+        }
+    }
+}
+
+impl<T> Default for MemoryPoolStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> PoolStore<T> for MemoryPoolStore<T> {
+    fn insert(&mut self, id: &str, item: T) -> Result<(), PoolError> {
+        if self.slots.contains_key(id) {
+            return Err(PoolError::Duplicate(id.to_string()));
+        }
+        self.slots.insert(id.to_string(), Slot::Generated(item));
+        Ok(())
+    }
+
+    fn state(&self, id: &str) -> Result<Option<PoolState>, PoolError> {
+        Ok(self.slots.get(id).map(Slot::state))
+    }
+
+    fn available(&self) -> Result<usize, PoolError> {
+        Ok(self
+            .slots
+            .values()
+            .filter(|slot| matches!(slot, Slot::Generated(_)))
+            .count())
+    }
+
+    fn reserve(&mut self, ids: &[&str]) -> Result<Vec<T>, PoolError> {
+        self.check(ids, PoolState::Generated)?;
+
+        // Every item was just checked to be generated, so none of these fail.
+        Ok(ids.iter().filter_map(|id| self.take(id)).collect())
+    }
+
+    fn reserve_any(&mut self, count: usize) -> Result<Vec<(String, T)>, PoolError> {
+        let ids: Vec<String> = self
+            .slots
+            .iter()
+            .filter(|(_, slot)| matches!(slot, Slot::Generated(_)))
+            .map(|(id, _)| id.clone())
+            .take(count)
+            .collect();
+        if ids.len() < count {
+            return Err(PoolError::Exhausted {
+                wanted: count,
+                available: ids.len(),
+            });
+        }
+
+        Ok(ids
+            .into_iter()
+            .filter_map(|id| self.take(&id).map(|item| (id, item)))
+            .collect())
+    }
+
+    fn consume(&mut self, ids: &[&str]) -> Result<(), PoolError> {
+        self.check(ids, PoolState::Reserved)?;
+
+        for id in ids {
+            self.slots.insert(id.to_string(), Slot::Consumed);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,300 @@
+//! Pools of triples and presignatures, each of which is handed out at most once.
+//!
+//! A triple or presignature used twice leaks the private key, yet both are plain
+//! values which can be cloned and passed around freely. A pool keeps them by
+//! id instead, and tracks each through three states:
+//!
+//! - [`PoolState::Generated`]: the item was added, and is free to use.
+//! - [`PoolState::Reserved`]: the item was handed out, to be used by one flow.
+//! - [`PoolState::Consumed`]: the flow using it is over. Only the id is kept.
+//!
+//! Items only ever move forward, so an item which was handed out once is never
+//! handed out again, and its id can't be reused for another item.
+//!
+//! Both parties of a flow must use the same items. One of them reserves any free
+//! items, and sends their ids to the other, which reserves those exact ids.
+//! Presigning takes a pair of triples, which is reserved as a whole or not at all.
+//!
+//! The items are kept in a [`PoolStore`], with [`MemoryPoolStore`] keeping them
+//! in memory.
+use std::{error, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::ProtocolError;
+use crate::triples::{TriplePub, TripleShare};
+use crate::{CSCurve, PresignOutput};
+
+mod memory;
+
+pub use memory::MemoryPoolStore;
+
+/// Where an item of a pool is in its life.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PoolState {
+    Generated,
+    Reserved,
+    Consumed,
+}
+
+/// An error from a pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolError {
+    /// The id was already used for another item, which may have been consumed since.
+    Duplicate(String),
+    /// No item was ever added with this id.
+    Unknown(String),
+    /// The item is not in the state the operation needs.
+    Unavailable {
+        id: String,
+        expected: PoolState,
+        found: PoolState,
+    },
+    /// There are not enough free items.
+    Exhausted { wanted: usize, available: usize },
+    /// The store itself failed.
+    Storage(String),
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolError::Duplicate(id) => write!(f, "id {:?} is already in the pool", id),
+            PoolError::Unknown(id) => write!(f, "id {:?} is not in the pool", id),
+            PoolError::Unavailable {
+                id,
+                expected,
+                found,
+            } => write!(f, "{:?} is {:?}, expected {:?}", id, found, expected),
+            PoolError::Exhausted { wanted, available } => write!(
+                f,
+                "wanted {} items from the pool, only {} are available",
+                wanted, available
+            ),
+            PoolError::Storage(e) => write!(f, "pool storage failed: {}", e),
+        }
+    }
+}
+
+impl error::Error for PoolError {}
+
+impl From<PoolError> for ProtocolError {
+    fn from(e: PoolError) -> Self {
+        ProtocolError::Other(Box::new(e))
+    }
+}
+
+/// Where a pool keeps its items.
+///
+/// Each call must apply atomically: either every item it names moves to its new
+/// state, or none does. A store shared between processes, e.g. in a database,
+/// must hold this across all of them.
+pub trait PoolStore<T> {
+    /// Add a generated item.
+    ///
+    /// This fails if `id` was ever added before, even if that item was consumed.
+    fn insert(&mut self, id: &str, item: T) -> Result<(), PoolError>;
+
+    /// The state of the item `id`, or `None` if it was never added.
+    fn state(&self, id: &str) -> Result<Option<PoolState>, PoolError>;
+
+    /// The number of generated items.
+    fn available(&self) -> Result<usize, PoolError>;
+
+    /// Reserve the generated items `ids`, returning them in the same order.
+    fn reserve(&mut self, ids: &[&str]) -> Result<Vec<T>, PoolError>;
+
+    /// Reserve any `count` generated items, returning them along with their ids.
+    fn reserve_any(&mut self, count: usize) -> Result<Vec<(String, T)>, PoolError>;
+
+    /// Mark the reserved items `ids` consumed.
+    fn consume(&mut self, ids: &[&str]) -> Result<(), PoolError>;
+}
+
+/// A triple, along with our share of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "
+    C::Scalar: Serialize + for<'a> Deserialize<'a>,
+    C::AffinePoint: Serialize + for<'a> Deserialize<'a>
+")]
+pub struct PooledTriple<C: CSCurve> {
+    pub public: TriplePub<C>,
+    pub share: TripleShare<C>,
+}
+
+/// A pair of triples reserved for one presigning.
+#[derive(Debug)]
+pub struct TriplePair<C: CSCurve> {
+    /// The ids to send to the other party, which reserves the same pair.
+    pub ids: [String; 2],
+    pub pub_0: TriplePub<C>,
+    pub pub_1: TriplePub<C>,
+    pub share_0: TripleShare<C>,
+    pub share_1: TripleShare<C>,
+}
+
+/// A presignature reserved for one signing.
+#[derive(Debug)]
+pub struct ReservedPresign<C: CSCurve> {
+    /// The id to send to the other party, which reserves the same presignature.
+    pub id: String,
+    pub output: PresignOutput<C>,
+}
+
+/// A pool of triples, handed out in pairs to presign.
+pub struct TriplePool<C: CSCurve, S = MemoryPoolStore<PooledTriple<C>>> {
+    store: S,
+    _curve: std::marker::PhantomData<C>,
+}
+
+impl<C: CSCurve, S: PoolStore<PooledTriple<C>>> TriplePool<C, S> {
+    pub fn new(store: S) -> Self {
+        TriplePool {
+            store,
+            _curve: std::marker::PhantomData,
+        }
+    }
+
+    pub fn insert(
+        &mut self,
+        id: &str,
+        public: TriplePub<C>,
+        share: TripleShare<C>,
+    ) -> Result<(), PoolError> {
+        self.store.insert(id, PooledTriple { public, share })
+    }
+
+    pub fn state(&self, id: &str) -> Result<Option<PoolState>, PoolError> {
+        self.store.state(id)
+    }
+
+    /// The number of triples which can still be handed out.
+    pub fn available(&self) -> Result<usize, PoolError> {
+        self.store.available()
+    }
+
+    /// Reserve any two free triples, to start a presigning.
+    pub fn reserve_pair(&mut self) -> Result<TriplePair<C>, PoolError> {
+        let mut triples = self.store.reserve_any(2)?;
+        let (id_1, triple_1) = triples.pop().ok_or(PoolError::Exhausted {
+            wanted: 2,
+            available: 0,
+        })?;
+        let (id_0, triple_0) = triples.pop().ok_or(PoolError::Exhausted {
+            wanted: 2,
+            available: 1,
+        })?;
+        Ok(pair([id_0, id_1], triple_0, triple_1))
+    }
+
+    /// Reserve the pair of triples the other party picked.
+    pub fn reserve_pair_by_id(
+        &mut self,
+        id_0: &str,
+        id_1: &str,
+    ) -> Result<TriplePair<C>, PoolError> {
+        let mut triples = self.store.reserve(&[id_0, id_1])?;
+        let triple_1 = triples
+            .pop()
+            .ok_or_else(|| PoolError::Unknown(id_1.to_string()))?;
+        let triple_0 = triples
+            .pop()
+            .ok_or_else(|| PoolError::Unknown(id_0.to_string()))?;
+        Ok(pair(
+            [id_0.to_string(), id_1.to_string()],
+            triple_0,
+            triple_1,
+        ))
+    }
+
+    /// Retire a pair once its presigning is over, whether it succeeded or not.
+    pub fn consume(&mut self, pair: TriplePair<C>) -> Result<(), PoolError> {
+        let [id_0, id_1] = &pair.ids;
+        self.store.consume(&[id_0, id_1])
+    }
+}
+
+impl<C: CSCurve> Default for TriplePool<C> {
+    fn default() -> Self {
+        Self::new(MemoryPoolStore::new())
+    }
+}
+
+fn pair<C: CSCurve>(
+    ids: [String; 2],
+    triple_0: PooledTriple<C>,
+    triple_1: PooledTriple<C>,
+) -> TriplePair<C> {
+    TriplePair {
+        ids,
+        pub_0: triple_0.public,
+        pub_1: triple_1.public,
+        share_0: triple_0.share,
+        share_1: triple_1.share,
+    }
+}
+
+/// A pool of presignatures, handed out one at a time to sign.
+pub struct PresignPool<C: CSCurve, S = MemoryPoolStore<PresignOutput<C>>> {
+    store: S,
+    _curve: std::marker::PhantomData<C>,
+}
+
+impl<C: CSCurve, S: PoolStore<PresignOutput<C>>> PresignPool<C, S> {
+    pub fn new(store: S) -> Self {
+        PresignPool {
+            store,
+            _curve: std::marker::PhantomData,
+        }
+    }
+
+    pub fn insert(&mut self, id: &str, output: PresignOutput<C>) -> Result<(), PoolError> {
+        self.store.insert(id, output)
+    }
+
+    pub fn state(&self, id: &str) -> Result<Option<PoolState>, PoolError> {
+        self.store.state(id)
+    }
+
+    /// The number of presignatures which can still be handed out.
+    pub fn available(&self) -> Result<usize, PoolError> {
+        self.store.available()
+    }
+
+    /// Reserve any free presignature, to start a signing.
+    pub fn reserve(&mut self) -> Result<ReservedPresign<C>, PoolError> {
+        let (id, output) = self
+            .store
+            .reserve_any(1)?
+            .pop()
+            .ok_or(PoolError::Exhausted {
+                wanted: 1,
+                available: 0,
+            })?;
+        Ok(ReservedPresign { id, output })
+    }
+
+    /// Reserve the presignature the other party picked.
+    pub fn reserve_by_id(&mut self, id: &str) -> Result<ReservedPresign<C>, PoolError> {
+        let output = self
+            .store
+            .reserve(&[id])?
+            .pop()
+            .ok_or_else(|| PoolError::Unknown(id.to_string()))?;
+        Ok(ReservedPresign {
+            id: id.to_string(),
+            output,
+        })
+    }
+
+    /// Retire a presignature once its signing is over, whether it succeeded or not.
+    pub fn consume(&mut self, presign: ReservedPresign<C>) -> Result<(), PoolError> {
+        self.store.consume(&[&presign.id])
+    }
+}
+
+impl<C: CSCurve> Default for PresignPool<C> {
+    fn default() -> Self {
+        Self::new(MemoryPoolStore::new())
+    }
+}
@@ -0,0 +1,657 @@
+//! Adapters running the client/server steps through the [`Protocol`] interface.
+//!
+//! The step functions in this module's siblings have to be sequenced by hand.
+//! The adapters here drive those steps for a single party instead, buffering
+//! incoming messages into the `Rcvd*Messages` structs, so that the flows can be
+//! run with [`run_protocol`](crate::protocol::run_protocol) over any transport.
+//!
+//! Every message is sealed in an [`Envelope`] tagged with the round of the step
+//! which produced it, and a step only runs once the round it depends on has
+//! arrived from every other participant.
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use elliptic_curve::CurveArithmetic;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::keyshare::{KeyshareState2, RcvdKeyshareMessages};
+use crate::presign::{PresignState2, RcvdPresignMessages2};
+use crate::protocol::{
+    Action, InitializationError, MessageData, Participant, Protocol, ProtocolError,
+};
+use crate::sign::{RcvdSignMessages, SignOutput, SignState2};
+use crate::tecdsa::triples_2::TriplesGenManyResult;
+use crate::tecdsa_cli_srv::cli_keygen::KeygenClient;
+use crate::tecdsa_cli_srv::cli_presign::PresignClient;
+use crate::tecdsa_cli_srv::cli_sign::SignClient;
+use crate::tecdsa_cli_srv::cli_triples::TriplesClient;
+use crate::tecdsa_cli_srv::envelope::{Envelope, ProtocolKind};
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::tecdsa_cli_srv::srv_triples::TriplesServer2;
+use crate::triples::{RcvdTriplesMessages, TriplePub, TripleShare, TriplesState};
+use crate::{CSCurve, KeygenOutput, PresignOutput};
+
+/// The round of the step which produced a message.
+type Round = usize;
+
+/// A buffer of received messages, keyed by sender.
+pub trait RcvdMessages: Serialize + DeserializeOwned {
+    /// Merge the entries of `other` which were sent by `from`.
+    ///
+    /// Entries claiming to come from anybody else are dropped.
+    fn merge_from(&mut self, from: Participant, other: Self);
+}
+
+macro_rules! impl_rcvd_messages {
+    ($ty:ident, $($field:ident),+) => {
+        impl<C: CSCurve> RcvdMessages for $ty<C>
+        where
+            C::Scalar: Serialize + for<'a> Deserialize<'a>,
+            C::AffinePoint: Serialize + for<'a> Deserialize<'a>,
+        {
+            fn merge_from(&mut self, from: Participant, mut other: Self) {
+                $(
+                    if let Some(data) = other.$field.remove(&from) {
+                        self.$field.insert(from, data);
+                    }
+                )+
+            }
+        }
+    };
+}
+
+impl_rcvd_messages!(RcvdKeyshareMessages, wait_0, wait_1, wait_2, wait_3);
+impl_rcvd_messages!(
+    RcvdTriplesMessages,
+    wait_0,
+    wait_1,
+    wait_2,
+    wait_3,
+    wait_4,
+    wait_5,
+    wait_6,
+    batch_random_ot_wait_0,
+    correlated_ot_wait_0,
+    random_ot_extension_wait_0,
+    random_ot_extension_wait_1,
+    mta_wait_0,
+    mta_wait_1
+);
+impl_rcvd_messages!(
+    RcvdPresignMessages2,
+    wait_0,
+    wait_1,
+    wait_0_commitments,
+    wait_0_confirmations
+);
+impl_rcvd_messages!(RcvdSignMessages, wait_0, wait_0_confirmations);
+
+/// The plumbing shared by the adapters.
+///
+/// This buffers incoming messages, tracks which rounds have arrived from whom,
+/// and queues up the actions produced by a step.
+struct StepBuffer<M, T> {
+    others: Vec<Participant>,
+    envelope: Envelope,
+    msgs: M,
+    rcvd: HashMap<Round, HashSet<Participant>>,
+    outbox: VecDeque<Action<T>>,
+    error: Option<ProtocolError>,
+    /// The error a step failed with, reported again on every later poke.
+    ///
+    /// A failed step has consumed the state, so the flow can't go on.
+    failed: Option<ProtocolError>,
+    done: bool,
+}
+
+impl<M: RcvdMessages, T> StepBuffer<M, T> {
+    fn new(config: &SessionConfig, envelope: Envelope, msgs: M) -> Self {
+        StepBuffer {
+            others: config.others(),
+            envelope,
+            msgs,
+            rcvd: HashMap::new(),
+            outbox: VecDeque::new(),
+            error: None,
+            failed: None,
+            done: false,
+        }
+    }
+
+    /// Check whether every other participant has sent their `round` messages.
+    ///
+    /// Round 0 is the start of the flow, and is always available.
+    fn has_round(&self, round: Round) -> bool {
+        round == 0
+            || self
+                .rcvd
+                .get(&round)
+                .is_some_and(|from| from.len() == self.others.len())
+    }
+
+    fn message(&mut self, from: Participant, data: MessageData) {
+        if !self.others.contains(&from) {
+            return;
+        }
+
+        match self.envelope.open_any_step::<M>(&data) {
+            Ok((round, msgs)) => {
+                self.rcvd.entry(round as Round).or_default().insert(from);
+                self.msgs.merge_from(from, msgs);
+            }
+            Err(e) => {
+                self.error.get_or_insert(e.into());
+            }
+        }
+    }
+
+    fn seal(&self, round: Round, msgs: &M) -> Vec<u8> {
+        self.envelope.with_step(round as u32).seal(msgs)
+    }
+
+    fn send_many(&mut self, round: Round, msgs: &M) {
+        self.outbox
+            .push_back(Action::SendMany(self.seal(round, msgs)));
+    }
+
+    fn send_private(&mut self, round: Round, to: Participant, msgs: &M) {
+        self.outbox
+            .push_back(Action::SendPrivate(to, self.seal(round, msgs)));
+    }
+
+    fn finish(&mut self, out: T) {
+        self.done = true;
+        self.outbox.push_back(Action::Return(out));
+    }
+
+    /// Poke the flow, running `step` whenever the round it needs has arrived.
+    ///
+    /// `step` returns the round that the next step depends on.
+    fn poke(
+        &mut self,
+        mut step: impl FnMut(&mut Self) -> Result<Round, ProtocolError>,
+        waiting_for: &mut Round,
+    ) -> Result<Action<T>, ProtocolError> {
+        loop {
+            if let Some(e) = &self.failed {
+                return Err(repeat_error(e));
+            }
+            if let Some(e) = self.error.take() {
+                return Err(e);
+            }
+            if let Some(action) = self.outbox.pop_front() {
+                return Ok(action);
+            }
+            if self.done || !self.has_round(*waiting_for) {
+                return Ok(Action::Wait);
+            }
+            match step(self) {
+                Ok(round) => *waiting_for = round,
+                Err(e) => {
+                    self.failed = Some(repeat_error(&e));
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+/// A copy of `e`, to report it again.
+///
+/// The source of a [`ProtocolError::Other`] can't be copied, so only its message is kept.
+fn repeat_error(e: &ProtocolError) -> ProtocolError {
+    match e {
+        ProtocolError::AssertionFailed(e) => ProtocolError::AssertionFailed(e.clone()),
+        ProtocolError::BadShare(p, e) => ProtocolError::BadShare(*p, e.clone()),
+        ProtocolError::BadProof(p, e) => ProtocolError::BadProof(*p, e.clone()),
+        ProtocolError::MissingMessage { from, wait } => {
+            ProtocolError::MissingMessage { from: *from, wait }
+        }
+        ProtocolError::OutOfOrder(what) => ProtocolError::OutOfOrder(what),
+        ProtocolError::Malformed(e) => ProtocolError::Malformed(e.clone()),
+        ProtocolError::KeyMismatch(e) => ProtocolError::KeyMismatch(e),
+        ProtocolError::Config(e) => ProtocolError::Config(e.clone()),
+        ProtocolError::Other(e) => ProtocolError::Other(e.to_string().into()),
+    }
+}
+
+fn validate(config: &SessionConfig) -> Result<(), InitializationError> {
+    config
+        .validate()
+        .map_err(|e| InitializationError::BadParameters(e.to_string()))
+}
+
+struct KeygenProtocol<C: CSCurve> {
+    config: SessionConfig,
+    step: usize,
+    waiting_for: Round,
+    state: Option<KeyshareState2<C>>,
+    buf: StepBuffer<RcvdKeyshareMessages<C>, KeygenOutput<C>>,
+}
+
+impl<C: CSCurve> Protocol for KeygenProtocol<C>
+where
+    C::Scalar: Serialize + for<'a> Deserialize<'a>,
+    C::AffinePoint: Serialize + for<'a> Deserialize<'a>,
+{
+    type Output = KeygenOutput<C>;
+
+    fn poke(&mut self) -> Result<Action<Self::Output>, ProtocolError> {
+        let config = &self.config;
+        let step = &mut self.step;
+        let state = &mut self.state;
+
+        self.buf.poke(
+            |buf| {
+                *step += 1;
+                let round = *step;
+                match round {
+                    1 => {
+                        let out = KeygenClient::cli_keygen_step_1::<C>(config)?;
+                        buf.send_many(round, &out.msgs_1);
+                        *state = Some(out.st_0);
+                    }
+                    2 | 3 => {
+                        let st = state.take().ok_or(ProtocolError::OutOfOrder("state"))?;
+                        let out = if round == 2 {
+                            KeygenClient::cli_keygen_step_2(config, st, &buf.msgs)?
+                        } else {
+                            KeygenClient::cli_keygen_step_3(config, st, &buf.msgs)?
+                        };
+                        buf.send_many(round, &out.msgs_1);
+                        *state = Some(out.st_0);
+                    }
+                    4 => {
+                        let st = state.take().ok_or(ProtocolError::OutOfOrder("state"))?;
+                        let out = KeygenClient::cli_keygen_step_4(config, st, &buf.msgs)?;
+                        for (to, msgs) in &out.msgs_1 {
+                            buf.send_private(round, *to, msgs);
+                        }
+                        *state = Some(out.st_0);
+                    }
+                    _ => {
+                        let st = state.take().ok_or(ProtocolError::OutOfOrder("state"))?;
+                        let out = KeygenClient::cli_keygen_step_5(config, st, &buf.msgs)?;
+                        buf.finish(out);
+                    }
+                }
+                Ok(round)
+            },
+            &mut self.waiting_for,
+        )
+    }
+
+    fn message(&mut self, from: Participant, data: MessageData) {
+        self.buf.message(from, data);
+    }
+}
+
+/// Run the step-based key generation as a [`Protocol`].
+///
+/// Each step waits for the previous round from every other participant,
+/// with the shares of step 4 sent privately.
+pub fn keygen<C: CSCurve>(
+    config: SessionConfig,
+) -> Result<impl Protocol<Output = KeygenOutput<C>>, InitializationError>
+where
+    C::Scalar: Serialize + for<'a> Deserialize<'a>,
+    C::AffinePoint: Serialize + for<'a> Deserialize<'a>,
+{
+    validate(&config)?;
+
+    Ok(KeygenProtocol {
+        buf: StepBuffer::new(
+            &config,
+            Envelope::new::<C>(ProtocolKind::Keygen, 0, &config.session_id),
+            RcvdKeyshareMessages::new(),
+        ),
+        config,
+        step: 0,
+        waiting_for: 0,
+        state: None,
+    })
+}
+
+/// The number of steps in the triple generation flow.
+const TRIPLES_STEPS: usize = 11;
+
+struct TriplesProtocol<C: CSCurve> {
+    config: SessionConfig,
+    /// Whether we play the multiplication sender, i.e. the server side.
+    is_sender: bool,
+    step: usize,
+    waiting_for: Round,
+    state: Option<TriplesState<C>>,
+    buf: StepBuffer<RcvdTriplesMessages<C>, TriplesGenManyResult<C>>,
+}
+
+/// Run one of the triple generation steps, returning the messages to send.
+fn triples_step<C: CSCurve>(
+    config: &SessionConfig,
+    is_sender: bool,
+    step: usize,
+    state: &mut Option<TriplesState<C>>,
+    msgs: RcvdTriplesMessages<C>,
+) -> Result<RcvdTriplesMessages<C>, ProtocolError> {
+    if step == 1 {
+        let (st, msgs) = if is_sender {
+            let out = TriplesServer2::triples_step_1::<C>(config)?;
+            (out.st_1, out.msgs_0)
+        } else {
+            let out = TriplesClient::triples_step_1::<C>(config)?;
+            (out.st_0, out.msgs_1)
+        };
+        *state = Some(st);
+        return Ok(msgs);
+    }
+
+    let st = state.take().ok_or(ProtocolError::OutOfOrder("state"))?;
+    let (st, msgs) = if is_sender {
+        let out = match step {
+            2 => TriplesServer2::triples_step_2(config, st, msgs),
+            3 => TriplesServer2::triples_step_3(config, st, msgs),
+            4 => TriplesServer2::triples_step_4(config, st, msgs),
+            5 => TriplesServer2::triples_step_5(config, st, msgs),
+            6 => TriplesServer2::triples_step_6(config, st, msgs),
+            7 => TriplesServer2::triples_step_7(config, st, msgs),
+            8 => TriplesServer2::triples_step_8(config, st, msgs),
+            9 => TriplesServer2::triples_step_9(config, st, msgs),
+            _ => TriplesServer2::triples_step_10(config, st, msgs),
+        }?;
+        (out.st_1, out.msgs_0)
+    } else {
+        let out = match step {
+            2 => TriplesClient::triples_step_2(config, st, msgs),
+            3 => TriplesClient::triples_step_3(config, st, msgs),
+            4 => TriplesClient::triples_step_4(config, st, msgs),
+            5 => TriplesClient::triples_step_5(config, st, msgs),
+            6 => TriplesClient::triples_step_6(config, st, msgs),
+            7 => TriplesClient::triples_step_7(config, st, msgs),
+            8 => TriplesClient::triples_step_8(config, st, msgs),
+            9 => TriplesClient::triples_step_9(config, st, msgs),
+            _ => TriplesClient::triples_step_10(config, st, msgs),
+        }?;
+        (out.st_0, out.msgs_1)
+    };
+    *state = Some(st);
+
+    Ok(msgs)
+}
+
+impl<C: CSCurve> Protocol for TriplesProtocol<C>
+where
+    C::Scalar: Serialize + for<'a> Deserialize<'a>,
+    C::AffinePoint: Serialize + for<'a> Deserialize<'a>,
+{
+    type Output = TriplesGenManyResult<C>;
+
+    fn poke(&mut self) -> Result<Action<Self::Output>, ProtocolError> {
+        let config = &self.config;
+        let is_sender = self.is_sender;
+        let step = &mut self.step;
+        let state = &mut self.state;
+
+        self.buf.poke(
+            |buf| {
+                *step += 1;
+                let round = *step;
+                if round == TRIPLES_STEPS {
+                    let st = state.take().ok_or(ProtocolError::OutOfOrder("state"))?;
+                    let msgs = buf.msgs.clone();
+                    let out = if is_sender {
+                        TriplesServer2::triples_step_11(config, st, msgs)?
+                    } else {
+                        TriplesClient::triples_step_11(config, st, msgs)?
+                    };
+                    buf.finish(out);
+                    return Ok(round);
+                }
+
+                let msgs = triples_step(config, is_sender, round, state, buf.msgs.clone())?;
+                // Sent even when empty, since the other side waits on every round.
+                buf.send_many(round, &msgs);
+
+                // The client's step n follows the server's step n - 1, while the
+                // server's step n follows the client's step n. Both final steps
+                // only need the last round of messages.
+                if is_sender && round + 1 < TRIPLES_STEPS {
+                    Ok(round + 1)
+                } else {
+                    Ok(round)
+                }
+            },
+            &mut self.waiting_for,
+        )
+    }
+
+    fn message(&mut self, from: Participant, data: MessageData) {
+        self.buf.message(from, data);
+    }
+}
+
+/// Run the step-based triple generation as a [`Protocol`].
+///
+/// This is a two party flow. The participant with the smaller id runs the
+/// client steps, and the other one the server steps.
+pub fn triples<C: CSCurve>(
+    config: SessionConfig,
+) -> Result<impl Protocol<Output = TriplesGenManyResult<C>>, InitializationError>
+where
+    C::Scalar: Serialize + for<'a> Deserialize<'a>,
+    C::AffinePoint: Serialize + for<'a> Deserialize<'a>,
+{
+    config
+        .validate_two_party()
+        .map_err(|e| InitializationError::BadParameters(e.to_string()))?;
+
+    let is_sender = config.others().iter().all(|p| *p < config.me);
+
+    Ok(TriplesProtocol {
+        buf: StepBuffer::new(
+            &config,
+            Envelope::new::<C>(ProtocolKind::Triples, 0, &config.session_id),
+            RcvdTriplesMessages::new(),
+        ),
+        config,
+        is_sender,
+        step: 0,
+        waiting_for: 0,
+        state: None,
+    })
+}
+
+struct PresignProtocol<C: CSCurve> {
+    config: SessionConfig,
+    step: usize,
+    waiting_for: Round,
+    args: Option<PresignArgs<C>>,
+    state: Option<PresignState2<C>>,
+    buf: StepBuffer<RcvdPresignMessages2<C>, PresignOutput<C>>,
+}
+
+struct PresignArgs<C: CSCurve> {
+    pub_0: TriplePub<C>,
+    pub_1: TriplePub<C>,
+    share_0: TripleShare<C>,
+    share_1: TripleShare<C>,
+    keygen_out: KeygenOutput<C>,
+}
+
+impl<C: CSCurve> Protocol for PresignProtocol<C>
+where
+    C::Scalar: Serialize + for<'a> Deserialize<'a>,
+    C::AffinePoint: Serialize + for<'a> Deserialize<'a>,
+{
+    type Output = PresignOutput<C>;
+
+    fn poke(&mut self) -> Result<Action<Self::Output>, ProtocolError> {
+        let config = &self.config;
+        let step = &mut self.step;
+        let args = &mut self.args;
+        let state = &mut self.state;
+
+        self.buf.poke(
+            |buf| {
+                *step += 1;
+                let round = *step;
+                match round {
+                    1 => {
+                        let PresignArgs {
+                            pub_0,
+                            pub_1,
+                            share_0,
+                            share_1,
+                            keygen_out,
+                        } = args
+                            .take()
+                            .ok_or(ProtocolError::OutOfOrder("presign arguments"))?;
+                        let out = PresignClient::presign_step_1(
+                            config, pub_0, pub_1, share_0, share_1, keygen_out,
+                        )?;
+                        buf.send_many(round, &out.msgs_1);
+                        *state = Some(out.st_0);
+                    }
+                    2 => {
+                        let out = PresignClient::presign_step_2(
+                            config,
+                            state.take().ok_or(ProtocolError::OutOfOrder("state"))?,
+                        )?;
+                        buf.send_many(round, &out.msgs_1);
+                        *state = Some(out.st_0);
+                    }
+                    _ => {
+                        let st = state.take().ok_or(ProtocolError::OutOfOrder("state"))?;
+                        let out = PresignClient::presign_step_3(config, st, &buf.msgs)?;
+                        buf.finish(out);
+                    }
+                }
+                Ok(round)
+            },
+            &mut self.waiting_for,
+        )
+    }
+
+    fn message(&mut self, from: Participant, data: MessageData) {
+        self.buf.message(from, data);
+    }
+}
+
+/// Run the step-based presigning as a [`Protocol`].
+///
+/// The triples and their shares must come from a triple generation over the
+/// same participants.
+pub fn presign<C: CSCurve>(
+    config: SessionConfig,
+    pub_0: TriplePub<C>,
+    pub_1: TriplePub<C>,
+    share_0: TripleShare<C>,
+    share_1: TripleShare<C>,
+    keygen_out: KeygenOutput<C>,
+) -> Result<impl Protocol<Output = PresignOutput<C>>, InitializationError>
+where
+    C::Scalar: Serialize + for<'a> Deserialize<'a>,
+    C::AffinePoint: Serialize + for<'a> Deserialize<'a>,
+{
+    validate(&config)?;
+
+    Ok(PresignProtocol {
+        buf: StepBuffer::new(
+            &config,
+            Envelope::new::<C>(ProtocolKind::Presign, 0, &config.session_id),
+            RcvdPresignMessages2::new(),
+        ),
+        config,
+        step: 0,
+        waiting_for: 0,
+        args: Some(PresignArgs {
+            pub_0,
+            pub_1,
+            share_0,
+            share_1,
+            keygen_out,
+        }),
+        state: None,
+    })
+}
+
+struct SignProtocol<C: CSCurve> {
+    config: SessionConfig,
+    step: usize,
+    waiting_for: Round,
+    presign_out: PresignOutput<C>,
+    msg_hash: C::Scalar,
+    state: Option<SignState2<C>>,
+    buf: StepBuffer<RcvdSignMessages<C>, SignOutput<C>>,
+}
+
+impl<C: CSCurve> Protocol for SignProtocol<C>
+where
+    C::Scalar: Serialize + for<'a> Deserialize<'a>,
+    C::AffinePoint: Serialize + for<'a> Deserialize<'a>,
+{
+    type Output = SignOutput<C>;
+
+    fn poke(&mut self) -> Result<Action<Self::Output>, ProtocolError> {
+        let config = &self.config;
+        let step = &mut self.step;
+        let presign_out = &self.presign_out;
+        let msg_hash = self.msg_hash;
+        let state = &mut self.state;
+
+        self.buf.poke(
+            |buf| {
+                *step += 1;
+                let round = *step;
+                match round {
+                    1 => {
+                        let out = SignClient::sign_step_1(config, msg_hash, presign_out.clone())?;
+                        buf.send_many(round, &out.msgs_1);
+                        *state = Some(out.st_0);
+                    }
+                    _ => {
+                        let mut st = state.take().ok_or(ProtocolError::OutOfOrder("state"))?;
+                        let out = SignClient::sign_step_2(
+                            config,
+                            &mut st,
+                            &buf.msgs,
+                            presign_out.clone(),
+                        )?;
+                        buf.finish(out);
+                    }
+                }
+                Ok(round)
+            },
+            &mut self.waiting_for,
+        )
+    }
+
+    fn message(&mut self, from: Participant, data: MessageData) {
+        self.buf.message(from, data);
+    }
+}
+
+/// Run the step-based signing as a [`Protocol`].
+pub fn sign<C: CSCurve>(
+    config: SessionConfig,
+    presign_out: PresignOutput<C>,
+    msg_hash: <C as CurveArithmetic>::Scalar,
+) -> Result<impl Protocol<Output = SignOutput<C>>, InitializationError>
+where
+    C::Scalar: Serialize + for<'a> Deserialize<'a>,
+    C::AffinePoint: Serialize + for<'a> Deserialize<'a>,
+{
+    validate(&config)?;
+
+    Ok(SignProtocol {
+        buf: StepBuffer::new(
+            &config,
+            Envelope::new::<C>(ProtocolKind::Sign, 0, &config.session_id),
+            RcvdSignMessages::new(),
+        ),
+        config,
+        step: 0,
+        waiting_for: 0,
+        presign_out,
+        msg_hash,
+        state: None,
+    })
+}
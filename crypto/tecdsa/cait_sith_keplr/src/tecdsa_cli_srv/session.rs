@@ -0,0 +1,215 @@
+use std::{error, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::participants::ParticipantList;
+use crate::protocol::{Participant, ProtocolError};
+use crate::serde::encode;
+
+/// The parameters shared by every step of a client/server session.
+///
+/// All parties in a session must agree on `participants`, `threshold`,
+/// `session_id` and `key_id`, while `me` identifies the party running the step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionConfig {
+    pub participants: Vec<Participant>,
+    pub me: Participant,
+    pub threshold: usize,
+    /// Unique to each run of a flow, and bound into its transcripts and commitments,
+    /// so that messages from one session are rejected by any other.
+    ///
+    /// This also tags every message, see [`Envelope`](super::envelope::Envelope).
+    pub session_id: String,
+    /// The key a presignature or signature is made with, required by those flows.
+    #[serde(default)]
+    pub key_id: String,
+}
+
+impl SessionConfig {
+    pub fn new(
+        participants: Vec<Participant>,
+        me: Participant,
+        threshold: usize,
+        session_id: impl Into<String>,
+    ) -> Self {
+        SessionConfig {
+            participants,
+            me,
+            threshold,
+            session_id: session_id.into(),
+            key_id: String::new(),
+        }
+    }
+
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = key_id.into();
+        self
+    }
+
+    /// The bytes binding the state of a flow to this session, and to the key if any.
+    pub fn transcript_id(&self) -> Vec<u8> {
+        encode(&(&self.session_id, &self.key_id))
+    }
+
+    /// Check that this configuration describes a valid session.
+    pub fn validate(&self) -> Result<(), ProtocolError> {
+        if self.session_id.is_empty() {
+            return Err(ConfigError::EmptySessionId.into());
+        }
+
+        if self.participants.len() < 2 {
+            return Err(ConfigError::TooFewParticipants(self.participants.len()).into());
+        };
+
+        // Spec 1.1
+        if self.threshold > self.participants.len() {
+            return Err(ConfigError::ThresholdTooHigh {
+                threshold: self.threshold,
+                participants: self.participants.len(),
+            }
+            .into());
+        }
+
+        let participant_list = self.participant_list()?;
+
+        if !participant_list.contains(self.me) {
+            return Err(ConfigError::NotAParticipant(self.me).into());
+        }
+
+        Ok(())
+    }
+
+    /// Like [`SessionConfig::validate`], but also requires a key id.
+    ///
+    /// Presigning and signing use this, so that they are bound to a single key.
+    pub fn validate_with_key(&self) -> Result<(), ProtocolError> {
+        self.validate()?;
+
+        if self.key_id.is_empty() {
+            return Err(ConfigError::EmptyKeyId.into());
+        }
+
+        Ok(())
+    }
+
+    /// Like [`SessionConfig::validate`], but also requires exactly two participants.
+    ///
+    /// Triple generation through the step API only supports a pair of parties,
+    /// as one of them runs the receiving side of each multiplication, and the
+    /// other one the sending side.
+    pub fn validate_two_party(&self) -> Result<(), ProtocolError> {
+        self.validate()?;
+
+        if self.participants.len() != 2 {
+            return Err(ConfigError::NotTwoParties(self.participants.len()).into());
+        }
+
+        Ok(())
+    }
+
+    pub fn participant_list(&self) -> Result<ParticipantList, ProtocolError> {
+        ParticipantList::new(&self.participants)
+            .ok_or_else(|| ConfigError::DuplicateParticipants.into())
+    }
+
+    /// The old participants taking part in a reshare to this session.
+    ///
+    /// These must be enough to reconstruct a key shared among `old_participants`
+    /// with `old_threshold`.
+    pub fn old_subset(
+        &self,
+        old_participants: &[Participant],
+        old_threshold: usize,
+    ) -> Result<ParticipantList, ProtocolError> {
+        let old_participants =
+            ParticipantList::new(old_participants).ok_or(ConfigError::DuplicateOldParticipants)?;
+
+        let old_subset = old_participants.intersection(&self.participant_list()?);
+        if old_subset.len() < old_threshold {
+            return Err(ConfigError::NotEnoughOldParticipants {
+                found: old_subset.len(),
+                threshold: old_threshold,
+            }
+            .into());
+        }
+
+        Ok(old_subset)
+    }
+
+    /// The participants other than `me`.
+    pub fn others(&self) -> Vec<Participant> {
+        self.participants
+            .iter()
+            .copied()
+            .filter(|p| *p != self.me)
+            .collect()
+    }
+}
+
+/// Why a [`SessionConfig`] does not describe a valid session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    EmptySessionId,
+    EmptyKeyId,
+    TooFewParticipants(usize),
+    /// The flow only runs between two parties, but the config names this many.
+    NotTwoParties(usize),
+    ThresholdTooHigh {
+        threshold: usize,
+        participants: usize,
+    },
+    DuplicateParticipants,
+    /// A party the session needs, such as `me`, is not one of the participants.
+    NotAParticipant(Participant),
+    DuplicateOldParticipants,
+    /// Too few of the old participants are left to reconstruct the key for resharing.
+    NotEnoughOldParticipants {
+        found: usize,
+        threshold: usize,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::EmptySessionId => write!(f, "session id cannot be empty"),
+            ConfigError::EmptyKeyId => write!(f, "key id cannot be empty"),
+            ConfigError::TooFewParticipants(n) => {
+                write!(f, "participant count cannot be < 2, found: {}", n)
+            }
+            ConfigError::NotTwoParties(n) => {
+                write!(f, "participant count must be 2, found: {}", n)
+            }
+            ConfigError::ThresholdTooHigh {
+                threshold,
+                participants,
+            } => write!(
+                f,
+                "threshold must be <= participant count, found {} > {}",
+                threshold, participants
+            ),
+            ConfigError::DuplicateParticipants => {
+                write!(f, "participant list cannot contain duplicates")
+            }
+            ConfigError::NotAParticipant(p) => {
+                write!(f, "participant list must contain {:?}", p)
+            }
+            ConfigError::DuplicateOldParticipants => {
+                write!(f, "old participant list cannot contain duplicates")
+            }
+            ConfigError::NotEnoughOldParticipants { found, threshold } => write!(
+                f,
+                "not enough old participants to reconstruct private key for resharing, found {} < {}",
+                found, threshold
+            ),
+        }
+    }
+}
+
+impl error::Error for ConfigError {}
+
+impl From<ConfigError> for ProtocolError {
+    fn from(e: ConfigError) -> Self {
+        ProtocolError::Config(e)
+    }
+}
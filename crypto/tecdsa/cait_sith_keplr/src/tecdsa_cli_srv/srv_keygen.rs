@@ -1,9 +1,11 @@
-use k256::Secp256k1;
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::keyshare::{steps_2, KeyshareState2, RcvdKeyshareMessages};
 use crate::protocol::Participant;
 use crate::protocol::ProtocolError;
+use crate::tecdsa_cli_srv::session::{ConfigError, SessionConfig};
 use crate::{CSCurve, KeygenOutput};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -16,96 +18,286 @@ pub struct ServerKeygenStepOutput<C: CSCurve> {
     pub msgs_0: RcvdKeyshareMessages<C>,
 }
 
+/// Output of a step sending private messages, keyed by recipient.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(bound = "
+    C::Scalar: Serialize + for<'a> Deserialize<'a>,
+    C::AffinePoint: Serialize + for<'a> Deserialize<'a>,
+")]
+pub struct ServerKeygenPrivateStepOutput<C: CSCurve> {
+    pub st_1: KeyshareState2<C>,
+    #[serde(with = "crate::protocol::participant_serde")]
+    pub msgs_0: HashMap<Participant, RcvdKeyshareMessages<C>>,
+}
+
 pub struct KeygenServer {}
 
 impl KeygenServer {
-    pub fn srv_keygen_step_1() -> Result<ServerKeygenStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_1 = participants[1];
+    pub fn srv_keygen_step_1<C: CSCurve>(
+        config: &SessionConfig,
+    ) -> Result<ServerKeygenStepOutput<C>, ProtocolError> {
+        config.validate()?;
 
-        let threshold = 2;
+        let p_1 = config.me;
 
         let mut msgs_0 = RcvdKeyshareMessages::new();
 
-        let mut st_1 = KeyshareState2::<Secp256k1>::new(participants.clone(), threshold);
+        let mut st_1 = KeyshareState2::<C>::new(
+            config.participants.clone(),
+            config.threshold,
+            config.transcript_id(),
+        );
 
-        steps_2::step_1::<Secp256k1>(&mut st_1, None).unwrap();
+        steps_2::step_1::<C>(&mut st_1)?;
 
         // send_many
-        msgs_0.wait_0.insert(p_1, st_1.commitment.unwrap());
+        msgs_0.wait_0.insert(
+            p_1,
+            st_1.commitment
+                .ok_or(ProtocolError::OutOfOrder("commitment"))?,
+        );
 
         Ok(ServerKeygenStepOutput { st_1, msgs_0 })
     }
 
-    pub fn srv_keygen_step_2(
-        mut st_1: KeyshareState2<Secp256k1>,
-        msgs_1: &RcvdKeyshareMessages<Secp256k1>,
-    ) -> Result<ServerKeygenStepOutput<Secp256k1>, ProtocolError> {
-        let p_1 = st_1.participants[1];
+    pub fn srv_keygen_step_2<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: KeyshareState2<C>,
+        msgs_1: &RcvdKeyshareMessages<C>,
+    ) -> Result<ServerKeygenStepOutput<C>, ProtocolError> {
+        let p_1 = config.me;
 
-        let mut msgs_0 = RcvdKeyshareMessages::<Secp256k1>::new();
+        let mut msgs_0 = RcvdKeyshareMessages::<C>::new();
 
-        steps_2::step_2::<Secp256k1>(&mut st_1, &msgs_1, p_1).unwrap();
+        steps_2::step_2::<C>(&mut st_1, &msgs_1, p_1)?;
 
-        msgs_0.wait_1.insert(p_1, st_1.confirmation.unwrap());
+        msgs_0.wait_1.insert(
+            p_1,
+            st_1.confirmation
+                .ok_or(ProtocolError::OutOfOrder("confirmation"))?,
+        );
 
         Ok(ServerKeygenStepOutput { st_1, msgs_0 })
     }
 
-    pub fn srv_keygen_step_3(
-        mut st_1: KeyshareState2<Secp256k1>,
-        _msgs_1: &RcvdKeyshareMessages<Secp256k1>,
-    ) -> Result<ServerKeygenStepOutput<Secp256k1>, ProtocolError> {
-        let p_1 = st_1.participants[1];
-        steps_2::step_3::<Secp256k1>(&mut st_1, p_1).unwrap();
+    pub fn srv_keygen_step_3<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: KeyshareState2<C>,
+        _msgs_1: &RcvdKeyshareMessages<C>,
+    ) -> Result<ServerKeygenStepOutput<C>, ProtocolError> {
+        let p_1 = config.me;
+        steps_2::step_3::<C>(&mut st_1, p_1)?;
 
-        let mut msgs_0 = RcvdKeyshareMessages::<Secp256k1>::new();
+        let mut msgs_0 = RcvdKeyshareMessages::<C>::new();
 
-        // 1 => 0
+        // 1 => others
         msgs_0.wait_2.insert(
             p_1,
             (
-                st_1.big_f.clone().unwrap(),
-                st_1.randomizer.clone().unwrap(),
-                st_1.phi_proof.clone().unwrap(),
+                st_1.big_f
+                    .clone()
+                    .ok_or(ProtocolError::OutOfOrder("big_f"))?,
+                st_1.randomizer
+                    .clone()
+                    .ok_or(ProtocolError::OutOfOrder("randomizer"))?,
+                st_1.phi_proof
+                    .clone()
+                    .ok_or(ProtocolError::OutOfOrder("phi_proof"))?,
             ),
         );
 
         Ok(ServerKeygenStepOutput { st_1, msgs_0 })
     }
 
-    pub fn srv_keygen_step_4(
-        mut st_1: KeyshareState2<Secp256k1>,
-        _msgs_1: &RcvdKeyshareMessages<Secp256k1>,
-    ) -> Result<ServerKeygenStepOutput<Secp256k1>, ProtocolError> {
-        let p_0 = st_1.participants[0];
-        let p_1 = st_1.participants[1];
+    pub fn srv_keygen_step_4<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: KeyshareState2<C>,
+        _msgs_1: &RcvdKeyshareMessages<C>,
+    ) -> Result<ServerKeygenPrivateStepOutput<C>, ProtocolError> {
+        let p_1 = config.me;
 
-        let out_0 = steps_2::step_4(&mut st_1, p_1).unwrap();
+        let out_0 = steps_2::step_4(&mut st_1, p_1)?;
 
-        let mut msgs_0 = RcvdKeyshareMessages::<Secp256k1>::new();
+        let mut msgs_0 = HashMap::new();
 
-        // 1 => {0} (send private)
-        let x_i_j_for_0 = out_0.x_i_js.get(&p_0).unwrap();
-        msgs_0.wait_3.insert(p_1, x_i_j_for_0.clone());
+        // 1 => {p} (send private)
+        for p in config.others() {
+            let x_i_j_for_p = out_0.x_i_js.get(&p).ok_or_else(|| {
+                ProtocolError::AssertionFailed(format!("no share computed for {p:?}"))
+            })?;
 
-        Ok(ServerKeygenStepOutput { st_1, msgs_0 })
+            let mut msgs_p = RcvdKeyshareMessages::<C>::new();
+            msgs_p.wait_3.insert(p_1, x_i_j_for_p.clone());
+            msgs_0.insert(p, msgs_p);
+        }
+
+        Ok(ServerKeygenPrivateStepOutput { st_1, msgs_0 })
     }
 
-    pub fn srv_keygen_step_5(
-        mut st_1: KeyshareState2<Secp256k1>,
-        msgs_1: &RcvdKeyshareMessages<Secp256k1>,
-    ) -> Result<KeygenOutput<Secp256k1>, ProtocolError> {
-        let p_1 = st_1.participants[1];
+    pub fn srv_keygen_step_5<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: KeyshareState2<C>,
+        msgs_1: &RcvdKeyshareMessages<C>,
+    ) -> Result<KeygenOutput<C>, ProtocolError> {
+        let p_1 = config.me;
 
-        let (sk_1, pk_1) = steps_2::step_5(&mut st_1, &msgs_1, p_1, None).unwrap();
+        let (sk_1, pk_1) = steps_2::step_5(&mut st_1, &msgs_1, p_1, None)?;
 
-        let keygen_1: KeygenOutput<Secp256k1> = KeygenOutput {
-            public_key: pk_1,
-            private_share: sk_1,
-        };
+        let keygen_1: KeygenOutput<C> = KeygenOutput::new(
+            sk_1,
+            pk_1,
+            st_1.big_f
+                .take()
+                .ok_or(ProtocolError::OutOfOrder("big_f"))?,
+        );
 
         Ok(keygen_1)
     }
+
+    /// Start refreshing the shares of an existing key.
+    ///
+    /// The refresh then continues with `srv_keygen_step_2` through `srv_keygen_step_4`,
+    /// and finishes with [`Self::srv_refresh_step_5`]. The participants and threshold
+    /// must be the same as those the key was generated with.
+    pub fn srv_refresh_step_1<C: CSCurve>(
+        config: &SessionConfig,
+        keygen_1: KeygenOutput<C>,
+    ) -> Result<ServerKeygenStepOutput<C>, ProtocolError> {
+        config.validate()?;
+
+        let p_1 = config.me;
+
+        let mut st_1 = KeyshareState2::<C>::new(
+            config.participants.clone(),
+            config.threshold,
+            config.transcript_id(),
+        );
+
+        steps_2::refresh_step_1::<C>(&mut st_1, p_1, keygen_1.private_share)?;
+
+        let mut msgs_0 = RcvdKeyshareMessages::<C>::new();
+
+        msgs_0.wait_0.insert(
+            p_1,
+            st_1.commitment
+                .ok_or(ProtocolError::OutOfOrder("commitment"))?,
+        );
+
+        Ok(ServerKeygenStepOutput { st_1, msgs_0 })
+    }
+
+    /// Finish a refresh, checking that the key is still `public_key`.
+    pub fn srv_refresh_step_5<C: CSCurve>(
+        config: &SessionConfig,
+        st_1: KeyshareState2<C>,
+        msgs_1: &RcvdKeyshareMessages<C>,
+        public_key: C::AffinePoint,
+    ) -> Result<KeygenOutput<C>, ProtocolError> {
+        Self::srv_reshare_step_5(config, st_1, msgs_1, public_key)
+    }
+
+    /// Start resharing an existing key to the participants and threshold of `config`.
+    ///
+    /// The key was shared among `old_participants` with `old_threshold`. Parties
+    /// joining with no old share pass `None`, and receive a fresh share.
+    /// The reshare then continues with `srv_keygen_step_2` through `srv_keygen_step_4`,
+    /// and finishes with [`Self::srv_reshare_step_5`].
+    pub fn srv_reshare_step_1<C: CSCurve>(
+        config: &SessionConfig,
+        old_participants: &[Participant],
+        old_threshold: usize,
+        my_share: Option<C::Scalar>,
+    ) -> Result<ServerKeygenStepOutput<C>, ProtocolError> {
+        config.validate()?;
+
+        let old_subset = config.old_subset(old_participants, old_threshold)?;
+
+        let p_1 = config.me;
+
+        let mut st_1 = KeyshareState2::<C>::new(
+            config.participants.clone(),
+            config.threshold,
+            config.transcript_id(),
+        );
+
+        steps_2::reshare_step_1::<C>(&mut st_1, &old_subset, p_1, my_share)?;
+
+        let mut msgs_0 = RcvdKeyshareMessages::<C>::new();
+
+        msgs_0.wait_0.insert(
+            p_1,
+            st_1.commitment
+                .ok_or(ProtocolError::OutOfOrder("commitment"))?,
+        );
+
+        Ok(ServerKeygenStepOutput { st_1, msgs_0 })
+    }
+
+    /// Finish a reshare, checking that the key is still `public_key`.
+    pub fn srv_reshare_step_5<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: KeyshareState2<C>,
+        msgs_1: &RcvdKeyshareMessages<C>,
+        public_key: C::AffinePoint,
+    ) -> Result<KeygenOutput<C>, ProtocolError> {
+        let p_1 = config.me;
+
+        let (private_share, public_key) =
+            steps_2::step_5(&mut st_1, msgs_1, p_1, Some(public_key.into()))?;
+
+        Ok(KeygenOutput::new(
+            private_share,
+            public_key,
+            st_1.big_f
+                .take()
+                .ok_or(ProtocolError::OutOfOrder("big_f"))?,
+        ))
+    }
+
+    /// Start importing an existing private key, known only to `importer`.
+    ///
+    /// The importing party passes its key as `secret`, everyone else passes `None`.
+    /// The import then continues with `srv_keygen_step_2` through `srv_keygen_step_4`,
+    /// and finishes with [`Self::srv_import_step_5`].
+    pub fn srv_import_step_1<C: CSCurve>(
+        config: &SessionConfig,
+        importer: Participant,
+        secret: Option<C::Scalar>,
+    ) -> Result<ServerKeygenStepOutput<C>, ProtocolError> {
+        config.validate()?;
+
+        if !config.participant_list()?.contains(importer) {
+            return Err(ConfigError::NotAParticipant(importer).into());
+        }
+
+        let p_1 = config.me;
+
+        let mut st_1 = KeyshareState2::<C>::new(
+            config.participants.clone(),
+            config.threshold,
+            config.transcript_id(),
+        );
+
+        steps_2::import_step_1::<C>(&mut st_1, p_1, importer, secret)?;
+
+        let mut msgs_0 = RcvdKeyshareMessages::<C>::new();
+
+        msgs_0.wait_0.insert(
+            p_1,
+            st_1.commitment
+                .ok_or(ProtocolError::OutOfOrder("commitment"))?,
+        );
+
+        Ok(ServerKeygenStepOutput { st_1, msgs_0 })
+    }
+
+    /// Finish an import, checking that the shared key matches `public_key`.
+    pub fn srv_import_step_5<C: CSCurve>(
+        config: &SessionConfig,
+        st_1: KeyshareState2<C>,
+        msgs_1: &RcvdKeyshareMessages<C>,
+        public_key: C::AffinePoint,
+    ) -> Result<KeygenOutput<C>, ProtocolError> {
+        Self::srv_reshare_step_5(config, st_1, msgs_1, public_key)
+    }
 }
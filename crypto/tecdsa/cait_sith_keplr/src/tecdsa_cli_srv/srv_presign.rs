@@ -1,42 +1,24 @@
 use elliptic_curve::ScalarPrimitive;
-use k256::Secp256k1;
 
 use crate::presign::{steps_2, PresignState2, RcvdPresignMessages2};
-use crate::protocol::Participant;
 use crate::protocol::ProtocolError;
+use crate::tecdsa_cli_srv::session::SessionConfig;
 use crate::triples::{TriplePub, TripleShare};
-use crate::{KeygenOutput, PresignOutput};
+use crate::{CSCurve, KeygenOutput, PresignOutput};
 
 pub struct PresignServer {}
 
 impl PresignServer {
-    pub fn presign_step_1(
-        pub_0: TriplePub<Secp256k1>,
-        pub_1: TriplePub<Secp256k1>,
-        share_0_1: TripleShare<Secp256k1>,
-        share_1_1: TripleShare<Secp256k1>,
-        keygen_1: KeygenOutput<Secp256k1>,
-    ) -> Result<(PresignState2<Secp256k1>, RcvdPresignMessages2<Secp256k1>), ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let threshold = 2;
-
-        if participants.len() < 2 {
-            return Err(ProtocolError::Other(
-                format!(
-                    "participant count cannot be < 2, found: {}",
-                    participants.len()
-                )
-                .into(),
-            ));
-        };
-
-        // Spec 1.1
-        if threshold > participants.len() {
-            return Err(ProtocolError::Other(
-                "threshold must be <= participant count".to_string().into(),
-            ));
-        }
+    pub fn presign_step_1<C: CSCurve>(
+        config: &SessionConfig,
+        pub_0: TriplePub<C>,
+        pub_1: TriplePub<C>,
+        share_0_1: TripleShare<C>,
+        share_1_1: TripleShare<C>,
+        keygen_1: KeygenOutput<C>,
+    ) -> Result<(PresignState2<C>, RcvdPresignMessages2<C>), ProtocolError> {
+        config.validate_with_key()?;
+
         // NOTE: We omit the check that the new participant set was present for
         // the triple generation, because presumably they need to have been present
         // in order to have shares.
@@ -57,8 +39,9 @@ impl PresignServer {
         // ];
 
         let mut state_1 = PresignState2::new(
-            participants.clone(),
-            threshold,
+            config.participants.clone(),
+            config.threshold,
+            config.transcript_id(),
             keygen_1,
             pub_0,
             pub_1,
@@ -66,44 +49,55 @@ impl PresignServer {
             share_1_1,
         );
 
-        let mut msgs_0 = RcvdPresignMessages2::<Secp256k1>::new();
+        let mut msgs_0 = RcvdPresignMessages2::<C>::new();
 
-        let p_1 = participants.get(1).unwrap();
+        let p_1 = config.me;
 
-        let out = steps_2::step_1(&mut state_1, *p_1, *p_1).unwrap();
+        let out = steps_2::step_1(&mut state_1, p_1, p_1)?;
 
-        msgs_0.wait_0.insert(*p_1, out.kd_i.into());
+        msgs_0.wait_0.insert(p_1, out.kd_i.into());
+        msgs_0.wait_0_commitments.insert(
+            p_1,
+            state_1
+                .share_commitments
+                .clone()
+                .ok_or(ProtocolError::OutOfOrder("share_commitments"))?,
+        );
+        msgs_0.wait_0_confirmations.insert(
+            p_1,
+            state_1
+                .confirmation
+                .ok_or(ProtocolError::OutOfOrder("confirmation"))?,
+        );
 
         Ok((state_1, msgs_0))
     }
 
-    pub fn presign_step_2(
-        mut st_1: PresignState2<Secp256k1>,
-    ) -> Result<(PresignState2<Secp256k1>, RcvdPresignMessages2<Secp256k1>), ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    pub fn presign_step_2<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: PresignState2<C>,
+    ) -> Result<(PresignState2<C>, RcvdPresignMessages2<C>), ProtocolError> {
+        let p_1 = config.me;
 
-        let p_1 = participants.get(1).unwrap();
+        let mut msgs_0 = RcvdPresignMessages2::<C>::new();
 
-        let mut msgs_0 = RcvdPresignMessages2::<Secp256k1>::new();
+        let out = steps_2::step_2(&mut st_1)?;
 
-        let out = steps_2::step_2(&mut st_1).unwrap();
-
-        let ka_i: ScalarPrimitive<Secp256k1> = out.ka_i.into();
-        let xb_i: ScalarPrimitive<Secp256k1> = out.xb_i.into();
-        msgs_0.wait_1.insert(*p_1, (ka_i, xb_i));
+        let ka_i: ScalarPrimitive<C> = out.ka_i.into();
+        let xb_i: ScalarPrimitive<C> = out.xb_i.into();
+        msgs_0.wait_1.insert(p_1, (ka_i, xb_i));
 
         Ok((st_1, msgs_0))
     }
 
-    pub fn presign_step_3(
-        mut st_1: PresignState2<Secp256k1>,
-        msgs_1: &RcvdPresignMessages2<Secp256k1>,
-    ) -> Result<PresignOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_1 = participants.get(1).unwrap();
+    pub fn presign_step_3<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: PresignState2<C>,
+        msgs_1: &RcvdPresignMessages2<C>,
+    ) -> Result<PresignOutput<C>, ProtocolError> {
+        let p_1 = config.me;
 
-        let out = steps_2::step_3(&mut st_1, &msgs_1, *p_1).unwrap();
+        let out = steps_2::step_3(&mut st_1, msgs_1, p_1)?;
 
         Ok(out)
     }
@@ -1,10 +1,9 @@
 use elliptic_curve::CurveArithmetic;
-use k256::Secp256k1;
 use serde::{Deserialize, Serialize};
 
-use crate::protocol::Participant;
 use crate::protocol::ProtocolError;
 use crate::sign::{steps_2, RcvdSignMessages, SignOutput, SignState2};
+use crate::tecdsa_cli_srv::session::SessionConfig;
 use crate::{CSCurve, PresignOutput};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -20,57 +19,44 @@ pub struct ServerSignStepOutput<C: CSCurve> {
 pub struct SignServer {}
 
 impl SignServer {
-    pub fn sign_step_1(
-        msg_hash: <Secp256k1 as CurveArithmetic>::Scalar,
-        presig_1: PresignOutput<Secp256k1>,
-    ) -> Result<ServerSignStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    pub fn sign_step_1<C: CSCurve>(
+        config: &SessionConfig,
+        msg_hash: <C as CurveArithmetic>::Scalar,
+        presig_1: PresignOutput<C>,
+    ) -> Result<ServerSignStepOutput<C>, ProtocolError> {
+        config.validate_with_key()?;
 
-        let threshold = 2;
+        let p_1 = config.me;
 
-        if participants.len() < 2 {
-            return Err(ProtocolError::Other(
-                format!(
-                    "participant count cannot be < 2, found: {}",
-                    participants.len()
-                )
-                .into(),
-            ));
-        };
+        let mut st_1 = SignState2::<C>::new(
+            config.participants.clone(),
+            config.threshold,
+            config.transcript_id(),
+        );
 
-        let p_1 = participants.get(1).unwrap();
+        let mut msgs_0 = RcvdSignMessages::<C>::new();
 
-        let mut st_1 = SignState2::<Secp256k1>::new(participants.clone(), threshold);
+        let out = steps_2::step_1(&mut st_1, p_1, msg_hash, presig_1)?;
 
-        let mut msgs_0 = RcvdSignMessages::<Secp256k1>::new();
-
-        let out = steps_2::step_1(&mut st_1, *p_1, msg_hash, presig_1).unwrap();
-
-        msgs_0.wait_0.insert(*p_1, out.s_i);
+        msgs_0.wait_0.insert(p_1, out.s_i);
+        msgs_0.wait_0_confirmations.insert(
+            p_1,
+            st_1.confirmation
+                .ok_or(ProtocolError::OutOfOrder("confirmation"))?,
+        );
 
         Ok(ServerSignStepOutput { st_1, msgs_0 })
     }
 
-    pub fn sign_step_2(
-        st_1: &mut SignState2<Secp256k1>,
-        msgs_1: &RcvdSignMessages<Secp256k1>,
-        presig_1: PresignOutput<Secp256k1>,
-    ) -> Result<SignOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        if participants.len() < 2 {
-            return Err(ProtocolError::Other(
-                format!(
-                    "participant count cannot be < 2, found: {}",
-                    participants.len()
-                )
-                .into(),
-            ));
-        };
-
-        let p_1 = participants.get(1).unwrap();
+    pub fn sign_step_2<C: CSCurve>(
+        config: &SessionConfig,
+        st_1: &mut SignState2<C>,
+        msgs_1: &RcvdSignMessages<C>,
+        presig_1: PresignOutput<C>,
+    ) -> Result<SignOutput<C>, ProtocolError> {
+        let p_1 = config.me;
 
-        let out = steps_2::step_2(st_1, &msgs_1, *p_1, presig_1).unwrap();
+        let out = steps_2::step_2(st_1, msgs_1, p_1, presig_1)?;
 
         Ok(out)
     }
@@ -1,9 +1,9 @@
-use k256::Secp256k1;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    protocol::{Participant, ProtocolError},
+    protocol::ProtocolError,
     tecdsa::triples_2::{generate_triples_2, TriplesGenManyResult, TriplesGenResult},
+    tecdsa_cli_srv::{cli_triples::wait_3_payload, session::SessionConfig},
     triples::{mul_steps, steps, RcvdTriplesMessages, TriplesState, Wait2Payload},
     CSCurve,
 };
@@ -11,15 +11,22 @@ use crate::{
 pub struct TriplesServer {}
 
 impl TriplesServer {
-    pub fn triples_step_1() -> TriplesGenResult<Secp256k1> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let threshold = 2;
-
-        generate_triples_2::<Secp256k1>(&participants, threshold)
+    pub fn triples_step_1<C: CSCurve>(
+        config: &SessionConfig,
+    ) -> Result<TriplesGenResult<C>, ProtocolError> {
+        config.validate()?;
+
+        Ok(generate_triples_2::<C>(
+            &config.participants,
+            config.threshold,
+        ))
     }
 }
 
+/// The server side of the step-based triple generation.
+///
+/// Like [`TriplesClient`](super::cli_triples::TriplesClient), this only runs
+/// between exactly two parties, and `triples_step_1` rejects any other config.
 pub struct TriplesServer2 {}
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -33,55 +40,53 @@ pub struct TriplesSrvStepOutput<C: CSCurve> {
 }
 
 impl TriplesServer2 {
-    pub fn triples_step_1() -> Result<TriplesSrvStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-        let threshold = 2;
-
-        let mut st_1 = TriplesState::<Secp256k1>::new(&participants.clone(), threshold, 2);
+    pub fn triples_step_1<C: CSCurve>(
+        config: &SessionConfig,
+    ) -> Result<TriplesSrvStepOutput<C>, ProtocolError> {
+        config.validate_two_party()?;
+
+        let mut st_1 = TriplesState::<C>::new(
+            &config.participants,
+            config.threshold,
+            2,
+            config.transcript_id(),
+        );
 
-        steps::step_1::<Secp256k1>(&mut st_1)?;
+        steps::step_1::<C>(&mut st_1)?;
 
-        let mut msgs_0 = RcvdTriplesMessages::<Secp256k1>::new();
-        let p_1 = participants
-            .get(1)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
+        let mut msgs_0 = RcvdTriplesMessages::<C>::new();
+        let p_1 = config.me;
 
-        msgs_0.wait_0.insert(*p_1, st_1.my_commitments.clone());
+        msgs_0.wait_0.insert(p_1, st_1.my_commitments.clone());
 
         Ok(TriplesSrvStepOutput { st_1, msgs_0 })
     }
 
-    pub fn triples_step_2(
-        mut st_1: TriplesState<Secp256k1>,
-        msgs_1: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesSrvStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    pub fn triples_step_2<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: TriplesState<C>,
+        msgs_1: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesSrvStepOutput<C>, ProtocolError> {
+        let p_1 = config.me;
+        steps::step_2::<C>(&mut st_1, &msgs_1, p_1)?;
 
-        let p_1 = participants
-            .get(1)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
-        steps::step_2::<Secp256k1>(&mut st_1, &msgs_1, *p_1)?;
-
-        let mut msgs_0 = RcvdTriplesMessages::<Secp256k1>::new();
-        msgs_0.wait_1.insert(*p_1, st_1.my_confirmations.clone());
+        let mut msgs_0 = RcvdTriplesMessages::<C>::new();
+        msgs_0.wait_1.insert(p_1, st_1.my_confirmations.clone());
 
         Ok(TriplesSrvStepOutput { st_1, msgs_0 })
     }
 
-    pub fn triples_step_3(
-        mut st_1: TriplesState<Secp256k1>,
-        msgs_1: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesSrvStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_1 = participants
-            .get(1)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
-        steps::step_3::<Secp256k1>(&mut st_1, &msgs_1, *p_1)?;
+    pub fn triples_step_3<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: TriplesState<C>,
+        msgs_1: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesSrvStepOutput<C>, ProtocolError> {
+        let p_1 = config.me;
+        steps::step_3::<C>(&mut st_1, &msgs_1, p_1)?;
 
-        let mut msgs_0 = RcvdTriplesMessages::<Secp256k1>::new();
+        let mut msgs_0 = RcvdTriplesMessages::<C>::new();
         msgs_0.wait_2.insert(
-            *p_1,
+            p_1,
             Wait2Payload {
                 big_e_i_v: st_1.big_e_i_v.clone(),
                 big_f_i_v: st_1.big_f_i_v.clone(),
@@ -95,151 +100,125 @@ impl TriplesServer2 {
         Ok(TriplesSrvStepOutput { st_1, msgs_0 })
     }
 
-    pub fn triples_step_4(
-        mut st_1: TriplesState<Secp256k1>,
-        msgs_1: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesSrvStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_1 = participants
-            .get(1)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
-        let msgs_to_send = steps::step_4::<Secp256k1>(&mut st_1, &msgs_1, *p_1)?;
-
-        let mut msgs_0 = RcvdTriplesMessages::<Secp256k1>::new();
+    pub fn triples_step_4<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: TriplesState<C>,
+        msgs_1: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesSrvStepOutput<C>, ProtocolError> {
+        let p_1 = config.me;
+        let msgs_to_send = steps::step_4::<C>(&mut st_1, &msgs_1, p_1)?;
 
-        // Dangerous! cardinality 1
-        for (_p, msg) in msgs_to_send {
-            msgs_0.wait_3.insert(*p_1, msg.clone());
-        }
+        let mut msgs_0 = RcvdTriplesMessages::<C>::new();
+        msgs_0
+            .wait_3
+            .insert(p_1, wait_3_payload(config, msgs_to_send)?);
 
         Ok(TriplesSrvStepOutput { st_1, msgs_0 })
     }
 
-    pub fn triples_step_5(
-        mut st_1: TriplesState<Secp256k1>,
-        msgs_1: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesSrvStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    pub fn triples_step_5<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: TriplesState<C>,
+        msgs_1: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesSrvStepOutput<C>, ProtocolError> {
+        let p_1 = config.me;
+        let wait_4 = steps::step_5::<C>(&mut st_1, &msgs_1, p_1)?;
 
-        let p_1 = participants
-            .get(1)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
-        let wait_4 = steps::step_5::<Secp256k1>(&mut st_1, &msgs_1, *p_1)?;
-
-        let mut msgs_0 = RcvdTriplesMessages::<Secp256k1>::new();
-        msgs_0.wait_4.insert(*p_1, wait_4);
+        let mut msgs_0 = RcvdTriplesMessages::<C>::new();
+        msgs_0.wait_4.insert(p_1, wait_4);
 
         Ok(TriplesSrvStepOutput { st_1, msgs_0 })
     }
 
-    pub fn triples_step_6(
-        mut st_1: TriplesState<Secp256k1>,
-        msgs_1: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesSrvStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_1 = participants
-            .get(1)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
+    pub fn triples_step_6<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: TriplesState<C>,
+        msgs_1: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesSrvStepOutput<C>, ProtocolError> {
+        let p_1 = config.me;
 
-        steps::step_6::<Secp256k1>(&mut st_1, &msgs_1, *p_1)?;
-        mul_steps::step_1(&mut st_1, &msgs_1, *p_1)?;
+        steps::step_6::<C>(&mut st_1, &msgs_1, p_1)?;
+        mul_steps::step_1(&mut st_1, &msgs_1, p_1)?;
 
         let big_y_affine_v_v =
-            mul_steps::multiplication_sender_many_step_1(&mut st_1, &msgs_1, *p_1)?;
+            mul_steps::multiplication_sender_many_step_1(&mut st_1, &msgs_1, p_1)?;
 
-        let mut msgs_0 = RcvdTriplesMessages::<Secp256k1>::new();
-        msgs_0.batch_random_ot_wait_0.insert(*p_1, big_y_affine_v_v);
+        let mut msgs_0 = RcvdTriplesMessages::<C>::new();
+        msgs_0.batch_random_ot_wait_0.insert(p_1, big_y_affine_v_v);
 
         Ok(TriplesSrvStepOutput { st_1, msgs_0 })
     }
 
-    pub fn triples_step_7(
-        mut st_1: TriplesState<Secp256k1>,
-        msgs_1: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesSrvStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    pub fn triples_step_7<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: TriplesState<C>,
+        msgs_1: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesSrvStepOutput<C>, ProtocolError> {
+        let p_1 = config.me;
 
-        let p_1 = participants
-            .get(1)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
+        let q_v = mul_steps::multiplication_sender_many_step_2(&mut st_1, &msgs_1, p_1)?;
 
-        let q_v = mul_steps::multiplication_sender_many_step_2(&mut st_1, &msgs_1, *p_1)?;
-
-        let mut msgs_0 = RcvdTriplesMessages::<Secp256k1>::new();
-        msgs_0.random_ot_extension_wait_0.insert(*p_1, q_v);
+        let mut msgs_0 = RcvdTriplesMessages::<C>::new();
+        msgs_0.random_ot_extension_wait_0.insert(p_1, q_v);
 
         Ok(TriplesSrvStepOutput { st_1, msgs_0 })
     }
 
-    pub fn triples_step_8(
-        mut st_1: TriplesState<Secp256k1>,
-        msgs_1: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesSrvStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_1 = participants
-            .get(1)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
+    pub fn triples_step_8<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: TriplesState<C>,
+        msgs_1: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesSrvStepOutput<C>, ProtocolError> {
+        let p_1 = config.me;
 
-        mul_steps::multiplication_sender_many_step_3(&mut st_1, &msgs_1, *p_1)?;
+        mul_steps::multiplication_sender_many_step_3(&mut st_1, &msgs_1, p_1)?;
 
         let mta_wait_0_payload =
-            mul_steps::multiplication_sender_many_step_4(&mut st_1, &msgs_1, *p_1)?;
+            mul_steps::multiplication_sender_many_step_4(&mut st_1, &msgs_1, p_1)?;
 
-        let mut msgs_0 = RcvdTriplesMessages::<Secp256k1>::new();
-        msgs_0.mta_wait_0.insert(*p_1, mta_wait_0_payload);
+        let mut msgs_0 = RcvdTriplesMessages::<C>::new();
+        msgs_0.mta_wait_0.insert(p_1, mta_wait_0_payload);
 
         Ok(TriplesSrvStepOutput { st_1, msgs_0 })
     }
 
-    pub fn triples_step_9(
-        mut st_1: TriplesState<Secp256k1>,
-        msgs_1: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesSrvStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    pub fn triples_step_9<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: TriplesState<C>,
+        msgs_1: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesSrvStepOutput<C>, ProtocolError> {
+        let p_1 = config.me;
+        let _ = mul_steps::multiplication_sender_many_step_5(&mut st_1, &msgs_1, p_1)?;
 
-        let p_1 = participants
-            .get(1)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
-        let _ = mul_steps::multiplication_sender_many_step_5(&mut st_1, &msgs_1, *p_1)?;
-
-        let msgs_0 = RcvdTriplesMessages::<Secp256k1>::new();
+        let msgs_0 = RcvdTriplesMessages::<C>::new();
 
         Ok(TriplesSrvStepOutput { st_1, msgs_0 })
     }
 
-    pub fn triples_step_10(
-        mut st_1: TriplesState<Secp256k1>,
-        msgs_1: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesSrvStepOutput<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_1 = participants
-            .get(1)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
+    pub fn triples_step_10<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: TriplesState<C>,
+        msgs_1: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesSrvStepOutput<C>, ProtocolError> {
+        let p_1 = config.me;
 
-        let (wait_5_payload, wait_6_payload) = steps::step_7(&mut st_1, &msgs_1, *p_1)?;
+        let (wait_5_payload, wait_6_payload) = steps::step_7(&mut st_1, &msgs_1, p_1)?;
 
-        let mut msgs_0 = RcvdTriplesMessages::<Secp256k1>::new();
-        msgs_0.wait_5.insert(*p_1, wait_5_payload);
-        msgs_0.wait_6.insert(*p_1, wait_6_payload);
+        let mut msgs_0 = RcvdTriplesMessages::<C>::new();
+        msgs_0.wait_5.insert(p_1, wait_5_payload);
+        msgs_0.wait_6.insert(p_1, wait_6_payload);
 
         Ok(TriplesSrvStepOutput { st_1, msgs_0 })
     }
 
-    pub fn triples_step_11(
-        mut st_1: TriplesState<Secp256k1>,
-        msgs_1: RcvdTriplesMessages<Secp256k1>,
-    ) -> Result<TriplesGenManyResult<Secp256k1>, ProtocolError> {
-        let participants = vec![Participant::from(0u32), Participant::from(1u32)];
-
-        let p_1 = participants
-            .get(1)
-            .ok_or(ProtocolError::Other("participant not exists".into()))?;
+    pub fn triples_step_11<C: CSCurve>(
+        config: &SessionConfig,
+        mut st_1: TriplesState<C>,
+        msgs_1: RcvdTriplesMessages<C>,
+    ) -> Result<TriplesGenManyResult<C>, ProtocolError> {
+        let p_1 = config.me;
 
-        let triples_gen_result_1 = steps::step_8(&mut st_1, &msgs_1, *p_1)?;
+        let triples_gen_result_1 = steps::step_8(&mut st_1, &msgs_1, p_1)?;
 
         Ok(triples_gen_result_1)
     }
@@ -0,0 +1,147 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use zeroize::Zeroizing;
+
+use super::keyring::SealedRecord;
+use super::{Keyring, MasterKey, RecordKind, ShareStore, StoreError};
+use crate::serde::{decode, encode};
+
+const KINDS: [RecordKind; 4] = [
+    RecordKind::KeyShare,
+    RecordKind::Triple,
+    RecordKind::Presign,
+    RecordKind::State,
+];
+
+/// A [`ShareStore`] keeping each record in a file.
+///
+/// A record lives at `<dir>/<kind>/<id in hex>`. Records are written to a
+/// temporary file first, then renamed over the old one, so a crash never
+/// leaves a record half written.
+#[derive(Debug)]
+pub struct FileShareStore {
+    dir: PathBuf,
+    keyring: Keyring,
+}
+
+impl FileShareStore {
+    /// Open the store in `dir`, creating the directory if needed.
+    pub fn open(dir: impl Into<PathBuf>, keyring: Keyring) -> Result<Self, StoreError> {
+        let dir = dir.into();
+        for kind in KINDS {
+            fs::create_dir_all(dir.join(kind.name())).map_err(io_error)?;
+        }
+        Ok(FileShareStore { dir, keyring })
+    }
+
+    fn path(&self, kind: RecordKind, id: &str) -> Result<PathBuf, StoreError> {
+        if id.is_empty() {
+            return Err(StoreError::Malformed(
+                "record id cannot be empty".to_string(),
+            ));
+        }
+        Ok(self.dir.join(kind.name()).join(hex_encode(id.as_bytes())))
+    }
+
+    fn read(&self, kind: RecordKind, id: &str) -> Result<Option<SealedRecord>, StoreError> {
+        let bytes = match fs::read(self.path(kind, id)?) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(io_error(e)),
+        };
+        decode(&bytes)
+            .map(Some)
+            .map_err(|e| StoreError::Malformed(e.to_string()))
+    }
+
+    fn write(&self, kind: RecordKind, id: &str, record: &SealedRecord) -> Result<(), StoreError> {
+        let path = self.path(kind, id)?;
+        let tmp = path.with_extension("tmp");
+        write_synced(&tmp, &encode(record)).map_err(io_error)?;
+        fs::rename(&tmp, &path).map_err(io_error)
+    }
+}
+
+impl ShareStore for FileShareStore {
+    fn put(&mut self, kind: RecordKind, id: &str, data: &[u8]) -> Result<(), StoreError> {
+        let record = self.keyring.seal(kind, id, data)?;
+        self.write(kind, id, &record)
+    }
+
+    fn get(&self, kind: RecordKind, id: &str) -> Result<Option<Zeroizing<Vec<u8>>>, StoreError> {
+        self.read(kind, id)?
+            .map(|record| self.keyring.open(kind, id, &record))
+            .transpose()
+    }
+
+    fn delete(&mut self, kind: RecordKind, id: &str) -> Result<bool, StoreError> {
+        match fs::remove_file(self.path(kind, id)?) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(io_error(e)),
+        }
+    }
+
+    fn ids(&self, kind: RecordKind) -> Result<Vec<String>, StoreError> {
+        let mut ids = vec![];
+        for entry in fs::read_dir(self.dir.join(kind.name())).map_err(io_error)? {
+            let name = entry.map_err(io_error)?.file_name();
+            // Skip the leftovers of interrupted writes, and anything else.
+            if let Some(id) = name.to_str().and_then(hex_decode) {
+                if let Ok(id) = String::from_utf8(id) {
+                    ids.push(id);
+                }
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    fn rotate(&mut self, key: MasterKey) -> Result<(), StoreError> {
+        self.keyring.rotate(key);
+
+        // Each record is replaced on its own. If this stops midway, the records
+        // left are still wrapped by a previous key, which the keyring keeps.
+        for kind in KINDS {
+            for id in self.ids(kind)? {
+                if let Some(record) = self.read(kind, &id)? {
+                    if !self.keyring.is_current(&record) {
+                        let record = self.keyring.rewrap(kind, &id, &record)?;
+                        self.write(kind, &id, &record)?;
+                    }
+                }
+            }
+        }
+
+        self.keyring.forget_previous();
+        Ok(())
+    }
+}
+
+fn write_synced(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+fn io_error(e: io::Error) -> StoreError {
+    StoreError::Io(e.to_string())
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            if pair.len() != 2 || !pair.iter().all(u8::is_ascii_hexdigit) {
+                return None;
+            }
+            u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()
+        })
+        .collect()
+}
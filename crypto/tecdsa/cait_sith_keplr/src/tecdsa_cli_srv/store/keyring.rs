@@ -0,0 +1,265 @@
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use super::{RecordKind, StoreError};
+use crate::serde::encode;
+
+/// The version of the record format, bumped whenever it changes.
+const RECORD_VERSION: u16 = 1;
+
+const DATA_LABEL: &[u8] = b"cait-sith-keplr store data";
+const WRAP_LABEL: &[u8] = b"cait-sith-keplr store key wrap";
+
+/// A key wrapping the data keys of records.
+///
+/// The id is stored along with each record, to find the key to open it with.
+/// The key itself is wiped when dropped.
+#[derive(Clone)]
+pub struct MasterKey {
+    id: String,
+    key: [u8; 32],
+}
+
+impl MasterKey {
+    pub fn new(id: impl Into<String>, key: [u8; 32]) -> Self {
+        MasterKey { id: id.into(), key }
+    }
+
+    /// A new random master key.
+    pub fn generate(id: impl Into<String>) -> Self {
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        Self::new(id, key)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl std::fmt::Debug for MasterKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MasterKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Zeroize for MasterKey {
+    fn zeroize(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+impl Drop for MasterKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for MasterKey {}
+
+/// The master keys of a store.
+///
+/// New records are wrapped with the current key, while the previous keys are
+/// only kept to open records which were not re-wrapped yet.
+#[derive(Debug, Clone)]
+pub struct Keyring {
+    current: MasterKey,
+    previous: Vec<MasterKey>,
+}
+
+/// A record, as written to the storage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct SealedRecord {
+    version: u16,
+    master_key_id: String,
+    wrap_nonce: Vec<u8>,
+    wrapped_key: Vec<u8>,
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+impl Keyring {
+    pub fn new(current: MasterKey) -> Self {
+        Keyring {
+            current,
+            previous: vec![],
+        }
+    }
+
+    /// Also open records wrapped by `key`, e.g. after a rotation which failed midway.
+    pub fn with_previous(mut self, key: MasterKey) -> Self {
+        self.previous.push(key);
+        self
+    }
+
+    pub fn current(&self) -> &MasterKey {
+        &self.current
+    }
+
+    /// Make `key` the current key, keeping the old one to open existing records.
+    pub(super) fn rotate(&mut self, key: MasterKey) {
+        let old = std::mem::replace(&mut self.current, key);
+        if old.id != self.current.id {
+            self.previous.retain(|k| k.id != self.current.id);
+            self.previous.push(old);
+        }
+    }
+
+    /// Forget every key but the current one.
+    pub(super) fn forget_previous(&mut self) {
+        self.previous.clear();
+    }
+
+    fn find(&self, id: &str) -> Result<&MasterKey, StoreError> {
+        std::iter::once(&self.current)
+            .chain(&self.previous)
+            .find(|k| k.id == id)
+            .ok_or_else(|| StoreError::UnknownMasterKey(id.to_string()))
+    }
+
+    /// Encrypt `data` with a fresh data key, wrapped by the current key.
+    pub(super) fn seal(
+        &self,
+        kind: RecordKind,
+        id: &str,
+        data: &[u8],
+    ) -> Result<SealedRecord, StoreError> {
+        let mut data_key = Zeroizing::new([0u8; 32]);
+        OsRng.fill_bytes(&mut *data_key);
+
+        let (nonce, ciphertext) = encrypt(&*data_key, &data_aad(kind, id), data)?;
+        let (wrap_nonce, wrapped_key) = encrypt(
+            &self.current.key,
+            &wrap_aad(kind, id, &self.current.id),
+            &*data_key,
+        )?;
+
+        Ok(SealedRecord {
+            version: RECORD_VERSION,
+            master_key_id: self.current.id.clone(),
+            wrap_nonce,
+            wrapped_key,
+            nonce,
+            ciphertext,
+        })
+    }
+
+    /// Decrypt `record`, returning the data wiped when dropped.
+    pub(super) fn open(
+        &self,
+        kind: RecordKind,
+        id: &str,
+        record: &SealedRecord,
+    ) -> Result<Zeroizing<Vec<u8>>, StoreError> {
+        let data_key = self.unwrap_key(kind, id, record)?;
+        decrypt(
+            &data_key,
+            &data_aad(kind, id),
+            &record.nonce,
+            &record.ciphertext,
+        )
+        .map(Zeroizing::new)
+        .ok_or_else(|| decrypt_error(kind, id))
+    }
+
+    /// Wrap the data key of `record` with the current key, leaving the data as is.
+    pub(super) fn rewrap(
+        &self,
+        kind: RecordKind,
+        id: &str,
+        record: &SealedRecord,
+    ) -> Result<SealedRecord, StoreError> {
+        let data_key = self.unwrap_key(kind, id, record)?;
+        let (wrap_nonce, wrapped_key) = encrypt(
+            &self.current.key,
+            &wrap_aad(kind, id, &self.current.id),
+            &data_key,
+        )?;
+
+        Ok(SealedRecord {
+            master_key_id: self.current.id.clone(),
+            wrap_nonce,
+            wrapped_key,
+            ..record.clone()
+        })
+    }
+
+    /// Whether `record` is wrapped by the current key.
+    pub(super) fn is_current(&self, record: &SealedRecord) -> bool {
+        record.master_key_id == self.current.id
+    }
+
+    fn unwrap_key(
+        &self,
+        kind: RecordKind,
+        id: &str,
+        record: &SealedRecord,
+    ) -> Result<Zeroizing<Vec<u8>>, StoreError> {
+        if record.version != RECORD_VERSION {
+            return Err(StoreError::Malformed(format!(
+                "unsupported record version {}, expected {}",
+                record.version, RECORD_VERSION
+            )));
+        }
+
+        let master_key = self.find(&record.master_key_id)?;
+        decrypt(
+            &master_key.key,
+            &wrap_aad(kind, id, &master_key.id),
+            &record.wrap_nonce,
+            &record.wrapped_key,
+        )
+        .map(Zeroizing::new)
+        .ok_or_else(|| decrypt_error(kind, id))
+    }
+}
+
+fn data_aad(kind: RecordKind, id: &str) -> Vec<u8> {
+    encode(&(DATA_LABEL, kind, id))
+}
+
+fn wrap_aad(kind: RecordKind, id: &str, master_key_id: &str) -> Vec<u8> {
+    encode(&(WRAP_LABEL, kind, id, master_key_id))
+}
+
+fn decrypt_error(kind: RecordKind, id: &str) -> StoreError {
+    StoreError::Decrypt {
+        kind,
+        id: id.to_string(),
+    }
+}
+
+/// Encrypt with a random nonce, returning it along with the ciphertext.
+fn encrypt(key: &[u8], aad: &[u8], msg: &[u8]) -> Result<(Vec<u8>, Vec<u8>), StoreError> {
+    let mut nonce = [0u8; 24];
+    OsRng.fill_bytes(&mut nonce);
+
+    let cipher = XChaCha20Poly1305::new_from_slice(key)
+        .map_err(|_| StoreError::Malformed("key must be 32 bytes".to_string()))?;
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), Payload { msg, aad })
+        .map_err(|_| StoreError::Malformed("record is too large to encrypt".to_string()))?;
+    Ok((nonce.to_vec(), ciphertext))
+}
+
+fn decrypt(key: &[u8], aad: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>> {
+    if key.len() != 32 || nonce.len() != 24 {
+        return None;
+    }
+
+    XChaCha20Poly1305::new_from_slice(key)
+        .ok()?
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .ok()
+}
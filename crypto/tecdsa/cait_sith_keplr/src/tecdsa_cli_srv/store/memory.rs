@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+use zeroize::Zeroizing;
+
+use super::keyring::SealedRecord;
+use super::{Keyring, MasterKey, RecordKind, ShareStore, StoreError};
+
+/// A [`ShareStore`] keeping its records in memory.
+///
+/// Records are encrypted just as they would be at rest, which is mostly of use
+/// for testing. Wrap the store in a `Mutex` to share it between threads.
+#[derive(Debug)]
+pub struct MemoryShareStore {
+    keyring: Keyring,
+    records: BTreeMap<(RecordKind, String), SealedRecord>,
+}
+
+impl MemoryShareStore {
+    pub fn new(keyring: Keyring) -> Self {
+        MemoryShareStore {
+            keyring,
+            records: BTreeMap::new(),
+        }
+    }
+}
+
+impl ShareStore for MemoryShareStore {
+    fn put(&mut self, kind: RecordKind, id: &str, data: &[u8]) -> Result<(), StoreError> {
+        let record = self.keyring.seal(kind, id, data)?;
+        self.records.insert((kind, id.to_string()), record);
+        Ok(())
+    }
+
+    fn get(&self, kind: RecordKind, id: &str) -> Result<Option<Zeroizing<Vec<u8>>>, StoreError> {
+        self.records
+            .get(&(kind, id.to_string()))
+            .map(|record| self.keyring.open(kind, id, record))
+            .transpose()
+    }
+
+    fn delete(&mut self, kind: RecordKind, id: &str) -> Result<bool, StoreError> {
+        Ok(self.records.remove(&(kind, id.to_string())).is_some())
+    }
+
+    fn ids(&self, kind: RecordKind) -> Result<Vec<String>, StoreError> {
+        Ok(self
+            .records
+            .keys()
+            .filter(|(k, _)| *k == kind)
+            .map(|(_, id)| id.clone())
+            .collect())
+    }
+
+    fn rotate(&mut self, key: MasterKey) -> Result<(), StoreError> {
+        self.keyring.rotate(key);
+
+        // Re-wrap every record before replacing any, so a failure changes nothing.
+        let mut rewrapped = BTreeMap::new();
+        for ((kind, id), record) in &self.records {
+            rewrapped.insert((*kind, id.clone()), self.keyring.rewrap(*kind, id, record)?);
+        }
+        self.records = rewrapped;
+
+        self.keyring.forget_previous();
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+//! Encrypted storage for key shares, triples, presignatures and step states.
+//!
+//! Records are kept by kind and id in a [`ShareStore`], and are encrypted at
+//! rest with envelope encryption: each record is sealed with a fresh data key,
+//! which is itself wrapped by a master key from a [`Keyring`]. Rotating the
+//! master key only re-wraps the data keys, without touching the records.
+//!
+//! The kind and id of a record are bound to its ciphertext, so a record can't be
+//! swapped for another one without failing to open.
+//!
+//! [`MemoryShareStore`] keeps the records in memory, and [`FileShareStore`]
+//! keeps each in a file of a directory.
+use std::{error, fmt};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::protocol::ProtocolError;
+use crate::serde::{decode, encode};
+
+mod file;
+mod keyring;
+mod memory;
+
+pub use file::FileShareStore;
+pub use keyring::{Keyring, MasterKey};
+pub use memory::MemoryShareStore;
+
+/// What a record holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum RecordKind {
+    /// A [`KeygenOutput`](crate::KeygenOutput).
+    KeyShare,
+    /// A triple, e.g. a [`PooledTriple`](super::pool::PooledTriple).
+    Triple,
+    /// A [`PresignOutput`](crate::PresignOutput).
+    Presign,
+    /// The state of a flow between two steps.
+    State,
+}
+
+impl RecordKind {
+    /// A name for the kind, also used as the directory of a [`FileShareStore`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            RecordKind::KeyShare => "key_share",
+            RecordKind::Triple => "triple",
+            RecordKind::Presign => "presign",
+            RecordKind::State => "state",
+        }
+    }
+}
+
+/// An error from a share store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreError {
+    /// The underlying storage failed.
+    Io(String),
+    /// A record failed to decrypt, because it was tampered with or moved.
+    Decrypt { kind: RecordKind, id: String },
+    /// A record is wrapped by a master key which is not in the keyring.
+    UnknownMasterKey(String),
+    /// A record, or the value in it, did not have the expected shape.
+    Malformed(String),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Io(e) => write!(f, "storage failed: {}", e),
+            StoreError::Decrypt { kind, id } => {
+                write!(f, "failed to decrypt {} record {:?}", kind.name(), id)
+            }
+            StoreError::UnknownMasterKey(key_id) => {
+                write!(f, "master key {:?} is not in the keyring", key_id)
+            }
+            StoreError::Malformed(e) => write!(f, "malformed record: {}", e),
+        }
+    }
+}
+
+impl error::Error for StoreError {}
+
+impl From<StoreError> for ProtocolError {
+    fn from(e: StoreError) -> Self {
+        ProtocolError::Other(Box::new(e))
+    }
+}
+
+/// Where encrypted records are kept.
+///
+/// Writing a record replaces any record of the same kind and id.
+pub trait ShareStore {
+    fn put(&mut self, kind: RecordKind, id: &str, data: &[u8]) -> Result<(), StoreError>;
+
+    /// The data of a record, wiped when dropped.
+    fn get(&self, kind: RecordKind, id: &str) -> Result<Option<Zeroizing<Vec<u8>>>, StoreError>;
+
+    /// Remove a record, returning whether it existed.
+    fn delete(&mut self, kind: RecordKind, id: &str) -> Result<bool, StoreError>;
+
+    /// The ids of every record of `kind`, in order.
+    fn ids(&self, kind: RecordKind) -> Result<Vec<String>, StoreError>;
+
+    /// Make `key` the master key, and re-wrap every record with it.
+    ///
+    /// The previous master keys are forgotten once every record is re-wrapped.
+    /// If this fails midway, the store still opens every record, and this can
+    /// be called again with the same key.
+    fn rotate(&mut self, key: MasterKey) -> Result<(), StoreError>;
+
+    /// Store `value` in its msgpack encoding, as used throughout the crate.
+    fn put_value<T: Serialize>(
+        &mut self,
+        kind: RecordKind,
+        id: &str,
+        value: &T,
+    ) -> Result<(), StoreError>
+    where
+        Self: Sized,
+    {
+        self.put(kind, id, &Zeroizing::new(encode(value)))
+    }
+
+    fn get_value<T: DeserializeOwned>(
+        &self,
+        kind: RecordKind,
+        id: &str,
+    ) -> Result<Option<T>, StoreError>
+    where
+        Self: Sized,
+    {
+        self.get(kind, id)?
+            .map(|data| decode(&data).map_err(|e| StoreError::Malformed(e.to_string())))
+            .transpose()
+    }
+}
@@ -1,17 +1,27 @@
-use ecdsa::Signature;
-use elliptic_curve::AffinePoint;
-use k256::ecdsa::signature::Verifier;
-use k256::ecdsa::VerifyingKey;
-use k256::{PublicKey, Secp256k1};
+use ecdsa::hazmat::{DigestPrimitive, VerifyPrimitive};
+use ecdsa::signature::Verifier;
+use ecdsa::{Signature, SignatureSize, VerifyingKey};
+use elliptic_curve::generic_array::ArrayLength;
+use elliptic_curve::{AffinePoint, PublicKey};
 
 use crate::protocol::ProtocolError;
+use crate::CSCurve;
 
-pub fn verify_sig(
-    sig: Signature<Secp256k1>,
-    public_key: AffinePoint<Secp256k1>,
+pub fn verify_sig<C>(
+    sig: Signature<C>,
+    public_key: AffinePoint<C>,
     msg: &[u8],
-) -> Result<(), ProtocolError> {
-    VerifyingKey::from(&PublicKey::from_affine(public_key).unwrap())
+) -> Result<(), ProtocolError>
+where
+    C: CSCurve + DigestPrimitive,
+    AffinePoint<C>: VerifyPrimitive<C>,
+    SignatureSize<C>: ArrayLength<u8>,
+{
+    let public_key = PublicKey::<C>::from_affine(public_key).map_err(|err| {
+        ProtocolError::Other(format!("Error parsing the public key, err: {:?}", err).into())
+    })?;
+
+    VerifyingKey::<C>::from(&public_key)
         .verify(&msg[..], &sig)
         .map_err(|err| {
             ProtocolError::Other(format!("Error verifying the sig, err: {:?}", err).into())
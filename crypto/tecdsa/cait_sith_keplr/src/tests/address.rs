@@ -0,0 +1,79 @@
+use k256::{AffinePoint, ProjectivePoint, Scalar, Secp256k1};
+
+use crate::address::{cosmos_address, eip55_checksum, eth_address};
+use crate::keyshare::KeygenOutput;
+use crate::math::Polynomial;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn unhex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// The key output whose public key is the generator, i.e. for the private key 1.
+fn generator_output() -> KeygenOutput<Secp256k1> {
+    let f = Polynomial::<Secp256k1>::extend_random(&mut rand_core::OsRng, 2, &Scalar::ONE);
+    KeygenOutput::new(Scalar::ONE, AffinePoint::GENERATOR, f.commit())
+}
+
+#[test]
+fn test_sec1_encodings() {
+    let output = generator_output();
+    assert_eq!(
+        hex(&output.public_key_compressed()),
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+    );
+    assert_eq!(
+        hex(&output.public_key_uncompressed()),
+        concat!(
+            "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"
+        )
+    );
+}
+
+#[test]
+fn test_eth_address() {
+    assert_eq!(
+        generator_output().eth_address(),
+        "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+    );
+    let two = (ProjectivePoint::GENERATOR * Scalar::from(2u64)).to_affine();
+    assert_eq!(
+        eth_address(&two),
+        "0x2B5AD5c4795c026514f8317c7a215E218DcCD6cF"
+    );
+}
+
+#[test]
+fn test_eip55_checksum() {
+    // The examples from the EIP-55 specification.
+    for address in [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ] {
+        let bytes: [u8; 20] = unhex(&address[2..].to_lowercase()).try_into().unwrap();
+        assert_eq!(eip55_checksum(&bytes), address);
+    }
+}
+
+#[test]
+fn test_cosmos_address() {
+    let output = generator_output();
+    assert_eq!(
+        output.cosmos_address("cosmos").unwrap(),
+        "cosmos1w508d6qejxtdg4y5r3zarvary0c5xw7k6ah60c"
+    );
+    assert_eq!(
+        cosmos_address(&output.public_key, "osmo").unwrap(),
+        "osmo1w508d6qejxtdg4y5r3zarvary0c5xw7kjxy2e2"
+    );
+    assert!(output.cosmos_address("").is_err());
+}
@@ -0,0 +1,257 @@
+use ecdsa::Signature;
+use k256::{ProjectivePoint, Secp256k1};
+
+use crate::compat::{self, scalar_hash};
+use crate::keyshare::{KeyshareState2, RcvdKeyshareMessages};
+use crate::presign::RcvdPresignMessages2;
+use crate::protocol::Participant;
+use crate::sign::{RcvdSignMessages, SignOutput};
+use crate::tecdsa::triples_2::TriplesGenResult;
+use crate::tecdsa_cli_srv::cli_keygen::{
+    ClientKeygenPrivateStepOutput, ClientKeygenStepOutput, KeygenClient,
+};
+use crate::tecdsa_cli_srv::cli_presign::{PresignClient, PresignStepOutput};
+use crate::tecdsa_cli_srv::cli_sign::{ClientSignStepOutput, SignClient};
+use crate::tecdsa_cli_srv::phases::TriplesRound1;
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::tecdsa_cli_srv::srv_keygen::{
+    KeygenServer, ServerKeygenPrivateStepOutput, ServerKeygenStepOutput,
+};
+use crate::tecdsa_cli_srv::srv_presign::PresignServer;
+use crate::tecdsa_cli_srv::srv_sign::{ServerSignStepOutput, SignServer};
+use crate::tecdsa_cli_srv::srv_triples::TriplesServer;
+use crate::tecdsa_cli_srv::verifier::verify_sig;
+use crate::KeygenOutput;
+
+/// Deliver a broadcast from `from` to every other party's inbox.
+fn broadcast(
+    inboxes: &mut [RcvdKeyshareMessages<Secp256k1>],
+    participants: &[Participant],
+    from: Participant,
+    msgs: RcvdKeyshareMessages<Secp256k1>,
+) {
+    for (p, inbox) in participants.iter().zip(inboxes.iter_mut()) {
+        if *p != from {
+            inbox.extend(msgs.clone());
+        }
+    }
+}
+
+/// Run a 2-of-3 keygen where the client and backup use `KeygenClient` and the
+/// server uses `KeygenServer`.
+fn keygen_2_of_3(configs: &[SessionConfig]) -> Vec<KeygenOutput<Secp256k1>> {
+    let participants = configs[0].participants.clone();
+    let (cli, srv, bak) = (&configs[0], &configs[1], &configs[2]);
+
+    let mut inboxes = vec![RcvdKeyshareMessages::<Secp256k1>::new(); 3];
+
+    // step 1
+    let ClientKeygenStepOutput { st_0, msgs_1 } =
+        KeygenClient::cli_keygen_step_1::<Secp256k1>(cli).unwrap();
+    broadcast(&mut inboxes, &participants, cli.me, msgs_1);
+    let ServerKeygenStepOutput { st_1, msgs_0 } =
+        KeygenServer::srv_keygen_step_1::<Secp256k1>(srv).unwrap();
+    broadcast(&mut inboxes, &participants, srv.me, msgs_0);
+    let ClientKeygenStepOutput { st_0: st_2, msgs_1 } =
+        KeygenClient::cli_keygen_step_1::<Secp256k1>(bak).unwrap();
+    broadcast(&mut inboxes, &participants, bak.me, msgs_1);
+
+    let mut states: (
+        KeyshareState2<Secp256k1>,
+        KeyshareState2<Secp256k1>,
+        KeyshareState2<Secp256k1>,
+    ) = (st_0, st_1, st_2);
+
+    // steps 2 and 3
+    for step in 2..=3 {
+        let (st_0, msgs_1) = {
+            let out = match step {
+                2 => KeygenClient::cli_keygen_step_2(cli, states.0, &inboxes[0]),
+                _ => KeygenClient::cli_keygen_step_3(cli, states.0, &inboxes[0]),
+            }
+            .unwrap();
+            (out.st_0, out.msgs_1)
+        };
+        let (st_1, msgs_0) = {
+            let out = match step {
+                2 => KeygenServer::srv_keygen_step_2(srv, states.1, &inboxes[1]),
+                _ => KeygenServer::srv_keygen_step_3(srv, states.1, &inboxes[1]),
+            }
+            .unwrap();
+            (out.st_1, out.msgs_0)
+        };
+        let (st_2, msgs_2) = {
+            let out = match step {
+                2 => KeygenClient::cli_keygen_step_2(bak, states.2, &inboxes[2]),
+                _ => KeygenClient::cli_keygen_step_3(bak, states.2, &inboxes[2]),
+            }
+            .unwrap();
+            (out.st_0, out.msgs_1)
+        };
+        broadcast(&mut inboxes, &participants, cli.me, msgs_1);
+        broadcast(&mut inboxes, &participants, srv.me, msgs_0);
+        broadcast(&mut inboxes, &participants, bak.me, msgs_2);
+        states = (st_0, st_1, st_2);
+    }
+
+    // step 4 (private)
+    let ClientKeygenPrivateStepOutput { st_0, msgs_1 } =
+        KeygenClient::cli_keygen_step_4(cli, states.0, &inboxes[0]).unwrap();
+    let ServerKeygenPrivateStepOutput { st_1, msgs_0 } =
+        KeygenServer::srv_keygen_step_4(srv, states.1, &inboxes[1]).unwrap();
+    let ClientKeygenPrivateStepOutput {
+        st_0: st_2,
+        msgs_1: msgs_2,
+    } = KeygenClient::cli_keygen_step_4(bak, states.2, &inboxes[2]).unwrap();
+
+    for private_msgs in [msgs_1, msgs_0, msgs_2] {
+        for (to, msgs) in private_msgs {
+            let idx = participants.iter().position(|p| *p == to).unwrap();
+            inboxes[idx].extend(msgs);
+        }
+    }
+
+    // step 5
+    vec![
+        KeygenClient::cli_keygen_step_5(cli, st_0, &inboxes[0]).unwrap(),
+        KeygenServer::srv_keygen_step_5(srv, st_1, &inboxes[1]).unwrap(),
+        KeygenClient::cli_keygen_step_5(bak, st_2, &inboxes[2]).unwrap(),
+    ]
+}
+
+#[test]
+pub fn test_e2e_cli_srv_2_of_3() {
+    let participants = vec![
+        Participant::from(0u32),
+        Participant::from(1u32),
+        Participant::from(2u32),
+    ];
+    let keygen_configs: Vec<_> = participants
+        .iter()
+        .map(|p| SessionConfig::new(participants.clone(), *p, 2, "session").with_key_id("key"))
+        .collect();
+
+    let keygen_outs = keygen_2_of_3(&keygen_configs);
+    assert_eq!(keygen_outs[0].public_key, keygen_outs[1].public_key);
+    assert_eq!(keygen_outs[0].public_key, keygen_outs[2].public_key);
+    for (p, out) in participants.iter().zip(&keygen_outs) {
+        let public_share = (ProjectivePoint::GENERATOR * out.private_share).to_affine();
+        assert!(keygen_outs
+            .iter()
+            .all(|other| other.public_share(*p) == Some(public_share)));
+    }
+
+    // The client signs together with the backup, the server is offline.
+    let signers = vec![participants[0], participants[2]];
+    let cli_config =
+        SessionConfig::new(signers.clone(), signers[0], 2, "session").with_key_id("key");
+    let bak_config =
+        SessionConfig::new(signers.clone(), signers[1], 2, "session").with_key_id("key");
+
+    let TriplesGenResult {
+        pub0,
+        pub1,
+        shares0,
+        shares1,
+    } = TriplesServer::triples_step_1::<Secp256k1>(&bak_config).unwrap();
+
+    let (cli_presign_res, bak_presign_res) = {
+        let mut cli_msgs_0 = RcvdPresignMessages2::<Secp256k1>::new();
+        let mut bak_msgs_2 = RcvdPresignMessages2::<Secp256k1>::new();
+
+        let PresignStepOutput { st_0, msgs_1 } = PresignClient::presign_step_1(
+            &cli_config,
+            pub0.clone(),
+            pub1.clone(),
+            shares0[0].clone(),
+            shares1[0].clone(),
+            keygen_outs[0].clone(),
+        )
+        .unwrap();
+        bak_msgs_2.extend(msgs_1);
+
+        let (st_2, msgs_0) = PresignServer::presign_step_1(
+            &bak_config,
+            pub0,
+            pub1,
+            shares0[1].clone(),
+            shares1[1].clone(),
+            keygen_outs[2].clone(),
+        )
+        .unwrap();
+        cli_msgs_0.extend(msgs_0);
+
+        let PresignStepOutput { st_0, msgs_1 } =
+            PresignClient::presign_step_2(&cli_config, st_0).unwrap();
+        bak_msgs_2.extend(msgs_1);
+
+        let (st_2, msgs_0) = PresignServer::presign_step_2(&bak_config, st_2).unwrap();
+        cli_msgs_0.extend(msgs_0);
+
+        (
+            PresignClient::presign_step_3(&cli_config, st_0, &cli_msgs_0).unwrap(),
+            PresignServer::presign_step_3(&bak_config, st_2, &bak_msgs_2).unwrap(),
+        )
+    };
+
+    let msg = b"some";
+    let msg_hash = scalar_hash(msg);
+
+    let mut cli_msgs_0 = RcvdSignMessages::<Secp256k1>::new();
+    let mut bak_msgs_2 = RcvdSignMessages::<Secp256k1>::new();
+
+    let ClientSignStepOutput { mut st_0, msgs_1 } =
+        SignClient::sign_step_1(&cli_config, msg_hash, cli_presign_res.clone()).unwrap();
+    bak_msgs_2.extend(msgs_1);
+
+    let ServerSignStepOutput { mut st_1, msgs_0 } =
+        SignServer::sign_step_1(&bak_config, msg_hash, bak_presign_res.clone()).unwrap();
+    cli_msgs_0.extend(msgs_0);
+
+    let SignOutput { sig: sig_0, .. } =
+        SignClient::sign_step_2(&cli_config, &mut st_0, &cli_msgs_0, cli_presign_res).unwrap();
+    let SignOutput { sig: sig_2, .. } =
+        SignServer::sign_step_2(&bak_config, &mut st_1, &bak_msgs_2, bak_presign_res).unwrap();
+
+    assert_eq!(sig_0.s, sig_2.s);
+
+    let sig: Signature<Secp256k1> =
+        Signature::from_scalars(compat::x_coordinate::<Secp256k1>(&sig_0.big_r), sig_0.s).unwrap();
+
+    verify_sig(sig, keygen_outs[1].public_key, msg).unwrap();
+}
+
+#[test]
+pub fn test_session_config_validation() {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+
+    assert!(
+        SessionConfig::new(participants.clone(), participants[0], 2, "session")
+            .validate()
+            .is_ok()
+    );
+    assert!(
+        SessionConfig::new(participants.clone(), participants[0], 3, "session")
+            .validate()
+            .is_err()
+    );
+    assert!(
+        SessionConfig::new(participants.clone(), Participant::from(5u32), 2, "session")
+            .validate()
+            .is_err()
+    );
+    assert!(SessionConfig::new(
+        vec![participants[0], participants[0]],
+        participants[0],
+        2,
+        "session"
+    )
+    .validate()
+    .is_err());
+
+    let three = vec![participants[0], participants[1], Participant::from(2u32)];
+    let config = SessionConfig::new(three, participants[0], 2, "session");
+    assert!(config.validate_two_party().is_err());
+    // Triple generation is rejected as it starts.
+    assert!(TriplesRound1::<Secp256k1>::start(config).is_err());
+}
@@ -0,0 +1,111 @@
+use elliptic_curve::Field;
+use k256::{AffinePoint, ProjectivePoint, Scalar, Secp256k1};
+use rand_core::OsRng;
+
+use super::cli_srv_refresh::run_steps_2_to_4;
+use crate::keyshare::RcvdKeyshareMessages;
+use crate::participants::ParticipantList;
+use crate::protocol::{Participant, ProtocolError};
+use crate::tecdsa_cli_srv::cli_keygen::{ClientKeygenStepOutput, KeygenClient};
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::tecdsa_cli_srv::srv_keygen::{KeygenServer, ServerKeygenStepOutput};
+use crate::KeygenOutput;
+
+/// Import `secret`, held by the client, into a 2-of-2 sharing with the server.
+fn import(
+    cli_config: &SessionConfig,
+    srv_config: &SessionConfig,
+    secret: Scalar,
+    public_key: AffinePoint,
+) -> (
+    Result<KeygenOutput<Secp256k1>, ProtocolError>,
+    Result<KeygenOutput<Secp256k1>, ProtocolError>,
+) {
+    let importer = cli_config.me;
+
+    let mut cli_msgs_0 = RcvdKeyshareMessages::<Secp256k1>::new();
+    let mut srv_msgs_1 = RcvdKeyshareMessages::<Secp256k1>::new();
+
+    let ClientKeygenStepOutput { st_0, msgs_1 } =
+        KeygenClient::cli_import_step_1::<Secp256k1>(cli_config, importer, Some(secret)).unwrap();
+    srv_msgs_1.extend(msgs_1);
+    let ServerKeygenStepOutput { st_1, msgs_0 } =
+        KeygenServer::srv_import_step_1::<Secp256k1>(srv_config, importer, None).unwrap();
+    cli_msgs_0.extend(msgs_0);
+
+    let (st_0, st_1) = run_steps_2_to_4(
+        cli_config,
+        srv_config,
+        st_0,
+        st_1,
+        &mut cli_msgs_0,
+        &mut srv_msgs_1,
+    );
+
+    (
+        KeygenClient::cli_import_step_5(cli_config, st_0, &cli_msgs_0, public_key),
+        KeygenServer::srv_import_step_5(srv_config, st_1, &srv_msgs_1, public_key),
+    )
+}
+
+#[test]
+pub fn test_e2e_cli_srv_import() {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    let cli_config =
+        SessionConfig::new(participants.clone(), participants[0], 2, "session").with_key_id("key");
+    let srv_config =
+        SessionConfig::new(participants.clone(), participants[1], 2, "session").with_key_id("key");
+
+    let secret = Scalar::random(&mut OsRng);
+    let public_key = (ProjectivePoint::GENERATOR * secret).to_affine();
+
+    let (keygen_0, keygen_1) = import(&cli_config, &srv_config, secret, public_key);
+    let (keygen_0, keygen_1) = (keygen_0.unwrap(), keygen_1.unwrap());
+
+    assert_eq!(keygen_0.public_key, public_key);
+    assert_eq!(keygen_1.public_key, public_key);
+    assert_ne!(keygen_0.private_share, secret);
+
+    let participant_list = ParticipantList::new(&participants).unwrap();
+    let private_key = participant_list.lagrange::<Secp256k1>(participants[0])
+        * keygen_0.private_share
+        + participant_list.lagrange::<Secp256k1>(participants[1]) * keygen_1.private_share;
+    assert_eq!(private_key, secret);
+
+    // The parties reject a key which does not match the supplied public key.
+    let (res_0, res_1) = import(&cli_config, &srv_config, secret, AffinePoint::GENERATOR);
+    assert!(res_0.is_err());
+    assert!(res_1.is_err());
+}
+
+#[test]
+pub fn test_cli_srv_import_bad_parameters() {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    let cli_config =
+        SessionConfig::new(participants.clone(), participants[0], 2, "session").with_key_id("key");
+    let srv_config =
+        SessionConfig::new(participants.clone(), participants[1], 2, "session").with_key_id("key");
+
+    // The importer must be a participant.
+    assert!(KeygenClient::cli_import_step_1::<Secp256k1>(
+        &cli_config,
+        Participant::from(2u32),
+        None
+    )
+    .is_err());
+
+    // The importer must provide its secret.
+    assert!(
+        KeygenClient::cli_import_step_1::<Secp256k1>(&cli_config, participants[0], None).is_err()
+    );
+
+    // Nobody else can provide one.
+    assert!(matches!(
+        KeygenServer::srv_import_step_1::<Secp256k1>(
+            &srv_config,
+            participants[0],
+            Some(Scalar::ONE)
+        ),
+        Err(ProtocolError::KeyMismatch(_))
+    ));
+}
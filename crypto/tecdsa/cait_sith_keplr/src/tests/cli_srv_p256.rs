@@ -0,0 +1,224 @@
+use ecdsa::Signature;
+use p256::NistP256;
+
+use crate::compat::{self, scalar_hash_p256, CSCurve};
+use crate::keyshare::RcvdKeyshareMessages;
+use crate::presign::RcvdPresignMessages2;
+use crate::protocol::Participant;
+use crate::sign::{RcvdSignMessages, SignOutput};
+use crate::tecdsa_cli_srv::cli_keygen::{
+    ClientKeygenPrivateStepOutput, ClientKeygenStepOutput, KeygenClient,
+};
+use crate::tecdsa_cli_srv::cli_presign::{PresignClient, PresignStepOutput};
+use crate::tecdsa_cli_srv::cli_sign::{ClientSignStepOutput, SignClient};
+use crate::tecdsa_cli_srv::cli_triples::{TriplesCliStepOutput, TriplesClient};
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::tecdsa_cli_srv::srv_keygen::{
+    KeygenServer, ServerKeygenPrivateStepOutput, ServerKeygenStepOutput,
+};
+use crate::tecdsa_cli_srv::srv_presign::PresignServer;
+use crate::tecdsa_cli_srv::srv_sign::{ServerSignStepOutput, SignServer};
+use crate::tecdsa_cli_srv::srv_triples::{TriplesServer2, TriplesSrvStepOutput};
+use crate::tecdsa_cli_srv::verifier::verify_sig;
+use crate::triples::{RcvdTriplesMessages, TriplesState};
+
+fn cli_triples_step(
+    config: &SessionConfig,
+    step: usize,
+    st_0: TriplesState<NistP256>,
+    msgs_0: RcvdTriplesMessages<NistP256>,
+) -> TriplesCliStepOutput<NistP256> {
+    match step {
+        2 => TriplesClient::triples_step_2(config, st_0, msgs_0),
+        3 => TriplesClient::triples_step_3(config, st_0, msgs_0),
+        4 => TriplesClient::triples_step_4(config, st_0, msgs_0),
+        5 => TriplesClient::triples_step_5(config, st_0, msgs_0),
+        6 => TriplesClient::triples_step_6(config, st_0, msgs_0),
+        7 => TriplesClient::triples_step_7(config, st_0, msgs_0),
+        8 => TriplesClient::triples_step_8(config, st_0, msgs_0),
+        9 => TriplesClient::triples_step_9(config, st_0, msgs_0),
+        10 => TriplesClient::triples_step_10(config, st_0, msgs_0),
+        _ => unreachable!(),
+    }
+    .unwrap()
+}
+
+fn srv_triples_step(
+    config: &SessionConfig,
+    step: usize,
+    st_1: TriplesState<NistP256>,
+    msgs_1: RcvdTriplesMessages<NistP256>,
+) -> TriplesSrvStepOutput<NistP256> {
+    match step {
+        2 => TriplesServer2::triples_step_2(config, st_1, msgs_1),
+        3 => TriplesServer2::triples_step_3(config, st_1, msgs_1),
+        4 => TriplesServer2::triples_step_4(config, st_1, msgs_1),
+        5 => TriplesServer2::triples_step_5(config, st_1, msgs_1),
+        6 => TriplesServer2::triples_step_6(config, st_1, msgs_1),
+        7 => TriplesServer2::triples_step_7(config, st_1, msgs_1),
+        8 => TriplesServer2::triples_step_8(config, st_1, msgs_1),
+        9 => TriplesServer2::triples_step_9(config, st_1, msgs_1),
+        10 => TriplesServer2::triples_step_10(config, st_1, msgs_1),
+        _ => unreachable!(),
+    }
+    .unwrap()
+}
+
+#[test]
+pub fn test_e2e_cli_srv_p256() {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    let cli_config =
+        SessionConfig::new(participants.clone(), participants[0], 2, "session").with_key_id("key");
+    let srv_config =
+        SessionConfig::new(participants.clone(), participants[1], 2, "session").with_key_id("key");
+
+    let (keygen_0, keygen_1) = {
+        let mut cli_msgs_0 = RcvdKeyshareMessages::<NistP256>::new();
+        let mut srv_msgs_1 = RcvdKeyshareMessages::<NistP256>::new();
+
+        let ClientKeygenStepOutput { st_0, msgs_1 } =
+            KeygenClient::cli_keygen_step_1::<NistP256>(&cli_config).unwrap();
+        srv_msgs_1.extend(msgs_1);
+        let ServerKeygenStepOutput { st_1, msgs_0 } =
+            KeygenServer::srv_keygen_step_1::<NistP256>(&srv_config).unwrap();
+        cli_msgs_0.extend(msgs_0);
+
+        let ClientKeygenStepOutput { st_0, msgs_1 } =
+            KeygenClient::cli_keygen_step_2(&cli_config, st_0, &cli_msgs_0).unwrap();
+        srv_msgs_1.extend(msgs_1);
+        let ServerKeygenStepOutput { st_1, msgs_0 } =
+            KeygenServer::srv_keygen_step_2(&srv_config, st_1, &srv_msgs_1).unwrap();
+        cli_msgs_0.extend(msgs_0);
+
+        let ClientKeygenStepOutput { st_0, msgs_1 } =
+            KeygenClient::cli_keygen_step_3(&cli_config, st_0, &cli_msgs_0).unwrap();
+        srv_msgs_1.extend(msgs_1);
+        let ServerKeygenStepOutput { st_1, msgs_0 } =
+            KeygenServer::srv_keygen_step_3(&srv_config, st_1, &srv_msgs_1).unwrap();
+        cli_msgs_0.extend(msgs_0);
+
+        let ClientKeygenPrivateStepOutput { st_0, mut msgs_1 } =
+            KeygenClient::cli_keygen_step_4(&cli_config, st_0, &cli_msgs_0).unwrap();
+        srv_msgs_1.extend(msgs_1.remove(&participants[1]).unwrap());
+        let ServerKeygenPrivateStepOutput { st_1, mut msgs_0 } =
+            KeygenServer::srv_keygen_step_4(&srv_config, st_1, &srv_msgs_1).unwrap();
+        cli_msgs_0.extend(msgs_0.remove(&participants[0]).unwrap());
+
+        (
+            KeygenClient::cli_keygen_step_5(&cli_config, st_0, &cli_msgs_0).unwrap(),
+            KeygenServer::srv_keygen_step_5(&srv_config, st_1, &srv_msgs_1).unwrap(),
+        )
+    };
+    assert_eq!(keygen_0.public_key, keygen_1.public_key);
+
+    let (cli_triples_res, srv_triples_res) = {
+        let mut cli_msgs_0 = RcvdTriplesMessages::<NistP256>::new();
+        let mut srv_msgs_1 = RcvdTriplesMessages::<NistP256>::new();
+
+        let TriplesCliStepOutput { mut st_0, msgs_1 } =
+            TriplesClient::triples_step_1::<NistP256>(&cli_config).unwrap();
+        srv_msgs_1.extend(msgs_1);
+        let TriplesSrvStepOutput { mut st_1, msgs_0 } =
+            TriplesServer2::triples_step_1::<NistP256>(&srv_config).unwrap();
+        cli_msgs_0.extend(msgs_0);
+
+        for step in 2..=10 {
+            let TriplesCliStepOutput {
+                st_0: next_0,
+                msgs_1,
+            } = cli_triples_step(&cli_config, step, st_0, cli_msgs_0.clone());
+            srv_msgs_1.extend(msgs_1);
+            let TriplesSrvStepOutput {
+                st_1: next_1,
+                msgs_0,
+            } = srv_triples_step(&srv_config, step, st_1, srv_msgs_1.clone());
+            cli_msgs_0.extend(msgs_0);
+
+            st_0 = next_0;
+            st_1 = next_1;
+        }
+
+        (
+            TriplesClient::triples_step_11(&cli_config, st_0, cli_msgs_0).unwrap(),
+            TriplesServer2::triples_step_11(&srv_config, st_1, srv_msgs_1).unwrap(),
+        )
+    };
+
+    let (cli_presign_res, srv_presign_res) = {
+        let mut cli_msgs_0 = RcvdPresignMessages2::<NistP256>::new();
+        let mut srv_msgs_1 = RcvdPresignMessages2::<NistP256>::new();
+
+        let PresignStepOutput { st_0, msgs_1 } = PresignClient::presign_step_1(
+            &cli_config,
+            cli_triples_res.pub_v[0].clone(),
+            cli_triples_res.pub_v[1].clone(),
+            cli_triples_res.share_v[0].clone(),
+            cli_triples_res.share_v[1].clone(),
+            keygen_0.clone(),
+        )
+        .unwrap();
+        srv_msgs_1.extend(msgs_1);
+
+        let (st_1, msgs_0) = PresignServer::presign_step_1(
+            &srv_config,
+            srv_triples_res.pub_v[0].clone(),
+            srv_triples_res.pub_v[1].clone(),
+            srv_triples_res.share_v[0].clone(),
+            srv_triples_res.share_v[1].clone(),
+            keygen_1.clone(),
+        )
+        .unwrap();
+        cli_msgs_0.extend(msgs_0);
+
+        let PresignStepOutput { st_0, msgs_1 } =
+            PresignClient::presign_step_2(&cli_config, st_0).unwrap();
+        srv_msgs_1.extend(msgs_1);
+
+        let (st_1, msgs_0) = PresignServer::presign_step_2(&srv_config, st_1).unwrap();
+        cli_msgs_0.extend(msgs_0);
+
+        (
+            PresignClient::presign_step_3(&cli_config, st_0, &cli_msgs_0).unwrap(),
+            PresignServer::presign_step_3(&srv_config, st_1, &srv_msgs_1).unwrap(),
+        )
+    };
+    assert_eq!(cli_presign_res.big_r, srv_presign_res.big_r);
+
+    let msg = b"some";
+    let msg_hash = scalar_hash_p256(msg);
+
+    let mut cli_msgs_0 = RcvdSignMessages::<NistP256>::new();
+    let mut srv_msgs_1 = RcvdSignMessages::<NistP256>::new();
+
+    let ClientSignStepOutput { mut st_0, msgs_1 } =
+        SignClient::sign_step_1(&cli_config, msg_hash, cli_presign_res.clone()).unwrap();
+    srv_msgs_1.extend(msgs_1);
+
+    let ServerSignStepOutput { mut st_1, msgs_0 } =
+        SignServer::sign_step_1(&srv_config, msg_hash, srv_presign_res.clone()).unwrap();
+    cli_msgs_0.extend(msgs_0);
+
+    let SignOutput { sig: sig_0, .. } =
+        SignClient::sign_step_2(&cli_config, &mut st_0, &cli_msgs_0, cli_presign_res).unwrap();
+    let SignOutput { sig: sig_1, .. } =
+        SignServer::sign_step_2(&srv_config, &mut st_1, &srv_msgs_1, srv_presign_res).unwrap();
+
+    assert_eq!(sig_0.s, sig_1.s);
+    assert!(sig_0.verify(&keygen_0.public_key, &msg_hash));
+
+    let sig: Signature<NistP256> =
+        Signature::from_scalars(compat::x_coordinate::<NistP256>(&sig_0.big_r), sig_0.s).unwrap();
+
+    verify_sig(sig, keygen_1.public_key, msg).unwrap();
+}
+
+#[test]
+pub fn test_p256_sample_scalar_constant_time() {
+    use elliptic_curve::Field;
+    use rand_core::OsRng;
+
+    let a = NistP256::sample_scalar_constant_time(&mut OsRng);
+    let b = NistP256::sample_scalar_constant_time(&mut OsRng);
+    assert_ne!(a, b);
+    assert!(!bool::from(a.is_zero()));
+}
@@ -0,0 +1,236 @@
+use k256::Secp256k1;
+
+use crate::compat::scalar_hash;
+use crate::protocol::{run_protocol, Action, Participant, Protocol, ProtocolError};
+use crate::sign::{RcvdSignMessages, SignOutput};
+use crate::tecdsa::triples_2::TriplesGenManyResult;
+use crate::tecdsa_cli_srv::cli_sign::{ClientSignStepOutput, SignClient};
+use crate::tecdsa_cli_srv::protocols::{keygen, presign, sign, triples};
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::tecdsa_cli_srv::srv_sign::{ServerSignStepOutput, SignServer};
+use crate::{KeygenOutput, PresignOutput};
+
+/// The protocols for each participant, as expected by `run_protocol`.
+type Protocols<T> = Vec<(Participant, Box<dyn Protocol<Output = T>>)>;
+
+fn configs(participants: &[Participant], threshold: usize) -> Vec<SessionConfig> {
+    participants
+        .iter()
+        .map(|p| {
+            SessionConfig::new(participants.to_vec(), *p, threshold, "session").with_key_id("key")
+        })
+        .collect()
+}
+
+#[test]
+pub fn test_e2e_cli_srv_protocols() {
+    let participants = vec![
+        Participant::from(0u32),
+        Participant::from(1u32),
+        Participant::from(2u32),
+    ];
+
+    let mut protocols: Protocols<KeygenOutput<Secp256k1>> = vec![];
+    for config in configs(&participants, 2) {
+        protocols.push((config.me, Box::new(keygen(config).unwrap())));
+    }
+    let mut keygen_outs = run_protocol(protocols).unwrap();
+    keygen_outs.sort_by_key(|(p, _)| *p);
+    let public_key = keygen_outs[0].1.public_key;
+    assert!(keygen_outs
+        .iter()
+        .all(|(_, out)| out.public_key == public_key));
+
+    // Sign with the first and the last participant.
+    let signers = vec![participants[0], participants[2]];
+    let signer_configs = configs(&signers, 2);
+
+    let mut protocols: Protocols<TriplesGenManyResult<Secp256k1>> = vec![];
+    for config in signer_configs.clone() {
+        protocols.push((config.me, Box::new(triples(config).unwrap())));
+    }
+    let mut triples_outs = run_protocol(protocols).unwrap();
+    triples_outs.sort_by_key(|(p, _)| *p);
+
+    let mut protocols: Protocols<PresignOutput<Secp256k1>> = vec![];
+    for (config, (_, triples_out)) in signer_configs.iter().zip(triples_outs) {
+        let keygen_out = keygen_outs
+            .iter()
+            .find(|(p, _)| *p == config.me)
+            .unwrap()
+            .1
+            .clone();
+        let protocol = presign(
+            config.clone(),
+            triples_out.pub_v[0].clone(),
+            triples_out.pub_v[1].clone(),
+            triples_out.share_v[0].clone(),
+            triples_out.share_v[1].clone(),
+            keygen_out,
+        )
+        .unwrap();
+        protocols.push((config.me, Box::new(protocol)));
+    }
+    let mut presign_outs = run_protocol(protocols).unwrap();
+    presign_outs.sort_by_key(|(p, _)| *p);
+    assert_eq!(presign_outs[0].1.big_r, presign_outs[1].1.big_r);
+
+    let msg_hash = scalar_hash(b"some");
+
+    let mut protocols: Protocols<SignOutput<Secp256k1>> = vec![];
+    for (config, (_, presign_out)) in signer_configs.iter().zip(presign_outs) {
+        let protocol = sign(config.clone(), presign_out, msg_hash).unwrap();
+        protocols.push((config.me, Box::new(protocol)));
+    }
+    let sign_outs = run_protocol(protocols).unwrap();
+
+    assert_eq!(sign_outs[0].1.sig.s, sign_outs[1].1.sig.s);
+    assert!(sign_outs[0].1.sig.verify(&public_key, &msg_hash));
+    assert!(sign_outs[0].1.recoverable().verify(&public_key, &msg_hash));
+}
+
+#[test]
+pub fn test_cli_srv_protocols_bad_parameters() {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+
+    assert!(keygen::<Secp256k1>(SessionConfig::new(
+        participants.clone(),
+        Participant::from(2u32),
+        2,
+        "session"
+    ))
+    .is_err());
+
+    let three = vec![participants[0], participants[1], Participant::from(2u32)];
+    assert!(
+        triples::<Secp256k1>(SessionConfig::new(three, participants[0], 2, "session")).is_err()
+    );
+}
+
+#[test]
+pub fn test_cli_srv_sign_identifies_bad_partial() {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    let configs = configs(&participants, 2);
+
+    let mut protocols: Protocols<KeygenOutput<Secp256k1>> = vec![];
+    for config in configs.clone() {
+        protocols.push((config.me, Box::new(keygen(config).unwrap())));
+    }
+    let mut keygen_outs = run_protocol(protocols).unwrap();
+    keygen_outs.sort_by_key(|(p, _)| *p);
+
+    let mut protocols: Protocols<TriplesGenManyResult<Secp256k1>> = vec![];
+    for config in configs.clone() {
+        protocols.push((config.me, Box::new(triples(config).unwrap())));
+    }
+    let mut triples_outs = run_protocol(protocols).unwrap();
+    triples_outs.sort_by_key(|(p, _)| *p);
+
+    let mut protocols: Protocols<PresignOutput<Secp256k1>> = vec![];
+    for ((config, (_, triples_out)), (_, keygen_out)) in
+        configs.iter().zip(triples_outs).zip(keygen_outs)
+    {
+        let protocol = presign(
+            config.clone(),
+            triples_out.pub_v[0].clone(),
+            triples_out.pub_v[1].clone(),
+            triples_out.share_v[0].clone(),
+            triples_out.share_v[1].clone(),
+            keygen_out,
+        )
+        .unwrap();
+        protocols.push((config.me, Box::new(protocol)));
+    }
+    let mut presign_outs = run_protocol(protocols).unwrap();
+    presign_outs.sort_by_key(|(p, _)| *p);
+    assert_eq!(presign_outs[0].1.commitments, presign_outs[1].1.commitments);
+    assert_eq!(presign_outs[0].1.commitments.len(), 2);
+
+    let msg_hash = scalar_hash(b"some");
+    let presig_0 = presign_outs[0].1.clone();
+    let presig_1 = presign_outs[1].1.clone();
+
+    let ClientSignStepOutput { mut st_0, .. } =
+        SignClient::sign_step_1(&configs[0], msg_hash, presig_0.clone()).unwrap();
+    let ServerSignStepOutput { msgs_0, .. } =
+        SignServer::sign_step_1(&configs[1], msg_hash, presig_1).unwrap();
+
+    // The server sends a partial signature for a different message.
+    let mut cli_msgs_0 = RcvdSignMessages::<Secp256k1>::new();
+    let bad_s_1 = SignServer::sign_step_1(
+        &configs[1],
+        scalar_hash(b"other"),
+        presign_outs[1].1.clone(),
+    )
+    .unwrap()
+    .msgs_0
+    .wait_0
+    .remove(&participants[1])
+    .unwrap();
+    cli_msgs_0.wait_0.insert(participants[1], bad_s_1);
+    cli_msgs_0.wait_0_confirmations.insert(
+        participants[1],
+        msgs_0.wait_0_confirmations[&participants[1]],
+    );
+
+    match SignClient::sign_step_2(&configs[0], &mut st_0, &cli_msgs_0, presig_0.clone()) {
+        Err(ProtocolError::BadShare(p, _)) => assert_eq!(p, participants[1]),
+        res => panic!("expected a bad share from the server, got {res:?}"),
+    }
+
+    // The honest partial signature still goes through.
+    cli_msgs_0.extend(msgs_0);
+    assert!(SignClient::sign_step_2(&configs[0], &mut st_0, &cli_msgs_0, presig_0.clone()).is_ok());
+
+    // Without commitments, the partial signatures can't be checked at all.
+    let ClientSignStepOutput { mut st_0, .. } =
+        SignClient::sign_step_1(&configs[0], msg_hash, presig_0.clone()).unwrap();
+    let mut bare_presig = presig_0;
+    bare_presig.commitments.clear();
+    match SignClient::sign_step_2(&configs[0], &mut st_0, &cli_msgs_0, bare_presig) {
+        Err(ProtocolError::Malformed(_)) => {}
+        res => panic!("expected a malformed presignature, got {res:?}"),
+    }
+}
+
+#[test]
+pub fn test_cli_srv_protocols_keep_failing_with_the_first_error() {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    // The key id is bound into the commitments, so those of the other party won't open.
+    let mut protocols: Vec<_> = ["a", "b"]
+        .iter()
+        .zip(&participants)
+        .map(|(key_id, p)| {
+            let config =
+                SessionConfig::new(participants.clone(), *p, 2, "session").with_key_id(*key_id);
+            keygen::<Secp256k1>(config).unwrap()
+        })
+        .collect();
+
+    let (i, err) = 'run: loop {
+        for i in 0..2 {
+            loop {
+                match protocols[i].poke() {
+                    Ok(Action::Wait) => break,
+                    Ok(Action::SendMany(data)) | Ok(Action::SendPrivate(_, data)) => {
+                        protocols[1 - i].message(participants[i], data)
+                    }
+                    Ok(Action::Return(_)) => panic!("keygen should fail"),
+                    Err(e) => break 'run (i, e),
+                }
+            }
+        }
+    };
+    assert!(
+        matches!(err, ProtocolError::BadShare(p, _) if p == participants[1 - i]),
+        "unexpected error {err:?}"
+    );
+
+    // Later pokes report the same error, rather than a missing state.
+    for _ in 0..2 {
+        match protocols[i].poke() {
+            Err(e) => assert_eq!(e.to_string(), err.to_string()),
+            res => panic!("expected the first error again, got {:?}", res.err()),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use k256::{AffinePoint, ProjectivePoint, Secp256k1};
+
+use crate::keyshare::{KeyshareState2, RcvdKeyshareMessages};
+use crate::participants::ParticipantList;
+use crate::protocol::{Participant, ProtocolError};
+use crate::tecdsa_cli_srv::cli_keygen::{
+    ClientKeygenPrivateStepOutput, ClientKeygenStepOutput, KeygenClient,
+};
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::tecdsa_cli_srv::srv_keygen::{
+    KeygenServer, ServerKeygenPrivateStepOutput, ServerKeygenStepOutput,
+};
+use crate::KeygenOutput;
+
+/// Run steps 2 to 4 of a 2-party keygen, leaving the inboxes ready for step 5.
+pub(super) fn run_steps_2_to_4(
+    cli_config: &SessionConfig,
+    srv_config: &SessionConfig,
+    st_0: KeyshareState2<Secp256k1>,
+    st_1: KeyshareState2<Secp256k1>,
+    cli_msgs_0: &mut RcvdKeyshareMessages<Secp256k1>,
+    srv_msgs_1: &mut RcvdKeyshareMessages<Secp256k1>,
+) -> (KeyshareState2<Secp256k1>, KeyshareState2<Secp256k1>) {
+    let ClientKeygenStepOutput { st_0, msgs_1 } =
+        KeygenClient::cli_keygen_step_2(cli_config, st_0, cli_msgs_0).unwrap();
+    srv_msgs_1.extend(msgs_1);
+    let ServerKeygenStepOutput { st_1, msgs_0 } =
+        KeygenServer::srv_keygen_step_2(srv_config, st_1, srv_msgs_1).unwrap();
+    cli_msgs_0.extend(msgs_0);
+
+    let ClientKeygenStepOutput { st_0, msgs_1 } =
+        KeygenClient::cli_keygen_step_3(cli_config, st_0, cli_msgs_0).unwrap();
+    srv_msgs_1.extend(msgs_1);
+    let ServerKeygenStepOutput { st_1, msgs_0 } =
+        KeygenServer::srv_keygen_step_3(srv_config, st_1, srv_msgs_1).unwrap();
+    cli_msgs_0.extend(msgs_0);
+
+    let ClientKeygenPrivateStepOutput { st_0, mut msgs_1 } =
+        KeygenClient::cli_keygen_step_4(cli_config, st_0, cli_msgs_0).unwrap();
+    srv_msgs_1.extend(msgs_1.remove(&srv_config.me).unwrap());
+    let ServerKeygenPrivateStepOutput { st_1, mut msgs_0 } =
+        KeygenServer::srv_keygen_step_4(srv_config, st_1, srv_msgs_1).unwrap();
+    cli_msgs_0.extend(msgs_0.remove(&cli_config.me).unwrap());
+
+    (st_0, st_1)
+}
+
+fn refresh(
+    cli_config: &SessionConfig,
+    srv_config: &SessionConfig,
+    keygen_0: KeygenOutput<Secp256k1>,
+    keygen_1: KeygenOutput<Secp256k1>,
+    public_key: AffinePoint,
+) -> (
+    Result<KeygenOutput<Secp256k1>, ProtocolError>,
+    Result<KeygenOutput<Secp256k1>, ProtocolError>,
+) {
+    let mut cli_msgs_0 = RcvdKeyshareMessages::<Secp256k1>::new();
+    let mut srv_msgs_1 = RcvdKeyshareMessages::<Secp256k1>::new();
+
+    let ClientKeygenStepOutput { st_0, msgs_1 } =
+        KeygenClient::cli_refresh_step_1(cli_config, keygen_0).unwrap();
+    srv_msgs_1.extend(msgs_1);
+    let ServerKeygenStepOutput { st_1, msgs_0 } =
+        KeygenServer::srv_refresh_step_1(srv_config, keygen_1).unwrap();
+    cli_msgs_0.extend(msgs_0);
+
+    let (st_0, st_1) = run_steps_2_to_4(
+        cli_config,
+        srv_config,
+        st_0,
+        st_1,
+        &mut cli_msgs_0,
+        &mut srv_msgs_1,
+    );
+
+    (
+        KeygenClient::cli_refresh_step_5(cli_config, st_0, &cli_msgs_0, public_key),
+        KeygenServer::srv_refresh_step_5(srv_config, st_1, &srv_msgs_1, public_key),
+    )
+}
+
+#[test]
+pub fn test_e2e_cli_srv_refresh() {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    let cli_config =
+        SessionConfig::new(participants.clone(), participants[0], 2, "session").with_key_id("key");
+    let srv_config =
+        SessionConfig::new(participants.clone(), participants[1], 2, "session").with_key_id("key");
+
+    let (keygen_0, keygen_1) = {
+        let mut cli_msgs_0 = RcvdKeyshareMessages::<Secp256k1>::new();
+        let mut srv_msgs_1 = RcvdKeyshareMessages::<Secp256k1>::new();
+
+        let ClientKeygenStepOutput { st_0, msgs_1 } =
+            KeygenClient::cli_keygen_step_1::<Secp256k1>(&cli_config).unwrap();
+        srv_msgs_1.extend(msgs_1);
+        let ServerKeygenStepOutput { st_1, msgs_0 } =
+            KeygenServer::srv_keygen_step_1::<Secp256k1>(&srv_config).unwrap();
+        cli_msgs_0.extend(msgs_0);
+
+        let (st_0, st_1) = run_steps_2_to_4(
+            &cli_config,
+            &srv_config,
+            st_0,
+            st_1,
+            &mut cli_msgs_0,
+            &mut srv_msgs_1,
+        );
+
+        (
+            KeygenClient::cli_keygen_step_5(&cli_config, st_0, &cli_msgs_0).unwrap(),
+            KeygenServer::srv_keygen_step_5(&srv_config, st_1, &srv_msgs_1).unwrap(),
+        )
+    };
+    let public_key = keygen_0.public_key;
+
+    let (refreshed_0, refreshed_1) = refresh(
+        &cli_config,
+        &srv_config,
+        keygen_0.clone(),
+        keygen_1.clone(),
+        public_key,
+    );
+    let (refreshed_0, refreshed_1) = (refreshed_0.unwrap(), refreshed_1.unwrap());
+
+    assert_eq!(refreshed_0.public_key, public_key);
+    assert_eq!(refreshed_1.public_key, public_key);
+    assert_ne!(refreshed_0.private_share, keygen_0.private_share);
+    assert_ne!(refreshed_1.private_share, keygen_1.private_share);
+
+    // The new shares still interpolate to the same private key.
+    let participant_list = ParticipantList::new(&participants).unwrap();
+    let private_key = participant_list.lagrange::<Secp256k1>(participants[0])
+        * refreshed_0.private_share
+        + participant_list.lagrange::<Secp256k1>(participants[1]) * refreshed_1.private_share;
+    assert_eq!(
+        (ProjectivePoint::GENERATOR * private_key).to_affine(),
+        public_key
+    );
+
+    // Refreshing against the wrong public key is rejected.
+    let (res_0, res_1) = refresh(
+        &cli_config,
+        &srv_config,
+        refreshed_0,
+        refreshed_1,
+        AffinePoint::GENERATOR,
+    );
+    assert!(res_0.is_err());
+    assert!(res_1.is_err());
+}
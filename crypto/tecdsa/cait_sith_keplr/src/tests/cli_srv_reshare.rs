@@ -0,0 +1,267 @@
+use k256::{AffinePoint, ProjectivePoint, Scalar, Secp256k1};
+
+use crate::keyshare::{KeyshareState2, RcvdKeyshareMessages};
+use crate::participants::ParticipantList;
+use crate::protocol::{Participant, ProtocolError};
+use crate::tecdsa_cli_srv::cli_keygen::KeygenClient;
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::tecdsa_cli_srv::srv_keygen::KeygenServer;
+use crate::KeygenOutput;
+
+/// The party running `KeygenClient`, everyone else runs `KeygenServer`.
+fn client() -> Participant {
+    Participant::from(0u32)
+}
+
+fn configs(participants: &[Participant], threshold: usize) -> Vec<SessionConfig> {
+    participants
+        .iter()
+        .map(|p| {
+            SessionConfig::new(participants.to_vec(), *p, threshold, "session").with_key_id("key")
+        })
+        .collect()
+}
+
+/// Deliver a broadcast from `from` to every other party's inbox.
+fn broadcast(
+    inboxes: &mut [RcvdKeyshareMessages<Secp256k1>],
+    configs: &[SessionConfig],
+    from: Participant,
+    msgs: RcvdKeyshareMessages<Secp256k1>,
+) {
+    for (config, inbox) in configs.iter().zip(inboxes.iter_mut()) {
+        if config.me != from {
+            inbox.extend(msgs.clone());
+        }
+    }
+}
+
+/// Run a reshare among the parties in `configs`.
+///
+/// `shares` holds the old share of each party in `configs`, if any.
+fn reshare(
+    configs: &[SessionConfig],
+    old_participants: &[Participant],
+    old_threshold: usize,
+    shares: Vec<Option<Scalar>>,
+    public_key: AffinePoint,
+) -> Vec<Result<KeygenOutput<Secp256k1>, ProtocolError>> {
+    let mut inboxes = vec![RcvdKeyshareMessages::<Secp256k1>::new(); configs.len()];
+
+    // step 1
+    let mut states: Vec<KeyshareState2<Secp256k1>> = vec![];
+    for (config, share) in configs.iter().zip(shares) {
+        let (st, msgs) = if config.me == client() {
+            let out =
+                KeygenClient::cli_reshare_step_1(config, old_participants, old_threshold, share)
+                    .unwrap();
+            (out.st_0, out.msgs_1)
+        } else {
+            let out =
+                KeygenServer::srv_reshare_step_1(config, old_participants, old_threshold, share)
+                    .unwrap();
+            (out.st_1, out.msgs_0)
+        };
+        broadcast(&mut inboxes, configs, config.me, msgs);
+        states.push(st);
+    }
+
+    // steps 2 and 3
+    for step in 2..=3 {
+        let mut next_states = vec![];
+        let mut outgoing = vec![];
+        for ((config, st), inbox) in configs.iter().zip(states).zip(&inboxes) {
+            let (st, msgs) = if config.me == client() {
+                let out = match step {
+                    2 => KeygenClient::cli_keygen_step_2(config, st, inbox),
+                    _ => KeygenClient::cli_keygen_step_3(config, st, inbox),
+                }
+                .unwrap();
+                (out.st_0, out.msgs_1)
+            } else {
+                let out = match step {
+                    2 => KeygenServer::srv_keygen_step_2(config, st, inbox),
+                    _ => KeygenServer::srv_keygen_step_3(config, st, inbox),
+                }
+                .unwrap();
+                (out.st_1, out.msgs_0)
+            };
+            next_states.push(st);
+            outgoing.push((config.me, msgs));
+        }
+        for (from, msgs) in outgoing {
+            broadcast(&mut inboxes, configs, from, msgs);
+        }
+        states = next_states;
+    }
+
+    // step 4 (private)
+    let mut next_states = vec![];
+    let mut outgoing = vec![];
+    for ((config, st), inbox) in configs.iter().zip(states).zip(&inboxes) {
+        let (st, msgs) = if config.me == client() {
+            let out = KeygenClient::cli_keygen_step_4(config, st, inbox).unwrap();
+            (out.st_0, out.msgs_1)
+        } else {
+            let out = KeygenServer::srv_keygen_step_4(config, st, inbox).unwrap();
+            (out.st_1, out.msgs_0)
+        };
+        next_states.push(st);
+        outgoing.push(msgs);
+    }
+    for private_msgs in outgoing {
+        for (to, msgs) in private_msgs {
+            let idx = configs.iter().position(|c| c.me == to).unwrap();
+            inboxes[idx].extend(msgs);
+        }
+    }
+
+    // step 5
+    configs
+        .iter()
+        .zip(next_states)
+        .zip(&inboxes)
+        .map(|((config, st), inbox)| {
+            if config.me == client() {
+                KeygenClient::cli_reshare_step_5(config, st, inbox, public_key)
+            } else {
+                KeygenServer::srv_reshare_step_5(config, st, inbox, public_key)
+            }
+        })
+        .collect()
+}
+
+/// Check that every run of `threshold` consecutive shares reconstructs `public_key`.
+fn assert_reconstructs(
+    outs: &[(Participant, KeygenOutput<Secp256k1>)],
+    threshold: usize,
+    public_key: AffinePoint,
+) {
+    for window in outs.windows(threshold) {
+        let participants: Vec<_> = window.iter().map(|(p, _)| *p).collect();
+        let participant_list = ParticipantList::new(&participants).unwrap();
+        let private_key: Scalar = window
+            .iter()
+            .map(|(p, out)| participant_list.lagrange::<Secp256k1>(*p) * out.private_share)
+            .sum();
+        assert_eq!(
+            (ProjectivePoint::GENERATOR * private_key).to_affine(),
+            public_key
+        );
+    }
+}
+
+#[test]
+pub fn test_e2e_cli_srv_reshare() {
+    let client = client();
+    let server = Participant::from(1u32);
+    let backup = Participant::from(2u32);
+
+    // A regular 2-of-2 keygen between the client and the server.
+    let old_participants = vec![client, server];
+    let old_outs: Vec<_> = {
+        let old_configs = configs(&old_participants, 2);
+        let cli = &old_configs[0];
+        let srv = &old_configs[1];
+
+        let mut cli_msgs_0 = RcvdKeyshareMessages::<Secp256k1>::new();
+        let mut srv_msgs_1 = RcvdKeyshareMessages::<Secp256k1>::new();
+
+        let out_0 = KeygenClient::cli_keygen_step_1::<Secp256k1>(cli).unwrap();
+        srv_msgs_1.extend(out_0.msgs_1);
+        let out_1 = KeygenServer::srv_keygen_step_1::<Secp256k1>(srv).unwrap();
+        cli_msgs_0.extend(out_1.msgs_0);
+
+        let out_0 = KeygenClient::cli_keygen_step_2(cli, out_0.st_0, &cli_msgs_0).unwrap();
+        srv_msgs_1.extend(out_0.msgs_1);
+        let out_1 = KeygenServer::srv_keygen_step_2(srv, out_1.st_1, &srv_msgs_1).unwrap();
+        cli_msgs_0.extend(out_1.msgs_0);
+
+        let out_0 = KeygenClient::cli_keygen_step_3(cli, out_0.st_0, &cli_msgs_0).unwrap();
+        srv_msgs_1.extend(out_0.msgs_1);
+        let out_1 = KeygenServer::srv_keygen_step_3(srv, out_1.st_1, &srv_msgs_1).unwrap();
+        cli_msgs_0.extend(out_1.msgs_0);
+
+        let mut out_0 = KeygenClient::cli_keygen_step_4(cli, out_0.st_0, &cli_msgs_0).unwrap();
+        srv_msgs_1.extend(out_0.msgs_1.remove(&server).unwrap());
+        let mut out_1 = KeygenServer::srv_keygen_step_4(srv, out_1.st_1, &srv_msgs_1).unwrap();
+        cli_msgs_0.extend(out_1.msgs_0.remove(&client).unwrap());
+
+        vec![
+            KeygenClient::cli_keygen_step_5(cli, out_0.st_0, &cli_msgs_0).unwrap(),
+            KeygenServer::srv_keygen_step_5(srv, out_1.st_1, &srv_msgs_1).unwrap(),
+        ]
+    };
+    let public_key = old_outs[0].public_key;
+
+    // 2-of-2 => 2-of-3, adding a backup with no old share.
+    let new_participants = vec![client, server, backup];
+    let new_outs = reshare(
+        &configs(&new_participants, 2),
+        &old_participants,
+        2,
+        vec![
+            Some(old_outs[0].private_share),
+            Some(old_outs[1].private_share),
+            None,
+        ],
+        public_key,
+    );
+    let new_outs: Vec<_> = new_participants
+        .iter()
+        .copied()
+        .zip(new_outs.into_iter().map(Result::unwrap))
+        .collect();
+    assert!(new_outs.iter().all(|(_, out)| out.public_key == public_key));
+    assert_reconstructs(&new_outs, 2, public_key);
+    // Any pair can sign, including the client with the backup.
+    assert_reconstructs(&[new_outs[0].clone(), new_outs[2].clone()], 2, public_key);
+
+    // Migrate from the old server to a new one, while the old server is offline.
+    let new_server = Participant::from(3u32);
+    let migrated_participants = vec![client, backup, new_server];
+    let migrated_outs = reshare(
+        &configs(&migrated_participants, 2),
+        &new_participants,
+        2,
+        vec![
+            Some(new_outs[0].1.private_share),
+            Some(new_outs[2].1.private_share),
+            None,
+        ],
+        public_key,
+    );
+    let migrated_outs: Vec<_> = migrated_participants
+        .iter()
+        .copied()
+        .zip(migrated_outs.into_iter().map(Result::unwrap))
+        .collect();
+    assert!(migrated_outs
+        .iter()
+        .all(|(_, out)| out.public_key == public_key));
+    assert_reconstructs(&migrated_outs, 2, public_key);
+}
+
+#[test]
+pub fn test_cli_srv_reshare_bad_parameters() {
+    let client = client();
+    let server = Participant::from(1u32);
+    let new_server = Participant::from(2u32);
+    let config =
+        SessionConfig::new(vec![client, new_server], client, 2, "session").with_key_id("key");
+
+    // Only the client is left from a 2-of-2 key.
+    assert!(KeygenClient::cli_reshare_step_1::<Secp256k1>(
+        &config,
+        &[client, server],
+        2,
+        Some(Scalar::ONE),
+    )
+    .is_err());
+
+    // The client is an old participant, but has lost its share.
+    assert!(
+        KeygenClient::cli_reshare_step_1::<Secp256k1>(&config, &[client, new_server], 2, None,)
+            .is_err()
+    );
+}
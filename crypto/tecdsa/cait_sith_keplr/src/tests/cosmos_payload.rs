@@ -0,0 +1,115 @@
+use elliptic_curve::Field;
+use k256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
+use k256::{ProjectivePoint, Scalar};
+use rand_core::OsRng;
+use serde_json::json;
+
+use crate::compat::x_coordinate;
+use crate::payload::cosmos::{
+    adr036_scalar, adr036_sign_doc, amino_scalar, amino_sign_bytes, compact_signature,
+    direct_scalar, direct_sign_bytes, sha256, sorted_json,
+};
+use crate::payload::hash_to_scalar;
+use crate::sign::FullSignature;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[test]
+fn test_amino_sign_doc() {
+    let sign_doc = json!({
+        "chain_id": "cosmoshub-4",
+        "account_number": "12",
+        "sequence": "3",
+        "fee": { "gas": "200000", "amount": [{ "denom": "uatom", "amount": "5000" }] },
+        "msgs": [{
+            "type": "cosmos-sdk/MsgSend",
+            "value": {
+                "from_address": "cosmos1a",
+                "to_address": "cosmos1b",
+                "amount": [{ "denom": "uatom", "amount": "1" }],
+            },
+        }],
+        "memo": "a <b> & c",
+    });
+
+    let sign_bytes = amino_sign_bytes(&sign_doc).unwrap();
+    assert_eq!(
+        String::from_utf8(sign_bytes.clone()).unwrap(),
+        concat!(
+            r#"{"account_number":"12","chain_id":"cosmoshub-4","#,
+            r#""fee":{"amount":[{"amount":"5000","denom":"uatom"}],"gas":"200000"},"#,
+            r#""memo":"a \u003cb\u003e \u0026 c","#,
+            r#""msgs":[{"type":"cosmos-sdk/MsgSend","value":{"amount":[{"amount":"1","denom":"uatom"}],"#,
+            r#""from_address":"cosmos1a","to_address":"cosmos1b"}}],"sequence":"3"}"#,
+        )
+    );
+    assert_eq!(
+        hex(&sha256(&sign_bytes)),
+        "5f5f576339134a52931d049d7e39a2c0990833b5989eda8fe1cd960622a8616e"
+    );
+    assert_eq!(
+        amino_scalar(&sign_doc).unwrap(),
+        hash_to_scalar(&sha256(&sign_bytes))
+    );
+
+    assert!(amino_sign_bytes(&json!(["not", "a", "sign", "doc"])).is_err());
+}
+
+#[test]
+fn test_direct_sign_doc() {
+    let sign_bytes = direct_sign_bytes(&[1, 2, 3], &[4, 5], "test", 300);
+    assert_eq!(hex(&sign_bytes), "0a03010203120204051a047465737420ac02");
+    // Default values are left out.
+    assert_eq!(direct_sign_bytes(&[1], &[], "", 0), vec![0x0a, 0x01, 0x01]);
+    assert_eq!(
+        direct_scalar(&sign_bytes),
+        hash_to_scalar(&sha256(&sign_bytes))
+    );
+}
+
+#[test]
+fn test_adr036_sign_doc() {
+    let signer = "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu";
+    let data = b"Hello, Keplr!";
+    assert_eq!(
+        sorted_json(&adr036_sign_doc(signer, data)),
+        concat!(
+            r#"{"account_number":"0","chain_id":"","fee":{"amount":[],"gas":"0"},"memo":"","#,
+            r#""msgs":[{"type":"sign/MsgSignData","value":{"data":"SGVsbG8sIEtlcGxyIQ==","#,
+            r#""signer":"cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu"}}],"sequence":"0"}"#,
+        )
+    );
+
+    let expected = "03a7b6ea92f5fec6c16da9bdfa3711138a90e0369b1e7844d1e88b2355327c72";
+    let hash = sha256(sorted_json(&adr036_sign_doc(signer, data)).as_bytes());
+    assert_eq!(hex(&hash), expected);
+    assert_eq!(adr036_scalar(signer, data), hash_to_scalar(&hash));
+}
+
+#[test]
+fn test_compact_signature() {
+    let private_key = Scalar::random(&mut OsRng);
+    let verifying_key =
+        VerifyingKey::from_affine((ProjectivePoint::GENERATOR * private_key).to_affine()).unwrap();
+    let sign_bytes = direct_sign_bytes(&[1, 2, 3], &[4, 5], "test", 300);
+    let msg_hash = direct_scalar(&sign_bytes);
+
+    for _ in 0..8 {
+        let k = Scalar::random(&mut OsRng);
+        let big_r = (ProjectivePoint::GENERATOR * k).to_affine();
+        let r = x_coordinate::<k256::Secp256k1>(&big_r);
+        let s = k.invert().unwrap() * (msg_hash + r * private_key);
+
+        // Both s and -s are valid, the encoding must pick the low one.
+        for s in [s, -s] {
+            let compact = compact_signature(&FullSignature { big_r, s });
+            let sig = Signature::from_slice(&compact).unwrap();
+            assert!(sig.normalize_s().is_none());
+            verifying_key
+                .verify_prehash(&sha256(&sign_bytes), &sig)
+                .unwrap();
+        }
+    }
+}
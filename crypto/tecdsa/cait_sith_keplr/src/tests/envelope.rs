@@ -0,0 +1,104 @@
+use k256::Secp256k1;
+use p256::NistP256;
+
+use crate::protocol::{run_protocol, Participant, Protocol};
+use crate::sign::RcvdSignMessages;
+use crate::tecdsa_cli_srv::envelope::{Envelope, EnvelopeError, ProtocolKind, WIRE_VERSION};
+use crate::tecdsa_cli_srv::protocols::keygen;
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::KeygenOutput;
+
+fn envelope(step: u32) -> Envelope {
+    Envelope::new::<Secp256k1>(ProtocolKind::Sign, step, "session-a")
+}
+
+#[test]
+fn test_envelope_round_trip() {
+    let mut msgs = RcvdSignMessages::<Secp256k1>::new();
+    msgs.wait_0
+        .insert(Participant::from(1u32), k256::Scalar::from(7u64).into());
+
+    let sealed = envelope(1).seal(&msgs);
+    let (read, _) = Envelope::read(&sealed).unwrap();
+    assert_eq!(read, envelope(1));
+    assert_eq!(read.curve, "Secp256k1");
+
+    let opened: RcvdSignMessages<Secp256k1> = envelope(1).open(&sealed).unwrap();
+    assert_eq!(opened.wait_0, msgs.wait_0);
+    let (step, _) = envelope(9)
+        .open_any_step::<RcvdSignMessages<Secp256k1>>(&sealed)
+        .unwrap();
+    assert_eq!(step, 1);
+}
+
+#[test]
+fn test_envelope_mismatches() {
+    let msgs = RcvdSignMessages::<Secp256k1>::new();
+    let sealed = envelope(1).seal(&msgs);
+    let open = |expected: Envelope| {
+        expected
+            .open::<RcvdSignMessages<Secp256k1>>(&sealed)
+            .unwrap_err()
+    };
+
+    assert!(matches!(
+        open(envelope(2)),
+        EnvelopeError::Step {
+            expected: 2,
+            found: 1
+        }
+    ));
+    assert!(matches!(
+        open(Envelope::new::<Secp256k1>(
+            ProtocolKind::Presign,
+            1,
+            "session-a"
+        )),
+        EnvelopeError::Kind { .. }
+    ));
+    assert!(matches!(
+        open(Envelope::new::<NistP256>(
+            ProtocolKind::Sign,
+            1,
+            "session-a"
+        )),
+        EnvelopeError::Curve { .. }
+    ));
+    assert!(matches!(
+        open(Envelope::new::<Secp256k1>(
+            ProtocolKind::Sign,
+            1,
+            "session-b"
+        )),
+        EnvelopeError::Session { .. }
+    ));
+
+    // A newer version is rejected before the rest is parsed.
+    let mut newer = crate::serde::encode(&(WIRE_VERSION + 1));
+    newer.extend_from_slice(b"anything at all");
+    assert!(matches!(
+        envelope(1).open::<RcvdSignMessages<Secp256k1>>(&newer),
+        Err(EnvelopeError::Version { found, .. }) if found == WIRE_VERSION + 1
+    ));
+
+    // So are bare messages, as sent before there was an envelope.
+    assert!(matches!(
+        envelope(1).open::<RcvdSignMessages<Secp256k1>>(&crate::serde::encode(&msgs)),
+        Err(EnvelopeError::Malformed(_))
+    ));
+}
+
+#[test]
+fn test_protocols_reject_other_sessions() {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    let mut protocols: Vec<(
+        Participant,
+        Box<dyn Protocol<Output = KeygenOutput<Secp256k1>>>,
+    )> = vec![];
+    for (p, session_id) in participants.iter().zip(["session-a", "session-b"]) {
+        let config = SessionConfig::new(participants.clone(), *p, 2, session_id);
+        protocols.push((*p, Box::new(keygen(config).unwrap())));
+    }
+    let err = run_protocol(protocols).unwrap_err();
+    assert!(err.to_string().contains("session"));
+}
@@ -0,0 +1,201 @@
+use elliptic_curve::Field;
+use k256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
+use k256::{ProjectivePoint, Scalar};
+use rand_core::OsRng;
+use serde_json::json;
+
+use crate::compat::x_coordinate;
+use crate::payload::eth::{
+    keccak256, personal_message_hash, personal_message_scalar, transaction_scalar, TypedData,
+};
+use crate::payload::hash_to_scalar;
+use crate::protocol::ProtocolError;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The example from the EIP-712 specification.
+const MAIL: &str = r#"{
+    "types": {
+        "EIP712Domain": [
+            { "name": "name", "type": "string" },
+            { "name": "version", "type": "string" },
+            { "name": "chainId", "type": "uint256" },
+            { "name": "verifyingContract", "type": "address" }
+        ],
+        "Person": [
+            { "name": "name", "type": "string" },
+            { "name": "wallet", "type": "address" }
+        ],
+        "Mail": [
+            { "name": "from", "type": "Person" },
+            { "name": "to", "type": "Person" },
+            { "name": "contents", "type": "string" }
+        ]
+    },
+    "primaryType": "Mail",
+    "domain": {
+        "name": "Ether Mail",
+        "version": "1",
+        "chainId": 1,
+        "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+    },
+    "message": {
+        "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+        "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+        "contents": "Hello, Bob!"
+    }
+}"#;
+
+#[test]
+fn test_keccak256() {
+    assert_eq!(
+        hex(&keccak256(b"")),
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    );
+}
+
+#[test]
+fn test_personal_message_hash() {
+    assert_eq!(
+        hex(&personal_message_hash(b"Hello World")),
+        "a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2"
+    );
+    assert_eq!(
+        personal_message_scalar(b"Hello World"),
+        hash_to_scalar(&personal_message_hash(b"Hello World"))
+    );
+}
+
+#[test]
+fn test_typed_data_mail() {
+    let typed_data = TypedData::from_json(MAIL).unwrap();
+    assert_eq!(
+        typed_data.encode_type("Mail").unwrap(),
+        "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+    );
+    assert_eq!(
+        hex(&typed_data.type_hash("Mail").unwrap()),
+        "a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2"
+    );
+    assert_eq!(
+        hex(&typed_data.domain_separator().unwrap()),
+        "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+    );
+    assert_eq!(
+        hex(&typed_data.hash_struct("Mail", &typed_data.message).unwrap()),
+        "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+    );
+    assert_eq!(
+        hex(&typed_data.hash().unwrap()),
+        "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+    );
+}
+
+#[test]
+fn test_typed_data_values() {
+    let mut typed_data = TypedData::from_json(MAIL).unwrap();
+    let hash = typed_data.hash().unwrap();
+
+    // Integers may be given as decimal or hex strings.
+    typed_data.domain["chainId"] = json!("1");
+    assert_eq!(typed_data.hash().unwrap(), hash);
+    typed_data.domain["chainId"] = json!("0x01");
+    assert_eq!(typed_data.hash().unwrap(), hash);
+
+    typed_data.domain["chainId"] = json!("-1");
+    assert!(typed_data.hash().is_err());
+    typed_data.domain["chainId"] = json!(1);
+    typed_data.message["from"]["wallet"] = json!("0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD8");
+    assert!(typed_data.hash().is_err());
+    typed_data.message["from"] = json!({ "name": "Cow" });
+    assert!(typed_data.hash().is_err());
+}
+
+#[test]
+fn test_typed_data_arrays_and_integers() {
+    let typed_data = TypedData::from_json(
+        r#"{
+            "types": {
+                "EIP712Domain": [{ "name": "name", "type": "string" }],
+                "Batch": [
+                    { "name": "amounts", "type": "int8[2]" },
+                    { "name": "tags", "type": "bytes4[]" },
+                    { "name": "data", "type": "bytes" },
+                    { "name": "ok", "type": "bool" }
+                ]
+            },
+            "primaryType": "Batch",
+            "domain": { "name": "Test" },
+            "message": {
+                "amounts": [-128, "127"],
+                "tags": ["0xdeadbeef"],
+                "data": "0x0102",
+                "ok": true
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let mut amounts = [0xffu8; 32].to_vec();
+    amounts[31] = 0x80;
+    amounts.extend_from_slice(&[0u8; 31]);
+    amounts.push(0x7f);
+    let mut tag = [0u8; 32];
+    tag[..4].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+    let mut ok = [0u8; 32];
+    ok[31] = 1;
+
+    let mut encoded = typed_data.type_hash("Batch").unwrap().to_vec();
+    encoded.extend_from_slice(&keccak256(&amounts));
+    encoded.extend_from_slice(&keccak256(&tag));
+    encoded.extend_from_slice(&keccak256(&[1, 2]));
+    encoded.extend_from_slice(&ok);
+    assert_eq!(
+        typed_data
+            .hash_struct("Batch", &typed_data.message)
+            .unwrap(),
+        keccak256(&encoded)
+    );
+
+    let mut out_of_range = typed_data.clone();
+    out_of_range.message["amounts"] = json!([-129, 0]);
+    assert!(out_of_range.hash().is_err());
+    let mut wrong_length = typed_data.clone();
+    wrong_length.message["amounts"] = json!([0]);
+    assert!(wrong_length.hash().is_err());
+
+    // Bytes must be given as ASCII hex digits, without a sign.
+    for data in ["0xé0", "0x0é0", "0x+1", "0x0g"] {
+        let mut invalid = typed_data.clone();
+        invalid.message["data"] = json!(data);
+        assert!(
+            matches!(invalid.hash(), Err(ProtocolError::Malformed(_))),
+            "{data}"
+        );
+    }
+}
+
+#[test]
+fn test_transaction_scalar_signs() {
+    let private_key = Scalar::random(&mut OsRng);
+    let public_key = (ProjectivePoint::GENERATOR * private_key).to_affine();
+
+    let raw_tx = b"\x02\xf0\x01\x80\x84\x3b\x9a\xca\x00";
+    let msg_hash = transaction_scalar(raw_tx);
+    assert_eq!(msg_hash, hash_to_scalar(&keccak256(raw_tx)));
+
+    let k = Scalar::random(&mut OsRng);
+    let big_r = (ProjectivePoint::GENERATOR * k).to_affine();
+    let r = x_coordinate::<k256::Secp256k1>(&big_r);
+    let s = k.invert().unwrap() * (msg_hash + r * private_key);
+
+    // The scalar is what k256 signs for the same prehash.
+    let sig = Signature::from_scalars(r, s).unwrap();
+    let sig = sig.normalize_s().unwrap_or(sig);
+    VerifyingKey::from_affine(public_key)
+        .unwrap()
+        .verify_prehash(&keccak256(raw_tx), &sig)
+        .unwrap();
+}
@@ -0,0 +1,236 @@
+use std::ffi::CString;
+use std::ptr;
+
+use k256::Secp256k1;
+
+use crate::ffi::*;
+use crate::keyshare::RcvdKeyshareMessages;
+use crate::protocol::Participant;
+use crate::sign::FullSignature;
+use crate::tecdsa_cli_srv::bindings::envelope;
+use crate::tecdsa_cli_srv::envelope::ProtocolKind;
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::KeygenOutput;
+
+fn config(me: u32) -> CString {
+    CString::new(format!(
+        r#"{{"participants":[0,1],"me":{},"threshold":2,"session_id":"session","key_id":"key"}}"#,
+        me
+    ))
+    .unwrap()
+}
+
+unsafe fn take(buffer: CskBuffer) -> Vec<u8> {
+    let out = if buffer.data.is_null() {
+        vec![]
+    } else {
+        std::slice::from_raw_parts(buffer.data, buffer.len).to_vec()
+    };
+    csk_buffer_free(buffer);
+    out
+}
+
+/// Drive the sessions of participants 0 and 1 to completion, then free them.
+unsafe fn run(sessions: [*mut CskSession; 2]) -> [Vec<u8>; 2] {
+    let mut done = [false; 2];
+    while !done.iter().all(|d| *d) {
+        for i in 0..2 {
+            loop {
+                let mut action = CskAction {
+                    kind: CskActionKind::Wait,
+                    to: 0,
+                    data: CskBuffer {
+                        data: ptr::null_mut(),
+                        len: 0,
+                    },
+                };
+                assert_eq!(csk_session_poke(sessions[i], &mut action), CskStatus::Ok);
+                match action.kind {
+                    CskActionKind::Wait => break,
+                    CskActionKind::SendMany | CskActionKind::SendPrivate => {
+                        let data = take(action.data);
+                        let status = csk_session_message(
+                            sessions[1 - i],
+                            i as u32,
+                            data.as_ptr(),
+                            data.len(),
+                        );
+                        assert_eq!(status, CskStatus::Ok);
+                    }
+                    CskActionKind::Return => {
+                        done[i] = true;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    sessions.map(|session| {
+        let mut out = CskBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        };
+        assert_eq!(csk_session_output(session, &mut out), CskStatus::Ok);
+        csk_session_free(session);
+        take(out)
+    })
+}
+
+unsafe fn new_sessions(f: impl Fn(u32, *mut *mut CskSession) -> CskStatus) -> [*mut CskSession; 2] {
+    [0, 1].map(|me| {
+        let mut session = ptr::null_mut();
+        assert_eq!(f(me, &mut session), CskStatus::Ok);
+        session
+    })
+}
+
+#[test]
+fn test_ffi_keygen_to_sign() {
+    unsafe {
+        let keygens = run(new_sessions(|me, out| {
+            csk_keygen_new(config(me).as_ptr(), out)
+        }));
+        let triples = run(new_sessions(|me, out| {
+            csk_triples_new(config(me).as_ptr(), out)
+        }));
+        let presigs = run(new_sessions(|me, out| {
+            let (t, k) = (&triples[me as usize], &keygens[me as usize]);
+            csk_presign_new(
+                config(me).as_ptr(),
+                t.as_ptr(),
+                t.len(),
+                k.as_ptr(),
+                k.len(),
+                out,
+            )
+        }));
+        let msg_hash = crate::payload::eth::personal_message_hash(b"hello");
+        let sigs = run(new_sessions(|me, out| {
+            let p = &presigs[me as usize];
+            csk_sign_new(
+                config(me).as_ptr(),
+                msg_hash.as_ptr(),
+                p.as_ptr(),
+                p.len(),
+                out,
+            )
+        }));
+
+        assert_eq!(sigs[0], sigs[1]);
+        let keygen_0: KeygenOutput<Secp256k1> = crate::serde::decode(&keygens[0]).unwrap();
+        let sig = FullSignature::<Secp256k1>::from_recoverable_bytes(&sigs[0]).unwrap();
+        assert!(sig.verify(
+            &keygen_0.public_key,
+            &crate::payload::hash_to_scalar(&msg_hash)
+        ));
+    }
+}
+
+#[test]
+fn test_ffi_errors() {
+    unsafe {
+        let mut session = ptr::null_mut();
+        assert_eq!(
+            csk_keygen_new(ptr::null(), &mut session),
+            CskStatus::NullPointer
+        );
+        let bad = CString::new("{").unwrap();
+        assert_eq!(
+            csk_keygen_new(bad.as_ptr(), &mut session),
+            CskStatus::InvalidConfig
+        );
+        assert!(session.is_null());
+
+        let junk = [0xc1u8];
+        assert_eq!(
+            csk_presign_new(
+                config(0).as_ptr(),
+                junk.as_ptr(),
+                junk.len(),
+                junk.as_ptr(),
+                junk.len(),
+                &mut session
+            ),
+            CskStatus::DecodeError
+        );
+
+        assert_eq!(
+            csk_keygen_new(config(0).as_ptr(), &mut session),
+            CskStatus::Ok
+        );
+        let mut out = CskBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        };
+        assert_eq!(
+            csk_session_output(session, &mut out),
+            CskStatus::NotFinished
+        );
+        assert_eq!(csk_session_last_error(session, &mut out), CskStatus::Ok);
+        assert!(!take(out).is_empty());
+        csk_session_free(session);
+        csk_session_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn test_ffi_session_poisoned_on_error() {
+    unsafe {
+        let mut session = ptr::null_mut();
+        assert_eq!(
+            csk_keygen_new(config(0).as_ptr(), &mut session),
+            CskStatus::Ok
+        );
+        let mut action = CskAction {
+            kind: CskActionKind::Wait,
+            to: 0,
+            data: CskBuffer {
+                data: ptr::null_mut(),
+                len: 0,
+            },
+        };
+        assert_eq!(csk_session_poke(session, &mut action), CskStatus::Ok);
+        take(std::mem::replace(
+            &mut action.data,
+            CskBuffer {
+                data: ptr::null_mut(),
+                len: 0,
+            },
+        ));
+
+        // A null buffer is the caller's mistake, and leaves the session usable.
+        assert_eq!(
+            csk_session_message(session, 1, ptr::null(), 1),
+            CskStatus::NullPointer
+        );
+
+        // Participant 1 claims to have sent its first round, without its message.
+        let config = SessionConfig::new(
+            vec![Participant::from(0u32), Participant::from(1u32)],
+            Participant::from(1u32),
+            2,
+            "session",
+        );
+        let empty = envelope(&config, ProtocolKind::Keygen, 1)
+            .seal(&RcvdKeyshareMessages::<Secp256k1>::new());
+        assert_eq!(
+            csk_session_message(session, 1, empty.as_ptr(), empty.len()),
+            CskStatus::Ok
+        );
+        assert_eq!(
+            csk_session_poke(session, &mut action),
+            CskStatus::MissingMessage
+        );
+
+        let mut out = CskBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        };
+        assert_eq!(csk_session_poke(session, &mut action), CskStatus::Poisoned);
+        assert_eq!(csk_session_output(session, &mut out), CskStatus::Poisoned);
+        assert_eq!(csk_session_last_error(session, &mut out), CskStatus::Ok);
+        assert!(!take(out).is_empty());
+        csk_session_free(session);
+    }
+}
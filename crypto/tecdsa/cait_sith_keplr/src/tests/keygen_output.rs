@@ -0,0 +1,66 @@
+use elliptic_curve::Field;
+use k256::{AffinePoint, ProjectivePoint, Scalar, Secp256k1};
+use rand_core::OsRng;
+use serde::Serialize;
+
+use crate::keyshare::KEYGEN_OUTPUT_VERSION;
+use crate::protocol::Participant;
+use crate::serde::{decode, encode};
+use crate::tecdsa::keygen_centralized::keygen_centralized;
+use crate::KeygenOutput;
+
+/// `KeygenOutput` as it was serialized before versioning.
+#[derive(Serialize)]
+struct KeygenOutputV1 {
+    private_share: Scalar,
+    public_key: AffinePoint,
+}
+
+#[test]
+pub fn test_keygen_output_public_shares() {
+    let participants = vec![
+        Participant::from(0u32),
+        Participant::from(1u32),
+        Participant::from(2u32),
+    ];
+    let out = keygen_centralized::<Secp256k1>(&participants, 2).unwrap();
+
+    for keygen_out in &out.keygen_outputs {
+        assert_eq!(keygen_out.version, KEYGEN_OUTPUT_VERSION);
+        for (p, other) in participants.iter().zip(&out.keygen_outputs) {
+            assert_eq!(
+                keygen_out.public_share(*p),
+                Some((ProjectivePoint::GENERATOR * other.private_share).to_affine())
+            );
+        }
+    }
+}
+
+#[test]
+pub fn test_keygen_output_serde_compat() {
+    let private_share = Scalar::random(&mut OsRng);
+    let public_key = (ProjectivePoint::GENERATOR * private_share).to_affine();
+
+    let v1 = encode(&KeygenOutputV1 {
+        private_share,
+        public_key,
+    });
+    let out: KeygenOutput<Secp256k1> = decode(&v1).unwrap();
+    assert_eq!(out.version, 1);
+    assert_eq!(out.private_share, private_share);
+    assert_eq!(out.public_key, public_key);
+    assert!(out.big_f.is_none());
+    assert!(out.public_share(Participant::from(0u32)).is_none());
+
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    let keygen_out = keygen_centralized::<Secp256k1>(&participants, 2)
+        .unwrap()
+        .keygen_outputs
+        .remove(0);
+    let out: KeygenOutput<Secp256k1> = decode(&encode(&keygen_out)).unwrap();
+    assert_eq!(out.version, KEYGEN_OUTPUT_VERSION);
+    assert_eq!(
+        out.public_share(participants[1]),
+        keygen_out.public_share(participants[1])
+    );
+}
@@ -9,3 +9,66 @@ mod cli_srv;
 
 #[cfg(test)]
 mod cli_srv_2;
+
+#[cfg(test)]
+mod cli_srv_3;
+
+#[cfg(test)]
+mod cli_srv_p256;
+
+#[cfg(test)]
+mod cli_srv_protocols;
+
+#[cfg(test)]
+mod cli_srv_refresh;
+
+#[cfg(test)]
+mod cli_srv_reshare;
+
+#[cfg(test)]
+mod cli_srv_import;
+
+#[cfg(test)]
+mod keygen_output;
+
+#[cfg(test)]
+mod recoverable;
+
+#[cfg(test)]
+mod eth_payload;
+
+#[cfg(test)]
+mod cosmos_payload;
+
+#[cfg(test)]
+mod address;
+
+#[cfg(test)]
+mod signature_encoding;
+
+#[cfg(all(test, feature = "wasm"))]
+mod wasm_bindings;
+
+#[cfg(all(test, feature = "ffi"))]
+mod ffi;
+
+#[cfg(test)]
+mod envelope;
+
+#[cfg(test)]
+mod step_errors;
+
+#[cfg(test)]
+mod phases;
+
+#[cfg(test)]
+mod session_binding;
+
+#[cfg(test)]
+mod pool;
+
+#[cfg(all(test, feature = "store"))]
+mod store;
+
+#[cfg(test)]
+mod zeroize;
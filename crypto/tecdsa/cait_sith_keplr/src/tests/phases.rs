@@ -0,0 +1,139 @@
+use k256::Secp256k1;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::compat::scalar_hash;
+use crate::protocol::Participant;
+use crate::serde::{decode, encode};
+use crate::tecdsa_cli_srv::phases::{
+    KeygenRound1, KeygenRound3, PresignRound1, SignRound1, TriplesRound1,
+};
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::triples::RcvdTriplesMessages;
+
+/// Store a phase and load it back, as a client or server would between steps.
+fn reload<T: Serialize + DeserializeOwned>(phase: &T) -> T {
+    decode(&encode(phase)).unwrap()
+}
+
+#[test]
+pub fn test_e2e_phases() {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    let cli_config =
+        SessionConfig::new(participants.clone(), participants[0], 2, "session").with_key_id("key");
+    let srv_config =
+        SessionConfig::new(participants.clone(), participants[1], 2, "session").with_key_id("key");
+
+    let (keygen_0, keygen_1) = {
+        let (c, to_srv) = KeygenRound1::<Secp256k1>::start(cli_config.clone()).unwrap();
+        let (s, to_cli) = KeygenRound1::<Secp256k1>::start(srv_config.clone()).unwrap();
+        assert_eq!(c.config(), &cli_config);
+
+        // Both parties run each step on the messages of the other's previous step.
+        let (c, c_out) = reload(&c).step(to_cli).unwrap();
+        let (s, s_out) = reload(&s).step(to_srv).unwrap();
+
+        let ((c, c_out), (s, s_out)) = (
+            reload(&c).step(s_out).unwrap(),
+            reload(&s).step(c_out).unwrap(),
+        );
+
+        let ((c, mut c_out), (s, mut s_out)) = (
+            reload(&c).step(s_out).unwrap(),
+            reload(&s).step(c_out).unwrap(),
+        );
+
+        let to_cli = s_out.remove(&participants[0]).unwrap();
+        let to_srv = c_out.remove(&participants[1]).unwrap();
+        (
+            reload(&c).finish(to_cli).unwrap(),
+            reload(&s).finish(to_srv).unwrap(),
+        )
+    };
+    let public_key = keygen_0.public_key;
+    assert_eq!(keygen_1.public_key, public_key);
+
+    let (triples_0, triples_1) = {
+        let (c, mut to_srv) = TriplesRound1::<Secp256k1>::start(cli_config.clone()).unwrap();
+        let (s, to_cli) = TriplesRound1::<Secp256k1>::start(srv_config.clone()).unwrap();
+
+        // The client runs step n on the server's step n - 1, and the server runs
+        // step n on the client's step n.
+        macro_rules! exchange {
+            ($c:ident, $s:ident, $to_cli:ident, $to_srv:ident) => {
+                let ($c, c_out) = reload(&$c).step($to_cli).unwrap();
+                $to_srv.extend(c_out);
+                let ($s, $to_cli) = reload(&$s).step($to_srv).unwrap();
+                #[allow(unused_mut)]
+                let mut $to_srv = RcvdTriplesMessages::new();
+            };
+        }
+
+        exchange!(c, s, to_cli, to_srv);
+        exchange!(c, s, to_cli, to_srv);
+        exchange!(c, s, to_cli, to_srv);
+        exchange!(c, s, to_cli, to_srv);
+        exchange!(c, s, to_cli, to_srv);
+        exchange!(c, s, to_cli, to_srv);
+        exchange!(c, s, to_cli, to_srv);
+        exchange!(c, s, to_cli, to_srv);
+        exchange!(c, s, to_cli, to_srv);
+
+        (
+            reload(&c).finish(to_cli).unwrap(),
+            reload(&s).finish(to_srv).unwrap(),
+        )
+    };
+    assert_eq!(triples_0.pub_v, triples_1.pub_v);
+
+    let (presign_0, presign_1) = {
+        let (c, to_srv) = PresignRound1::start(
+            cli_config.clone(),
+            triples_0.pub_v[0].clone(),
+            triples_0.pub_v[1].clone(),
+            triples_0.share_v[0].clone(),
+            triples_0.share_v[1].clone(),
+            keygen_0,
+        )
+        .unwrap();
+        let (s, to_cli) = PresignRound1::start(
+            srv_config.clone(),
+            triples_1.pub_v[0].clone(),
+            triples_1.pub_v[1].clone(),
+            triples_1.share_v[0].clone(),
+            triples_1.share_v[1].clone(),
+            keygen_1,
+        )
+        .unwrap();
+
+        let (c, c_out) = reload(&c).step(to_cli).unwrap();
+        let (s, s_out) = reload(&s).step(to_srv).unwrap();
+
+        (
+            reload(&c).finish(s_out).unwrap(),
+            reload(&s).finish(c_out).unwrap(),
+        )
+    };
+    assert_eq!(presign_0.big_r, presign_1.big_r);
+
+    let msg_hash = scalar_hash(b"some");
+    let (c, to_srv) = SignRound1::start(cli_config, presign_0, msg_hash).unwrap();
+    let (s, to_cli) = SignRound1::start(srv_config, presign_1, msg_hash).unwrap();
+
+    let sign_0 = reload(&c).finish(to_cli).unwrap();
+    let sign_1 = reload(&s).finish(to_srv).unwrap();
+
+    assert_eq!(sign_0.sig.s, sign_1.sig.s);
+    assert!(sign_0.sig.verify(&public_key, &msg_hash));
+}
+
+#[test]
+pub fn test_phases_reject_other_rounds() {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    let config = SessionConfig::new(participants.clone(), participants[0], 2, "session");
+    let (round_1, _) = KeygenRound1::<Secp256k1>::start(config).unwrap();
+
+    // Every keygen round has the same state and messages, only the tag tells them apart.
+    let bytes = encode(&round_1);
+    assert!(decode::<KeygenRound1<Secp256k1>>(&bytes).is_ok());
+    assert!(decode::<KeygenRound3<Secp256k1>>(&bytes).is_err());
+}
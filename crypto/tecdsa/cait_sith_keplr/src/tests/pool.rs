@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use elliptic_curve::Field;
+use k256::{ProjectivePoint, Scalar, Secp256k1};
+use rand_core::OsRng;
+
+use crate::protocol::Participant;
+use crate::tecdsa_cli_srv::pool::{PoolError, PoolState, PresignPool, TriplePool};
+use crate::triples::deal;
+use crate::PresignOutput;
+
+/// Deal the triples `ids`, returning the pool of each participant.
+fn triple_pools(ids: &[&str]) -> [TriplePool<Secp256k1>; 2] {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    let mut pools = [TriplePool::default(), TriplePool::default()];
+    for id in ids {
+        let (public, shares) = deal::<Secp256k1>(&mut OsRng, &participants, 2);
+        for (pool, share) in pools.iter_mut().zip(shares) {
+            pool.insert(id, public.clone(), share).unwrap();
+        }
+    }
+    pools
+}
+
+fn presign_output() -> PresignOutput<Secp256k1> {
+    PresignOutput {
+        big_r: (ProjectivePoint::GENERATOR * Scalar::random(&mut OsRng)).into(),
+        k: Scalar::random(&mut OsRng),
+        sigma: Scalar::random(&mut OsRng),
+        commitments: HashMap::new(),
+    }
+}
+
+#[test]
+pub fn test_triple_pool_hands_out_matched_pairs() {
+    let [mut pool_0, mut pool_1] = triple_pools(&["a", "b", "c"]);
+
+    let pair_0 = pool_0.reserve_pair().unwrap();
+    assert_eq!(pair_0.ids, ["a".to_string(), "b".to_string()]);
+
+    // The other party reserves the same pair, by id.
+    let pair_1 = pool_1.reserve_pair_by_id("a", "b").unwrap();
+    assert_eq!(pair_0.pub_0, pair_1.pub_0);
+    assert_eq!(pair_0.pub_1, pair_1.pub_1);
+
+    assert_eq!(pool_0.state("a").unwrap(), Some(PoolState::Reserved));
+    assert_eq!(pool_0.available().unwrap(), 1);
+
+    pool_0.consume(pair_0).unwrap();
+    pool_1.consume(pair_1).unwrap();
+    assert_eq!(pool_0.state("a").unwrap(), Some(PoolState::Consumed));
+    assert_eq!(pool_0.state("d").unwrap(), None);
+}
+
+#[test]
+pub fn test_triple_pool_refuses_second_use() {
+    let [mut pool, _] = triple_pools(&["a", "b", "c"]);
+    let pair = pool.reserve_pair_by_id("a", "b").unwrap();
+
+    assert_eq!(
+        pool.reserve_pair_by_id("a", "c").unwrap_err(),
+        PoolError::Unavailable {
+            id: "a".to_string(),
+            expected: PoolState::Generated,
+            found: PoolState::Reserved,
+        }
+    );
+    assert_eq!(
+        pool.reserve_pair_by_id("c", "c").unwrap_err(),
+        PoolError::Duplicate("c".to_string())
+    );
+    assert_eq!(
+        pool.reserve_pair().unwrap_err(),
+        PoolError::Exhausted {
+            wanted: 2,
+            available: 1,
+        }
+    );
+    // None of the failed reservations took the free triple.
+    assert_eq!(pool.state("c").unwrap(), Some(PoolState::Generated));
+
+    pool.consume(pair).unwrap();
+    assert!(pool.reserve_pair_by_id("a", "b").is_err());
+
+    // A consumed id can't come back with another triple.
+    let (public, mut shares) = deal::<Secp256k1>(
+        &mut OsRng,
+        &[Participant::from(0u32), Participant::from(1u32)],
+        2,
+    );
+    assert_eq!(
+        pool.insert("a", public, shares.remove(0)).unwrap_err(),
+        PoolError::Duplicate("a".to_string())
+    );
+}
+
+#[test]
+pub fn test_presign_pool_refuses_second_use() {
+    let mut pool = PresignPool::<Secp256k1>::default();
+    pool.insert("p", presign_output()).unwrap();
+
+    let presign = pool.reserve().unwrap();
+    assert_eq!(presign.id, "p");
+    assert!(matches!(
+        pool.reserve_by_id("p"),
+        Err(PoolError::Unavailable { .. })
+    ));
+    assert!(matches!(pool.reserve(), Err(PoolError::Exhausted { .. })));
+    assert_eq!(
+        pool.reserve_by_id("q").unwrap_err(),
+        PoolError::Unknown("q".to_string())
+    );
+
+    pool.consume(presign).unwrap();
+    assert_eq!(pool.state("p").unwrap(), Some(PoolState::Consumed));
+    assert!(pool.insert("p", presign_output()).is_err());
+}
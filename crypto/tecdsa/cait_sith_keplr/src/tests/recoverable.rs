@@ -0,0 +1,76 @@
+use elliptic_curve::{scalar::IsHigh, Field, PrimeField};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use k256::{ProjectivePoint, Scalar, Secp256k1};
+use rand_core::OsRng;
+
+use crate::compat::x_coordinate;
+use crate::protocol::ProtocolError;
+use crate::sign::{FullSignature, SignOutput};
+
+/// Sign `msg_hash` the way the sign steps do, normalizing `s` to be low.
+fn sign(private_key: Scalar, msg_hash: Scalar) -> SignOutput<Secp256k1> {
+    let k = Scalar::random(&mut OsRng);
+    let big_r = (ProjectivePoint::GENERATOR * k).to_affine();
+    let r = x_coordinate::<Secp256k1>(&big_r);
+    let s = k.invert().unwrap() * (msg_hash + r * private_key);
+
+    let is_high = bool::from(s.is_high());
+    SignOutput {
+        sig: FullSignature {
+            big_r,
+            s: if is_high { -s } else { s },
+        },
+        is_high,
+    }
+}
+
+#[test]
+pub fn test_recoverable_signature() {
+    let private_key = Scalar::random(&mut OsRng);
+    let public_key = (ProjectivePoint::GENERATOR * private_key).to_affine();
+
+    let mut seen_flipped = [false; 2];
+    for _ in 0..32 {
+        let msg_hash = Scalar::random(&mut OsRng);
+        let out = sign(private_key, msg_hash);
+        seen_flipped[usize::from(out.is_high)] = true;
+
+        let sig = out.recoverable();
+        assert!(sig.recovery_id <= 1);
+        assert_eq!(sig.recover(&msg_hash).unwrap(), public_key);
+        assert!(sig.verify(&public_key, &msg_hash));
+        assert!(!sig.verify(&public_key, &(msg_hash + Scalar::ONE)));
+
+        let bytes = sig.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 65);
+        assert_eq!(bytes[64], sig.recovery_id + 27);
+        assert_eq!(sig.eip155_v(1), u64::from(sig.recovery_id) + 37);
+
+        // The encoding agrees with the k256 recovery.
+        let k256_sig = Signature::from_slice(&bytes[..64]).unwrap();
+        let recovery_id = RecoveryId::from_byte(bytes[64] - 27).unwrap();
+        let recovered =
+            VerifyingKey::recover_from_prehash(&msg_hash.to_repr(), &k256_sig, recovery_id)
+                .unwrap();
+        assert_eq!(*recovered.as_affine(), public_key);
+    }
+    assert_eq!(seen_flipped, [true, true]);
+}
+
+#[test]
+pub fn test_recoverable_signature_wrong_parity() {
+    let private_key = Scalar::random(&mut OsRng);
+    let public_key = (ProjectivePoint::GENERATOR * private_key).to_affine();
+    let msg_hash = Scalar::random(&mut OsRng);
+
+    let mut sig = sign(private_key, msg_hash).recoverable();
+    sig.recovery_id ^= 1;
+    assert!(!sig.verify(&public_key, &msg_hash));
+
+    sig.recovery_id = 2;
+    assert!(matches!(
+        sig.recover(&msg_hash),
+        Err(ProtocolError::Malformed(_))
+    ));
+    assert!(matches!(sig.to_bytes(), Err(ProtocolError::Malformed(_))));
+}
@@ -0,0 +1,181 @@
+use k256::Secp256k1;
+
+use crate::protocol::{Participant, ProtocolError};
+use crate::tecdsa::triples_2::TriplesGenManyResult;
+use crate::tecdsa_cli_srv::phases::{KeygenRound1, PresignRound1, TriplesRound1};
+use crate::tecdsa_cli_srv::session::{ConfigError, SessionConfig};
+use crate::triples::RcvdTriplesMessages;
+use crate::KeygenOutput;
+
+fn participants() -> Vec<Participant> {
+    vec![Participant::from(0u32), Participant::from(1u32)]
+}
+
+fn configs(session_ids: [&str; 2], key_ids: [&str; 2]) -> (SessionConfig, SessionConfig) {
+    let participants = participants();
+    let config = |i: usize| {
+        SessionConfig::new(participants.clone(), participants[i], 2, session_ids[i])
+            .with_key_id(key_ids[i])
+    };
+    (config(0), config(1))
+}
+
+/// Run a key generation between two parties, returning the first error either of them hits.
+fn keygen(
+    cli_config: SessionConfig,
+    srv_config: SessionConfig,
+) -> Result<(KeygenOutput<Secp256k1>, KeygenOutput<Secp256k1>), ProtocolError> {
+    let participants = participants();
+
+    let (c, to_srv) = KeygenRound1::<Secp256k1>::start(cli_config)?;
+    let (s, to_cli) = KeygenRound1::<Secp256k1>::start(srv_config)?;
+    let ((c, c_out), (s, s_out)) = (c.step(to_cli)?, s.step(to_srv)?);
+    let ((c, c_out), (s, s_out)) = (c.step(s_out)?, s.step(c_out)?);
+    let ((c, mut c_out), (s, mut s_out)) = (c.step(s_out)?, s.step(c_out)?);
+
+    let to_cli = s_out.remove(&participants[0]).unwrap();
+    let to_srv = c_out.remove(&participants[1]).unwrap();
+    Ok((c.finish(to_cli)?, s.finish(to_srv)?))
+}
+
+/// Run a triple generation between two parties, returning the first error either of them hits.
+fn triples(
+    cli_config: SessionConfig,
+    srv_config: SessionConfig,
+) -> Result<
+    (
+        TriplesGenManyResult<Secp256k1>,
+        TriplesGenManyResult<Secp256k1>,
+    ),
+    ProtocolError,
+> {
+    let (c, mut to_srv) = TriplesRound1::<Secp256k1>::start(cli_config)?;
+    let (s, to_cli) = TriplesRound1::<Secp256k1>::start(srv_config)?;
+
+    macro_rules! exchange {
+        ($c:ident, $s:ident, $to_cli:ident, $to_srv:ident) => {
+            let ($c, c_out) = $c.step($to_cli)?;
+            $to_srv.extend(c_out);
+            let ($s, $to_cli) = $s.step($to_srv)?;
+            #[allow(unused_mut)]
+            let mut $to_srv = RcvdTriplesMessages::new();
+        };
+    }
+
+    exchange!(c, s, to_cli, to_srv);
+    exchange!(c, s, to_cli, to_srv);
+    exchange!(c, s, to_cli, to_srv);
+    exchange!(c, s, to_cli, to_srv);
+    exchange!(c, s, to_cli, to_srv);
+    exchange!(c, s, to_cli, to_srv);
+    exchange!(c, s, to_cli, to_srv);
+    exchange!(c, s, to_cli, to_srv);
+    exchange!(c, s, to_cli, to_srv);
+
+    Ok((c.finish(to_cli)?, s.finish(to_srv)?))
+}
+
+#[test]
+pub fn test_keygen_rejects_other_session() {
+    let (cli_config, srv_config) = configs(["session-a", "session-b"], ["key", "key"]);
+
+    match keygen(cli_config, srv_config) {
+        Err(ProtocolError::BadShare(..) | ProtocolError::BadProof(..)) => {}
+        res => panic!("expected the sessions to be told apart, got {res:?}"),
+    }
+}
+
+#[test]
+pub fn test_triples_reject_other_session() {
+    let (cli_config, srv_config) = configs(["session-a", "session-b"], ["key", "key"]);
+
+    match triples(cli_config, srv_config) {
+        Err(ProtocolError::BadShare(..) | ProtocolError::BadProof(..)) => {}
+        res => panic!(
+            "expected the sessions to be told apart, got {:?}",
+            res.map(|_| ())
+        ),
+    }
+}
+
+#[test]
+pub fn test_presign_rejects_other_key() {
+    let participants = participants();
+    let (cli_config, srv_config) = configs(["session", "session"], ["key", "key"]);
+    let (keygen_0, keygen_1) = keygen(cli_config.clone(), srv_config.clone()).unwrap();
+    let (triples_0, triples_1) = triples(cli_config.clone(), srv_config.clone()).unwrap();
+
+    // The server presigns for another key id than the client.
+    let srv_config = srv_config.with_key_id("other key");
+
+    let (c, to_srv) = PresignRound1::start(
+        cli_config,
+        triples_0.pub_v[0].clone(),
+        triples_0.pub_v[1].clone(),
+        triples_0.share_v[0].clone(),
+        triples_0.share_v[1].clone(),
+        keygen_0,
+    )
+    .unwrap();
+    let (s, to_cli) = PresignRound1::start(
+        srv_config,
+        triples_1.pub_v[0].clone(),
+        triples_1.pub_v[1].clone(),
+        triples_1.share_v[0].clone(),
+        triples_1.share_v[1].clone(),
+        keygen_1,
+    )
+    .unwrap();
+
+    let (c, _) = c.step(to_cli).unwrap();
+    let (_, s_out) = s.step(to_srv).unwrap();
+
+    match c.finish(s_out) {
+        Err(ProtocolError::BadShare(p, _)) => assert_eq!(p, participants[1]),
+        res => panic!("expected a bad share from the server, got {res:?}"),
+    }
+}
+
+#[test]
+pub fn test_session_config_requires_ids() {
+    let (cli_config, _) = configs(["", "session"], ["key", "key"]);
+    assert!(matches!(
+        cli_config.validate(),
+        Err(ProtocolError::Config(ConfigError::EmptySessionId))
+    ));
+    assert!(KeygenRound1::<Secp256k1>::start(cli_config).is_err());
+
+    let (cli_config, _) = configs(["session", "session"], ["", ""]);
+    assert!(cli_config.validate().is_ok());
+    assert!(matches!(
+        cli_config.validate_with_key(),
+        Err(ProtocolError::Config(ConfigError::EmptyKeyId))
+    ));
+}
+
+#[test]
+pub fn test_session_config_errors() {
+    let (mut config, _) = configs(["session", "session"], ["key", "key"]);
+    config.threshold = 3;
+    assert!(matches!(
+        config.validate(),
+        Err(ProtocolError::Config(ConfigError::ThresholdTooHigh {
+            threshold: 3,
+            participants: 2
+        }))
+    ));
+
+    config.threshold = 2;
+    config.me = Participant::from(2u32);
+    assert!(matches!(
+        config.validate(),
+        Err(ProtocolError::Config(ConfigError::NotAParticipant(_)))
+    ));
+
+    config.participants.push(Participant::from(2u32));
+    assert!(config.validate().is_ok());
+    assert!(matches!(
+        config.validate_two_party(),
+        Err(ProtocolError::Config(ConfigError::NotTwoParties(3)))
+    ));
+}
@@ -0,0 +1,149 @@
+use elliptic_curve::{group::Group, scalar::IsHigh, Field, PrimeField};
+use k256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
+use k256::{ProjectivePoint, Scalar, Secp256k1};
+use rand_core::OsRng;
+
+use crate::compat::{x_coordinate, CSCurve};
+use crate::sign::{FullSignature, SignOutput};
+
+/// Sign `msg_hash` the way the sign steps do, normalizing `s` to be low.
+fn sign<C: CSCurve>(private_key: C::Scalar, msg_hash: C::Scalar) -> SignOutput<C> {
+    let k = C::Scalar::random(&mut OsRng);
+    let big_r = (C::ProjectivePoint::generator() * k).into();
+    let r = x_coordinate::<C>(&big_r);
+    let s = k.invert().unwrap() * (msg_hash + r * private_key);
+
+    let is_high = bool::from(s.is_high());
+    SignOutput {
+        sig: FullSignature {
+            big_r,
+            s: if is_high { -s } else { s },
+        },
+        is_high,
+    }
+}
+
+#[test]
+fn test_signature_encodings_round_trip() {
+    let private_key = Scalar::random(&mut OsRng);
+    let verifying_key =
+        VerifyingKey::from_affine((ProjectivePoint::GENERATOR * private_key).to_affine()).unwrap();
+    let public_key = *verifying_key.as_affine();
+
+    for _ in 0..16 {
+        let msg_hash = Scalar::random(&mut OsRng);
+        let out = sign::<Secp256k1>(private_key, msg_hash);
+        let sig = &out.sig;
+
+        let compact = sig.to_compact();
+        assert_eq!(compact.len(), 64);
+        let parsed = FullSignature::<Secp256k1>::from_compact(&compact).unwrap();
+        assert_eq!(parsed.to_compact(), compact);
+        assert!(parsed.verify(&public_key, &msg_hash));
+
+        // Other tools accept the encodings.
+        let ecdsa_sig = sig.to_ecdsa().unwrap();
+        assert_eq!(ecdsa_sig, Signature::from_slice(&compact).unwrap());
+        verifying_key
+            .verify_prehash(&msg_hash.to_repr(), &ecdsa_sig)
+            .unwrap();
+        let parsed = FullSignature::from_ecdsa(&ecdsa_sig).unwrap();
+        assert_eq!(parsed.to_compact(), compact);
+
+        let der = sig.to_der().unwrap();
+        assert_eq!(der, ecdsa_sig.to_der().as_bytes());
+        let parsed = FullSignature::<Secp256k1>::from_der(&der).unwrap();
+        assert_eq!(parsed.to_compact(), compact);
+
+        // The recoverable encoding keeps the nonce point matching the low s.
+        let recoverable = sig.to_recoverable_bytes(out.is_high).unwrap();
+        assert_eq!(recoverable, out.recoverable().to_bytes().unwrap());
+        let parsed = FullSignature::<Secp256k1>::from_recoverable_bytes(&recoverable).unwrap();
+        let big_r = if out.is_high {
+            -ProjectivePoint::from(sig.big_r)
+        } else {
+            ProjectivePoint::from(sig.big_r)
+        };
+        assert_eq!(parsed.big_r, big_r.to_affine());
+        assert_eq!(parsed.s, sig.s);
+        let mut v01 = recoverable.clone();
+        v01[64] -= 27;
+        assert_eq!(
+            FullSignature::<Secp256k1>::from_recoverable_bytes(&v01)
+                .unwrap()
+                .big_r,
+            parsed.big_r
+        );
+    }
+}
+
+#[test]
+fn test_signature_encodings_p256() {
+    let private_key = p256::Scalar::random(&mut OsRng);
+    let verifying_key = p256::ecdsa::VerifyingKey::from_affine(
+        (p256::ProjectivePoint::GENERATOR * private_key).to_affine(),
+    )
+    .unwrap();
+    let msg_hash = p256::Scalar::random(&mut OsRng);
+    let sig = sign::<p256::NistP256>(private_key, msg_hash).sig;
+
+    let der = sig.to_der().unwrap();
+    let parsed = FullSignature::<p256::NistP256>::from_der(&der).unwrap();
+    assert!(parsed.verify(verifying_key.as_affine(), &msg_hash));
+    verifying_key
+        .verify_prehash(&msg_hash.to_repr(), &parsed.to_ecdsa().unwrap())
+        .unwrap();
+}
+
+#[test]
+fn test_signature_parsing_is_strict() {
+    let private_key = Scalar::random(&mut OsRng);
+    let msg_hash = Scalar::random(&mut OsRng);
+    let sig = sign::<Secp256k1>(private_key, msg_hash).sig;
+    let compact = sig.to_compact();
+
+    // A high s is rejected, even though it verifies.
+    let mut high = compact.clone();
+    high[32..].copy_from_slice(&(-sig.s).to_repr());
+    assert!(FullSignature::<Secp256k1>::from_compact(&high).is_err());
+    let high_sig = Signature::from_slice(&high).unwrap();
+    assert!(FullSignature::<Secp256k1>::from_ecdsa(&high_sig).is_err());
+    assert!(FullSignature::<Secp256k1>::from_der(high_sig.to_der().as_bytes()).is_err());
+
+    // Encoding normalizes s.
+    let flipped = FullSignature::<Secp256k1> {
+        big_r: sig.big_r,
+        s: -sig.s,
+    };
+    assert_eq!(flipped.to_compact(), compact);
+
+    // Zero and out of range scalars are rejected.
+    let mut zero_r = compact.clone();
+    zero_r[..32].fill(0);
+    assert!(FullSignature::<Secp256k1>::from_compact(&zero_r).is_err());
+    let mut zero_s = compact.clone();
+    zero_s[32..].fill(0);
+    assert!(FullSignature::<Secp256k1>::from_compact(&zero_s).is_err());
+    let mut big_r = compact.clone();
+    big_r[..32].fill(0xff);
+    assert!(FullSignature::<Secp256k1>::from_compact(&big_r).is_err());
+
+    // So are bad lengths and recovery ids.
+    assert!(FullSignature::<Secp256k1>::from_compact(&compact[..63]).is_err());
+    assert!(FullSignature::<Secp256k1>::from_recoverable_bytes(&compact).is_err());
+    let mut bad_v = compact.clone();
+    bad_v.push(29);
+    assert!(FullSignature::<Secp256k1>::from_recoverable_bytes(&bad_v).is_err());
+    assert!(FullSignature::<Secp256k1>::from_recoverable_bytes(&[]).is_err());
+
+    // A DER encoding with a superfluous leading zero is not strict DER.
+    let der = sig.to_der().unwrap();
+    let r_len = usize::from(der[3]);
+    let mut padded = vec![0x30, der[1] + 1, 0x02, der[3] + 1, 0x00];
+    padded.extend_from_slice(&der[4..4 + r_len]);
+    padded.extend_from_slice(&der[4 + r_len..]);
+    if der[4] < 0x80 {
+        assert!(FullSignature::<Secp256k1>::from_der(&padded).is_err());
+    }
+    assert!(FullSignature::<Secp256k1>::from_der(&der[..der.len() - 1]).is_err());
+}
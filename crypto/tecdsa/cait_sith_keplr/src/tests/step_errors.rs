@@ -0,0 +1,103 @@
+use k256::Secp256k1;
+
+use crate::keyshare::RcvdKeyshareMessages;
+use crate::protocol::{Participant, ProtocolError};
+use crate::tecdsa_cli_srv::cli_keygen::{
+    ClientKeygenPrivateStepOutput, ClientKeygenStepOutput, KeygenClient,
+};
+use crate::tecdsa_cli_srv::cli_triples::{TriplesCliStepOutput, TriplesClient};
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::tecdsa_cli_srv::srv_keygen::{
+    KeygenServer, ServerKeygenPrivateStepOutput, ServerKeygenStepOutput,
+};
+use crate::tecdsa_cli_srv::srv_triples::{TriplesServer2, TriplesSrvStepOutput};
+
+fn configs() -> (Vec<Participant>, SessionConfig, SessionConfig) {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    let cli_config =
+        SessionConfig::new(participants.clone(), participants[0], 2, "session").with_key_id("key");
+    let srv_config =
+        SessionConfig::new(participants.clone(), participants[1], 2, "session").with_key_id("key");
+    (participants, cli_config, srv_config)
+}
+
+#[test]
+pub fn test_keygen_step_errors() {
+    let (participants, cli_config, srv_config) = configs();
+
+    let ClientKeygenStepOutput { st_0, msgs_1 } =
+        KeygenClient::cli_keygen_step_1::<Secp256k1>(&cli_config).unwrap();
+    let ServerKeygenStepOutput { st_1, msgs_0 } =
+        KeygenServer::srv_keygen_step_1::<Secp256k1>(&srv_config).unwrap();
+
+    // The last step needs the confirmation computed by step 2.
+    let err = KeygenClient::cli_keygen_step_5(&cli_config, st_0.clone(), &msgs_0).unwrap_err();
+    assert!(matches!(err, ProtocolError::OutOfOrder("confirmation")));
+
+    // Step 2 needs the commitment of the server.
+    let err =
+        KeygenClient::cli_keygen_step_2(&cli_config, st_0.clone(), &RcvdKeyshareMessages::new())
+            .unwrap_err();
+    match &err {
+        ProtocolError::MissingMessage { from, wait } => {
+            assert_eq!(*from, participants[1]);
+            assert_eq!(*wait, "wait_0");
+        }
+        _ => panic!("expected a missing message, got {err:?}"),
+    }
+    assert_eq!(err.culprit(), Some(participants[1]));
+
+    let mut cli_msgs_0 = msgs_0;
+    let mut srv_msgs_1 = msgs_1;
+
+    let ClientKeygenStepOutput { st_0, msgs_1 } =
+        KeygenClient::cli_keygen_step_2(&cli_config, st_0, &cli_msgs_0).unwrap();
+    let ServerKeygenStepOutput { st_1, msgs_0 } =
+        KeygenServer::srv_keygen_step_2(&srv_config, st_1, &srv_msgs_1).unwrap();
+    cli_msgs_0.extend(msgs_0);
+    srv_msgs_1.extend(msgs_1);
+
+    let ClientKeygenStepOutput { st_0, msgs_1 } =
+        KeygenClient::cli_keygen_step_3(&cli_config, st_0, &cli_msgs_0).unwrap();
+    let ServerKeygenStepOutput { st_1, msgs_0 } =
+        KeygenServer::srv_keygen_step_3(&srv_config, st_1, &srv_msgs_1).unwrap();
+    cli_msgs_0.extend(msgs_0);
+    srv_msgs_1.extend(msgs_1);
+
+    let ClientKeygenPrivateStepOutput { st_0, mut msgs_1 } =
+        KeygenClient::cli_keygen_step_4(&cli_config, st_0, &cli_msgs_0).unwrap();
+    let ServerKeygenPrivateStepOutput { st_1, mut msgs_0 } =
+        KeygenServer::srv_keygen_step_4(&srv_config, st_1, &srv_msgs_1).unwrap();
+    cli_msgs_0.extend(msgs_0.remove(&participants[0]).unwrap());
+    srv_msgs_1.extend(msgs_1.remove(&participants[1]).unwrap());
+
+    // The server sends the proof of the client back, which is not a proof for its polynomial.
+    let mut bad_msgs_0 = cli_msgs_0.clone();
+    let cli_proof = srv_msgs_1.wait_2[&participants[0]].2.clone();
+    bad_msgs_0.wait_2.get_mut(&participants[1]).unwrap().2 = cli_proof;
+
+    let err = KeygenClient::cli_keygen_step_5(&cli_config, st_0.clone(), &bad_msgs_0).unwrap_err();
+    assert!(matches!(err, ProtocolError::BadProof(p, _) if p == participants[1]));
+    assert_eq!(err.culprit(), Some(participants[1]));
+
+    let keygen_0 = KeygenClient::cli_keygen_step_5(&cli_config, st_0, &cli_msgs_0).unwrap();
+    let keygen_1 = KeygenServer::srv_keygen_step_5(&srv_config, st_1, &srv_msgs_1).unwrap();
+    assert_eq!(keygen_0.public_key, keygen_1.public_key);
+}
+
+#[test]
+pub fn test_triples_malformed_payload() {
+    let (participants, cli_config, srv_config) = configs();
+
+    let TriplesCliStepOutput { st_0, .. } =
+        TriplesClient::triples_step_1::<Secp256k1>(&cli_config).unwrap();
+    let TriplesSrvStepOutput { mut msgs_0, .. } =
+        TriplesServer2::triples_step_1::<Secp256k1>(&srv_config).unwrap();
+
+    // The server sends one commitment too few.
+    msgs_0.wait_0.get_mut(&participants[1]).unwrap().pop();
+
+    let err = TriplesClient::triples_step_2(&cli_config, st_0, msgs_0).unwrap_err();
+    assert!(matches!(err, ProtocolError::Malformed(_)));
+    assert_eq!(err.culprit(), None);
+}
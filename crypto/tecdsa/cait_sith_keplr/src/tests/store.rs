@@ -0,0 +1,131 @@
+use std::fs;
+use std::path::PathBuf;
+
+use k256::Secp256k1;
+use rand_core::{OsRng, RngCore};
+
+use crate::protocol::Participant;
+use crate::tecdsa::keygen_centralized::keygen_centralized;
+use crate::tecdsa_cli_srv::store::{
+    FileShareStore, Keyring, MasterKey, MemoryShareStore, RecordKind, ShareStore, StoreError,
+};
+use crate::KeygenOutput;
+
+/// A fresh directory, removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        let mut suffix = [0u8; 8];
+        OsRng.fill_bytes(&mut suffix);
+        let name: String = suffix.iter().map(|b| format!("{:02x}", b)).collect();
+        TempDir(std::env::temp_dir().join(format!("cait-sith-store-{}", name)))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn keygen_output() -> KeygenOutput<Secp256k1> {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    keygen_centralized::<Secp256k1>(&participants, 2)
+        .unwrap()
+        .keygen_outputs
+        .remove(0)
+}
+
+#[test]
+pub fn test_memory_store_roundtrip() {
+    let mut store = MemoryShareStore::new(Keyring::new(MasterKey::generate("k1")));
+    let keygen_out = keygen_output();
+
+    store
+        .put_value(RecordKind::KeyShare, "key", &keygen_out)
+        .unwrap();
+    store.put(RecordKind::State, "session", b"st_1").unwrap();
+
+    let loaded: KeygenOutput<Secp256k1> = store
+        .get_value(RecordKind::KeyShare, "key")
+        .unwrap()
+        .unwrap();
+    assert_eq!(loaded.public_key, keygen_out.public_key);
+    assert_eq!(loaded.private_share, keygen_out.private_share);
+
+    // Records of one kind don't show up as another.
+    assert_eq!(store.get(RecordKind::Presign, "key").unwrap(), None);
+    assert_eq!(store.ids(RecordKind::State).unwrap(), vec!["session"]);
+
+    store.rotate(MasterKey::generate("k2")).unwrap();
+    assert_eq!(
+        *store.get(RecordKind::State, "session").unwrap().unwrap(),
+        b"st_1"
+    );
+
+    assert!(store.delete(RecordKind::State, "session").unwrap());
+    assert!(!store.delete(RecordKind::State, "session").unwrap());
+}
+
+#[test]
+pub fn test_file_store_rotation() {
+    let dir = TempDir::new();
+    let k1 = MasterKey::generate("k1");
+    let k2 = MasterKey::generate("k2");
+
+    let mut store = FileShareStore::open(&dir.0, Keyring::new(k1.clone())).unwrap();
+    store.put(RecordKind::Triple, "t0", b"triple 0").unwrap();
+    store.put(RecordKind::Triple, "t1", b"triple 1").unwrap();
+    store.put(RecordKind::Presign, "p0", b"presign").unwrap();
+    assert_eq!(store.ids(RecordKind::Triple).unwrap(), vec!["t0", "t1"]);
+
+    // The data is not stored in the clear.
+    let raw = fs::read(dir.0.join("presign").join("7030")).unwrap();
+    assert!(!raw.windows(7).any(|w| w == b"presign"));
+
+    store.rotate(k2.clone()).unwrap();
+
+    // Only the new master key opens the records now.
+    let store = FileShareStore::open(&dir.0, Keyring::new(k2)).unwrap();
+    assert_eq!(
+        *store.get(RecordKind::Triple, "t1").unwrap().unwrap(),
+        b"triple 1"
+    );
+    let store = FileShareStore::open(&dir.0, Keyring::new(k1)).unwrap();
+    assert_eq!(
+        store.get(RecordKind::Triple, "t1").unwrap_err(),
+        StoreError::UnknownMasterKey("k2".to_string())
+    );
+}
+
+#[test]
+pub fn test_file_store_rejects_moved_records() {
+    let dir = TempDir::new();
+    let key = MasterKey::generate("k1");
+
+    let mut store = FileShareStore::open(&dir.0, Keyring::new(key.clone())).unwrap();
+    store.put(RecordKind::Presign, "a", b"presign a").unwrap();
+
+    // Copy the record of presignature "a" over to "b", and to a triple.
+    let record = dir.0.join("presign").join("61");
+    fs::copy(&record, dir.0.join("presign").join("62")).unwrap();
+    fs::copy(&record, dir.0.join("triple").join("61")).unwrap();
+
+    assert!(matches!(
+        store.get(RecordKind::Presign, "b"),
+        Err(StoreError::Decrypt { .. })
+    ));
+    assert!(matches!(
+        store.get(RecordKind::Triple, "a"),
+        Err(StoreError::Decrypt { .. })
+    ));
+
+    // A master key with the same id, but other bytes, opens nothing either.
+    let store = FileShareStore::open(&dir.0, Keyring::new(MasterKey::generate("k1"))).unwrap();
+    assert!(matches!(
+        store.get(RecordKind::Presign, "a"),
+        Err(StoreError::Decrypt { .. })
+    ));
+    assert!(store.get(RecordKind::Presign, "").is_err());
+}
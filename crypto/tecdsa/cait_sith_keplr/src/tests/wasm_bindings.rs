@@ -0,0 +1,304 @@
+use k256::Secp256k1;
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::JsCast;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::keyshare::RcvdKeyshareMessages;
+use crate::protocol::{run_protocol, Participant, Protocol};
+use crate::serde::{decode, encode};
+use crate::sign::FullSignature;
+use crate::tecdsa::triples_2::TriplesGenManyResult;
+use crate::tecdsa_cli_srv::bindings::{server, ErrorKind};
+use crate::tecdsa_cli_srv::envelope::Envelope;
+use crate::tecdsa_cli_srv::envelope::ProtocolKind::{self, Keygen, Sign};
+use crate::tecdsa_cli_srv::protocols::{presign, triples, RcvdMessages};
+use crate::tecdsa_cli_srv::session::SessionConfig;
+use crate::tecdsa_cli_srv::srv_keygen::KeygenServer;
+use crate::tecdsa_cli_srv::srv_sign::SignServer;
+use crate::wasm::{WasmKeygenClient, WasmPresignClient, WasmSignClient, WasmTriplesClient};
+use crate::{KeygenOutput, PresignOutput};
+
+type Protocols<T> = Vec<(Participant, Box<dyn Protocol<Output = T>>)>;
+
+const CLIENT_CONFIG: &str =
+    r#"{"participants":[0,1],"me":0,"threshold":2,"session_id":"session","key_id":"key"}"#;
+const SERVER_CONFIG: &str =
+    r#"{"participants":[0,1],"me":1,"threshold":2,"session_id":"session","key_id":"key"}"#;
+
+fn configs() -> (SessionConfig, SessionConfig) {
+    let participants = vec![Participant::from(0u32), Participant::from(1u32)];
+    (
+        SessionConfig::new(participants.clone(), participants[0], 2, "session").with_key_id("key"),
+        SessionConfig::new(participants.clone(), participants[1], 2, "session").with_key_id("key"),
+    )
+}
+
+fn sealed<M: Serialize>(kind: ProtocolKind, step: u32, msgs: &M) -> Vec<u8> {
+    Envelope::new::<Secp256k1>(kind, step, "session").seal(msgs)
+}
+
+fn opened<M: DeserializeOwned>(kind: ProtocolKind, step: u32, msgs: &[u8]) -> M {
+    Envelope::new::<Secp256k1>(kind, step, "session")
+        .open(msgs)
+        .unwrap()
+}
+
+/// Run keygen with the client through the bindings, and the server in Rust.
+fn keygen() -> (KeygenOutput<Secp256k1>, KeygenOutput<Secp256k1>) {
+    let (_, srv_config) = configs();
+    let client = Participant::from(0u32);
+    // Unlike the bindings, the Rust API leaves it to the caller to keep received messages.
+    let mut srv_msgs = RcvdKeyshareMessages::new();
+    let mut receive = |step: u32, msgs: &[u8]| {
+        let msgs: RcvdKeyshareMessages<Secp256k1> = opened(Keygen, step, msgs);
+        srv_msgs.merge_from(client, msgs);
+        srv_msgs.clone()
+    };
+
+    let cli_1 = WasmKeygenClient::keygen_step_1(CLIENT_CONFIG).unwrap();
+    let srv_1 = KeygenServer::srv_keygen_step_1::<Secp256k1>(&srv_config).unwrap();
+
+    let cli_2 = WasmKeygenClient::keygen_step_2(
+        CLIENT_CONFIG,
+        &cli_1.state(),
+        &sealed(Keygen, 1, &srv_1.msgs_0),
+    )
+    .unwrap();
+    let srv_2 =
+        KeygenServer::srv_keygen_step_2(&srv_config, srv_1.st_1, &receive(1, &cli_1.msgs()))
+            .unwrap();
+
+    let cli_3 = WasmKeygenClient::keygen_step_3(
+        CLIENT_CONFIG,
+        &cli_2.state(),
+        &sealed(Keygen, 2, &srv_2.msgs_0),
+    )
+    .unwrap();
+    let srv_3 =
+        KeygenServer::srv_keygen_step_3(&srv_config, srv_2.st_1, &receive(2, &cli_2.msgs()))
+            .unwrap();
+
+    let cli_4 = WasmKeygenClient::keygen_step_4(
+        CLIENT_CONFIG,
+        &cli_3.state(),
+        &sealed(Keygen, 3, &srv_3.msgs_0),
+    )
+    .unwrap();
+    let mut srv_4 =
+        KeygenServer::srv_keygen_step_4(&srv_config, srv_3.st_1, &receive(3, &cli_3.msgs()))
+            .unwrap();
+
+    let srv_msgs_4 = srv_4.msgs_0.remove(&client).unwrap();
+    let keygen_0 = WasmKeygenClient::keygen_step_5(
+        CLIENT_CONFIG,
+        &cli_4.state(),
+        &sealed(Keygen, 4, &srv_msgs_4),
+    )
+    .unwrap();
+    let keygen_1 = KeygenServer::srv_keygen_step_5(
+        &srv_config,
+        srv_4.st_1,
+        &receive(4, &cli_4.msgs_for(1).unwrap()),
+    )
+    .unwrap();
+
+    (decode(&keygen_0).unwrap(), keygen_1)
+}
+
+fn presignatures(
+    keygen_0: KeygenOutput<Secp256k1>,
+    keygen_1: KeygenOutput<Secp256k1>,
+) -> (PresignOutput<Secp256k1>, PresignOutput<Secp256k1>) {
+    let (cli_config, srv_config) = configs();
+
+    let mut protocols: Protocols<TriplesGenManyResult<Secp256k1>> = vec![];
+    for config in [cli_config.clone(), srv_config.clone()] {
+        protocols.push((config.me, Box::new(triples(config).unwrap())));
+    }
+    let mut triples_outs = run_protocol(protocols).unwrap();
+    triples_outs.sort_by_key(|(p, _)| *p);
+
+    let mut protocols: Protocols<PresignOutput<Secp256k1>> = vec![];
+    for ((config, keygen_out), (_, triples_out)) in [(cli_config, keygen_0), (srv_config, keygen_1)]
+        .into_iter()
+        .zip(triples_outs)
+    {
+        let protocol = presign(
+            config.clone(),
+            triples_out.pub_v[0].clone(),
+            triples_out.pub_v[1].clone(),
+            triples_out.share_v[0].clone(),
+            triples_out.share_v[1].clone(),
+            keygen_out,
+        )
+        .unwrap();
+        protocols.push((config.me, Box::new(protocol)));
+    }
+    let mut presign_outs = run_protocol(protocols).unwrap();
+    presign_outs.sort_by_key(|(p, _)| *p);
+    let presig_1 = presign_outs.pop().unwrap().1;
+    let presig_0 = presign_outs.pop().unwrap().1;
+    (presig_0, presig_1)
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn test_wasm_keygen_and_sign() {
+    let (_, srv_config) = configs();
+    let (keygen_0, keygen_1) = keygen();
+    assert_eq!(keygen_0.public_key, keygen_1.public_key);
+    let public_key = keygen_0.public_key;
+
+    let (presig_0, presig_1) = presignatures(keygen_0, keygen_1);
+    let msg_hash = crate::payload::eth::personal_message_hash(b"hello");
+    let msg_scalar = crate::payload::hash_to_scalar(&msg_hash);
+
+    let cli_1 = WasmSignClient::sign_step_1(CLIENT_CONFIG, &msg_hash, &encode(&presig_0)).unwrap();
+    let mut srv_1 = SignServer::sign_step_1(&srv_config, msg_scalar, presig_1.clone()).unwrap();
+
+    let sig = WasmSignClient::sign_step_2(
+        CLIENT_CONFIG,
+        &cli_1.state(),
+        &sealed(Sign, 1, &srv_1.msgs_0),
+        &encode(&presig_0),
+    )
+    .unwrap();
+    let srv_msgs = opened(Sign, 1, &cli_1.msgs());
+    let srv_sig =
+        SignServer::sign_step_2(&srv_config, &mut srv_1.st_1, &srv_msgs, presig_1).unwrap();
+
+    assert_eq!(sig.compact(), srv_sig.sig.to_compact());
+    let parsed =
+        FullSignature::<Secp256k1>::from_recoverable_bytes(&sig.recoverable().unwrap()).unwrap();
+    assert!(parsed.verify(&public_key, &msg_scalar));
+    assert_eq!(sig.recovery_id(), srv_sig.recoverable().recovery_id);
+    assert!(FullSignature::<Secp256k1>::from_der(&sig.der().unwrap())
+        .unwrap()
+        .verify(&public_key, &msg_scalar));
+}
+
+/// Run every flow with the client and the server bindings, passing each the bytes the other returns.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn test_wasm_client_against_server_bindings() {
+    let cli_1 = WasmKeygenClient::keygen_step_1(CLIENT_CONFIG).unwrap();
+    let srv_1 = server::keygen_step_1(SERVER_CONFIG).unwrap();
+    let cli_2 =
+        WasmKeygenClient::keygen_step_2(CLIENT_CONFIG, &cli_1.state(), &srv_1.msgs).unwrap();
+    let srv_2 = server::keygen_step_2(SERVER_CONFIG, &srv_1.state, &cli_1.msgs()).unwrap();
+    let cli_3 =
+        WasmKeygenClient::keygen_step_3(CLIENT_CONFIG, &cli_2.state(), &srv_2.msgs).unwrap();
+    let srv_3 = server::keygen_step_3(SERVER_CONFIG, &srv_2.state, &cli_2.msgs()).unwrap();
+    let cli_4 =
+        WasmKeygenClient::keygen_step_4(CLIENT_CONFIG, &cli_3.state(), &srv_3.msgs).unwrap();
+    let srv_4 = server::keygen_step_4(SERVER_CONFIG, &srv_3.state, &cli_3.msgs()).unwrap();
+    let keygen_0 = WasmKeygenClient::keygen_step_5(
+        CLIENT_CONFIG,
+        &cli_4.state(),
+        &srv_4.msgs[&Participant::from(0u32)],
+    )
+    .unwrap();
+    let keygen_1 =
+        server::keygen_step_5(SERVER_CONFIG, &srv_4.state, &cli_4.msgs_for(1).unwrap()).unwrap();
+
+    // The server runs behind the client, taking the messages of the client step of the same number.
+    let cli = WasmTriplesClient::triples_step_1(CLIENT_CONFIG).unwrap();
+    let mut srv = server::triples_step_1(SERVER_CONFIG, &cli.msgs()).unwrap();
+    let mut cli = (cli.state(), cli.msgs());
+    let cli_steps = [
+        WasmTriplesClient::triples_step_2,
+        WasmTriplesClient::triples_step_3,
+        WasmTriplesClient::triples_step_4,
+        WasmTriplesClient::triples_step_5,
+        WasmTriplesClient::triples_step_6,
+        WasmTriplesClient::triples_step_7,
+        WasmTriplesClient::triples_step_8,
+        WasmTriplesClient::triples_step_9,
+        WasmTriplesClient::triples_step_10,
+    ];
+    let srv_steps = [
+        server::triples_step_2,
+        server::triples_step_3,
+        server::triples_step_4,
+        server::triples_step_5,
+        server::triples_step_6,
+        server::triples_step_7,
+        server::triples_step_8,
+        server::triples_step_9,
+        server::triples_step_10,
+    ];
+    for (cli_step, srv_step) in cli_steps.into_iter().zip(srv_steps) {
+        let out = cli_step(CLIENT_CONFIG, &cli.0, &srv.msgs).unwrap();
+        cli = (out.state(), out.msgs());
+        srv = srv_step(SERVER_CONFIG, &srv.state, &cli.1).unwrap();
+    }
+    let triples_0 = WasmTriplesClient::triples_step_11(CLIENT_CONFIG, &cli.0, &srv.msgs).unwrap();
+    let triples_1 = server::triples_step_11(SERVER_CONFIG, &srv.state, &cli.1).unwrap();
+
+    let cli_1 = WasmPresignClient::presign_step_1(CLIENT_CONFIG, &triples_0, &keygen_0).unwrap();
+    let srv_1 = server::presign_step_1(SERVER_CONFIG, &triples_1, &keygen_1).unwrap();
+    let cli_2 =
+        WasmPresignClient::presign_step_2(CLIENT_CONFIG, &cli_1.state(), &srv_1.msgs).unwrap();
+    let srv_2 = server::presign_step_2(SERVER_CONFIG, &srv_1.state, &cli_1.msgs()).unwrap();
+    let presig_0 =
+        WasmPresignClient::presign_step_3(CLIENT_CONFIG, &cli_2.state(), &srv_2.msgs).unwrap();
+    let presig_1 = server::presign_step_3(SERVER_CONFIG, &srv_2.state, &cli_2.msgs()).unwrap();
+
+    let msg_hash = crate::payload::eth::personal_message_hash(b"hello");
+    let cli_1 = WasmSignClient::sign_step_1(CLIENT_CONFIG, &msg_hash, &presig_0).unwrap();
+    let srv_1 = server::sign_step_1(SERVER_CONFIG, &msg_hash, &presig_1).unwrap();
+    let sig =
+        WasmSignClient::sign_step_2(CLIENT_CONFIG, &cli_1.state(), &srv_1.msgs, &presig_0).unwrap();
+    let srv_sig =
+        server::sign_step_2(SERVER_CONFIG, &srv_1.state, &cli_1.msgs(), &presig_1).unwrap();
+
+    let keygen_0: KeygenOutput<Secp256k1> = decode(&keygen_0).unwrap();
+    assert_eq!(sig.compact(), srv_sig.sig.to_compact());
+    assert!(srv_sig.sig.verify(
+        &keygen_0.public_key,
+        &crate::payload::hash_to_scalar(&msg_hash)
+    ));
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn test_step_bindings_require_two_parties() {
+    let config =
+        r#"{"participants":[0,1,2],"me":1,"threshold":2,"session_id":"session","key_id":"key"}"#;
+    let err = server::keygen_step_1(config).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::Config);
+}
+
+/// Errors are only JS values on wasm.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+fn test_wasm_errors() {
+    let name = |err: wasm_bindgen::JsValue| -> String {
+        err.dyn_into::<js_sys::Error>().unwrap().name().into()
+    };
+
+    let err = WasmKeygenClient::keygen_step_1("{").err().unwrap();
+    assert_eq!(name(err), "ConfigError");
+
+    let cli_1 = WasmKeygenClient::keygen_step_1(CLIENT_CONFIG).unwrap();
+    let err = WasmKeygenClient::keygen_step_2(CLIENT_CONFIG, &cli_1.state(), &[0xc1])
+        .err()
+        .unwrap();
+    assert_eq!(name(err), "DecodeError");
+
+    let err = WasmKeygenClient::keygen_step_1(
+        r#"{"participants":[0],"me":0,"threshold":2,"session_id":"session"}"#,
+    )
+    .err()
+    .unwrap();
+    assert_eq!(name(err), "ConfigError");
+
+    let err = WasmKeygenClient::keygen_step_1(
+        r#"{"participants":[0,1,2],"me":0,"threshold":2,"session_id":"session"}"#,
+    )
+    .err()
+    .unwrap();
+    assert_eq!(name(err), "ConfigError");
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use elliptic_curve::{Field, ScalarPrimitive};
+use k256::{ProjectivePoint, Scalar, Secp256k1};
+use rand_core::OsRng;
+use zeroize::Zeroize;
+
+use crate::keyshare::RcvdKeyshareMessages;
+use crate::math::Polynomial;
+use crate::presign::{steps_2, PresignState2};
+use crate::protocol::Participant;
+use crate::tecdsa::keygen_centralized::keygen_centralized;
+use crate::triples::{deal, TriplePub, TripleShare};
+use crate::{KeygenOutput, PresignOutput};
+
+fn participants() -> Vec<Participant> {
+    vec![Participant::from(0u32), Participant::from(1u32)]
+}
+
+fn keygen_output() -> KeygenOutput<Secp256k1> {
+    keygen_centralized::<Secp256k1>(&participants(), 2)
+        .unwrap()
+        .keygen_outputs
+        .remove(0)
+}
+
+fn triple() -> (TriplePub<Secp256k1>, TripleShare<Secp256k1>) {
+    let (triple_pub, mut shares) = deal::<Secp256k1>(&mut OsRng, &participants(), 2);
+    (triple_pub, shares.remove(0))
+}
+
+fn presign_state() -> PresignState2<Secp256k1> {
+    let (pub_0, share_0) = triple();
+    let (pub_1, share_1) = triple();
+    PresignState2::new(
+        participants(),
+        2,
+        b"session".to_vec(),
+        keygen_output(),
+        pub_0,
+        pub_1,
+        share_0,
+        share_1,
+    )
+}
+
+fn rcvd_keyshare_messages() -> RcvdKeyshareMessages<Secp256k1> {
+    let mut msgs = RcvdKeyshareMessages::new();
+    msgs.wait_3.insert(
+        participants()[1],
+        ScalarPrimitive::from(Scalar::random(&mut OsRng)),
+    );
+    msgs
+}
+
+/// Whether `debug` shows `secret` in any of the ways a scalar formats.
+fn shows(debug: &str, secret: &Scalar) -> bool {
+    debug.contains(&format!("{:?}", secret))
+}
+
+#[test]
+pub fn test_debug_redacts_secrets() {
+    let keygen_out = keygen_output();
+    let debug = format!("{:?}", keygen_out);
+    assert!(!shows(&debug, &keygen_out.private_share));
+    assert!(debug.contains("<redacted>"));
+
+    let (_, share) = triple();
+    let debug = format!("{:?}", share);
+    for secret in [&share.a, &share.b, &share.c] {
+        assert!(!shows(&debug, secret));
+    }
+
+    let f = Polynomial::<Secp256k1>::random(&mut OsRng, 2);
+    let debug = format!("{:?}", f);
+    assert!(!shows(&debug, &f[0]) && !shows(&debug, &f[1]));
+
+    let presig = PresignOutput::<Secp256k1> {
+        big_r: ProjectivePoint::GENERATOR.into(),
+        k: Scalar::random(&mut OsRng),
+        sigma: Scalar::random(&mut OsRng),
+        commitments: HashMap::new(),
+    };
+    let debug = format!("{:?}", presig);
+    assert!(!shows(&debug, &presig.k));
+    assert!(!shows(&debug, &presig.sigma));
+
+    let mut state = presign_state();
+    steps_2::step_1(&mut state, participants()[0], participants()[0]).unwrap();
+    let debug = format!("{:?}", state);
+    assert!(!shows(&debug, &state.keygen_out.private_share));
+    assert!(!shows(&debug, &state.share_0_0.a));
+    assert!(!shows(&debug, &state.k_prime_i.unwrap()));
+
+    let msgs = rcvd_keyshare_messages();
+    let debug = format!("{:?}", msgs);
+    assert!(!debug.contains(&format!("{:?}", msgs.wait_3[&participants()[1]])));
+}
+
+#[test]
+pub fn test_zeroize_wipes_secrets() {
+    let mut keygen_out = keygen_output();
+    let public_key = keygen_out.public_key;
+    keygen_out.zeroize();
+    assert_eq!(keygen_out.private_share, Scalar::ZERO);
+    // Only the secrets are wiped.
+    assert_eq!(keygen_out.public_key, public_key);
+
+    let (_, mut share) = triple();
+    share.zeroize();
+    assert_eq!([share.a, share.b, share.c], [Scalar::ZERO; 3]);
+
+    let mut state = presign_state();
+    steps_2::step_1(&mut state, participants()[0], participants()[0]).unwrap();
+    state.zeroize();
+    assert_eq!(state.keygen_out.private_share, Scalar::ZERO);
+    assert_eq!(state.share_1_0.c, Scalar::ZERO);
+    assert!(state.k_i.is_none() && state.k_prime_i.is_none());
+    assert!(state.share_commitments.is_some());
+
+    let mut msgs = rcvd_keyshare_messages();
+    msgs.zeroize();
+    assert!(msgs.wait_3.is_empty());
+}
+
+#[test]
+pub fn test_steps_wipe_consumed_secrets() {
+    let mut state = presign_state();
+    let me = participants()[0];
+
+    steps_2::step_1(&mut state, me, me).unwrap();
+    assert!(state.k_prime_i.is_some());
+
+    steps_2::step_2(&mut state).unwrap();
+    assert!(state.ka_i.is_some() && state.xb_i.is_some());
+    assert!(state.k_prime_i.is_none());
+    assert!(state.a_prime_i.is_none());
+    assert!(state.b_prime_i.is_none());
+    assert!(state.x_prime_i.is_none());
+}
@@ -3,6 +3,7 @@ use ck_meow::Meow;
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroize;
 
 use crate::constants::SECURITY_PARAMETER;
 
@@ -18,6 +19,12 @@ pub const SEC_PARAM_8_2: usize = ((SECURITY_PARAMETER + 8 - 1) / 8) * 2;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BitVector([u64; SEC_PARAM_64]);
 
+impl Zeroize for BitVector {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl BitVector {
     pub fn zero() -> Self {
         Self([0u64; SEC_PARAM_64])
@@ -272,6 +279,12 @@ const PRG_CTX: &[u8] = b"cait-sith v0.8.0 correlated OT PRG";
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub struct BitMatrix(Vec<BitVector>);
 
+impl Zeroize for BitMatrix {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl BitMatrix {
     /// Create a random matrix of a certain chunk size.
     ///
@@ -349,6 +362,12 @@ pub struct SquareBitMatrix {
     pub matrix: BitMatrix,
 }
 
+impl Zeroize for SquareBitMatrix {
+    fn zeroize(&mut self) {
+        self.matrix.zeroize();
+    }
+}
+
 impl TryFrom<BitMatrix> for SquareBitMatrix {
     type Error = ();
 
@@ -402,6 +421,12 @@ impl SquareBitMatrix {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChoiceVector(Vec<BitVector>);
 
+impl Zeroize for ChoiceVector {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl ChoiceVector {
     /// Generate a random vector with a certain number of bits.
     pub fn random(rng: &mut impl CryptoRngCore, size: usize) -> Self {
@@ -25,9 +25,12 @@
 //! This protocol requires a setup protocol to be one once beforehand.
 //! After this setup protocol has been run, an arbitarary number of triples can
 //! be generated.
+use std::fmt;
+
 use ::serde::{Deserialize, Serialize};
 use elliptic_curve::{Field, Group};
 use rand_core::CryptoRngCore;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::{compat::CSCurve, math::Polynomial, protocol::Participant, serde};
 
@@ -52,13 +55,37 @@ pub struct TriplePub<C: CSCurve> {
 /// This consists of shares of each individual part.
 ///
 /// i.e. we have a share of a, b, and c such that a * b = c.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+///
+/// The shares are wiped when dropped, and never show up in `Debug` output.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TripleShare<C: CSCurve> {
     pub a: C::Scalar,
     pub b: C::Scalar,
     pub c: C::Scalar,
 }
 
+impl<C: CSCurve> fmt::Debug for TripleShare<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TripleShare").finish_non_exhaustive()
+    }
+}
+
+impl<C: CSCurve> Zeroize for TripleShare<C> {
+    fn zeroize(&mut self) {
+        self.a.zeroize();
+        self.b.zeroize();
+        self.c.zeroize();
+    }
+}
+
+impl<C: CSCurve> Drop for TripleShare<C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<C: CSCurve> ZeroizeOnDrop for TripleShare<C> {}
+
 /// Create a new triple from scratch.
 ///
 /// This can be used to generate a triple if you then trust the person running
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use elliptic_curve::Scalar;
 use elliptic_curve::{Field, Group, ScalarPrimitive};
 use magikitten::MeowRng;
@@ -11,6 +13,7 @@ use super::bits::{BitMatrix, BitVector, ChoiceVector, DoubleBitVector, SquareBit
 use super::random_ot_extension_2::hash_to_scalar;
 use super::random_ot_extension_2::RandomOtExtensionParams;
 use super::state::TriplesState;
+use super::steps::{check_computed, check_payload_len, message_from};
 use super::RcvdTriplesMessages;
 use super::{MTAWait0Payload, MTAWait1Payload};
 use crate::compat::{CSCurve, SerializablePoint};
@@ -25,6 +28,9 @@ pub fn step_1<C: CSCurve>(
     _msgs: &RcvdTriplesMessages<C>,
     me: Participant,
 ) -> Result<(), ProtocolError> {
+    check_computed(&st.e_v_coefficients, st.triples_count, "e_v_coefficients")?;
+    check_computed(&st.f_v_coefficients, st.triples_count, "f_v_coefficients")?;
+
     let mut e_v = vec![];
     let mut f_v = vec![];
 
@@ -50,13 +56,7 @@ pub fn step_1<C: CSCurve>(
     st.e0_v = e0_v;
     st.f0_v = f0_v;
 
-    let participant_list = ParticipantList::new(&st.participants).ok_or_else(|| {
-        ProtocolError::Other(
-            "participant list cannot contain duplicates"
-                .to_string()
-                .into(),
-        )
-    })?;
+    let participant_list = ParticipantList::from_state(&st.participants)?;
 
     for p in participant_list.others(me) {
         if p < me {
@@ -108,6 +108,34 @@ pub fn multiplication_receiver_many_step_2<C: CSCurve>(
     msgs: &RcvdTriplesMessages<C>,
     me: Participant,
 ) -> Result<Vec<BitMatrix>, ProtocolError> {
+    check_computed(&st.yv, st.triples_count, "yv")?;
+    check_computed(&st.big_y_affine_v, st.triples_count, "big_y_affine_v")?;
+    check_computed(&st.big_z_v, st.triples_count, "big_z_v")?;
+    check_computed(&st.e0_v, st.triples_count, "e0_v")?;
+    check_computed(&st.f0_v, st.triples_count, "f0_v")?;
+    check_computed(&st.my_confirmations, st.triples_count, "my_confirmations")?;
+
+    let (p, big_x_affine_v_v) = other_message(
+        st,
+        me,
+        &msgs.batch_random_ot_wait_0,
+        "batch_random_ot_wait_0",
+    )?;
+    if big_x_affine_v_v.len() != SECURITY_PARAMETER {
+        return Err(ProtocolError::Malformed(format!(
+            "batch random OT points from {p:?} have {} rows, expected {SECURITY_PARAMETER}",
+            big_x_affine_v_v.len()
+        )));
+    }
+    for big_x_i_affine_v in big_x_affine_v_v {
+        check_payload_len(
+            p,
+            "batch random OT points",
+            big_x_i_affine_v,
+            st.triples_count,
+        )?;
+    }
+
     let dkv = {
         // batch random sender
         let y_v_arc = st.yv.clone();
@@ -121,18 +149,7 @@ pub fn multiplication_receiver_many_step_2<C: CSCurve>(
             let big_y_affine_v_arc = big_y_affine_v_arc.clone();
             let big_z_v_arc = big_z_v_arc.clone();
 
-            // loop single time
-            let mut big_x_i_affine_v = vec![];
-            for (_, big_x_i_affine_v_v) in &msgs.batch_random_ot_wait_0 {
-                let v = big_x_i_affine_v_v
-                    .get(i)
-                    .ok_or(ProtocolError::Other(
-                        format!("batch_random_ot_wait_0 missing").into(),
-                    ))?
-                    .clone();
-                big_x_i_affine_v = v;
-                break;
-            }
+            let big_x_i_affine_v = &big_x_affine_v_v[i];
 
             let mut ret = vec![];
             for j in 0..st.triples_count {
@@ -163,7 +180,12 @@ pub fn multiplication_receiver_many_step_2<C: CSCurve>(
             let out = &outs[i];
             let big_k0: BitMatrix = out.iter().map(|r| r.0).collect();
             let big_k1: BitMatrix = out.iter().map(|r| r.1).collect();
-            ret.push((big_k0.try_into().unwrap(), big_k1.try_into().unwrap()));
+            let not_square =
+                |_| ProtocolError::AssertionFailed("batch random OT output is not square".into());
+            ret.push((
+                big_k0.try_into().map_err(not_square)?,
+                big_k1.try_into().map_err(not_square)?,
+            ));
         }
 
         ret
@@ -206,7 +228,13 @@ pub fn multiplication_receiver_many_step_2<C: CSCurve>(
             .collect();
 
         // correlated ot receiver
-        assert_eq!(x.height(), correlated_ot_sender_params.batch_size);
+        if x.height() != correlated_ot_sender_params.batch_size {
+            return Err(ProtocolError::AssertionFailed(format!(
+                "expected matrix of height {} found {}",
+                correlated_ot_sender_params.batch_size,
+                x.height()
+            )));
+        }
 
         // Spec 1
         let t0 = k0.expand_transpose(params.sid, correlated_ot_sender_params.batch_size);
@@ -229,8 +257,11 @@ pub fn multiplication_receiver_many_step_2<C: CSCurve>(
 pub fn multiplication_receiver_many_step_3<C: CSCurve>(
     st: &mut TriplesState<C>,
     msgs: &RcvdTriplesMessages<C>,
-    _me: Participant,
+    me: Participant,
 ) -> Result<Vec<(DoubleBitVector, Vec<DoubleBitVector>)>, ProtocolError> {
+    check_computed(&st.b_v, st.triples_count, "b_v")?;
+    check_computed(&st.t_v, st.triples_count, "t_v")?;
+
     let batch_size = C::BITS + SECURITY_PARAMETER;
     let adjusted_size = super::random_ot_extension_2::adjust_size(batch_size * 2);
 
@@ -241,12 +272,13 @@ pub fn multiplication_receiver_many_step_3<C: CSCurve>(
 
     // Step 5
     // let seed: [u8; 32] = chan.recv(wait0).await?;
-    let mut seed_v = vec![];
-
-    for (p, seed_v_) in &msgs.random_ot_extension_wait_0 {
-        seed_v = seed_v_.clone();
-        break;
-    }
+    let (p, seed_v) = other_message(
+        st,
+        me,
+        &msgs.random_ot_extension_wait_0,
+        "random_ot_extension_wait_0",
+    )?;
+    check_payload_len(p, "random OT extension seeds", seed_v, st.triples_count)?;
 
     let mu = adjusted_size / SECURITY_PARAMETER;
 
@@ -317,23 +349,17 @@ pub fn multiplication_receiver_many_step_4<C: CSCurve>(
     msgs: &RcvdTriplesMessages<C>,
     me: Participant,
 ) -> Result<MTAWait1Payload<C>, ProtocolError> {
-    let participant_list = ParticipantList::new(&st.participants).ok_or_else(|| {
-        ProtocolError::Other(
-            "participant list cannot contain duplicates"
-                .to_string()
-                .into(),
-        )
-    })?;
+    let participant_list = ParticipantList::from_state(&st.participants)?;
 
-    let mut c1_v = vec![];
-    let mut c2_v = vec![];
+    check_computed(&st.receiver_res0_v, st.triples_count, "receiver_res0_v")?;
+    check_computed(&st.receiver_res1_v, st.triples_count, "receiver_res1_v")?;
+    check_computed(&st.e0_v, st.triples_count, "e0_v")?;
+    check_computed(&st.f0_v, st.triples_count, "f0_v")?;
 
-    for (p, payload) in &msgs.mta_wait_0 {
-        let MTAWait0Payload { c1_v: c1, c2_v: c2 } = payload;
-        c1_v = c1.clone();
-        c2_v = c2.clone();
-        break;
-    }
+    let (p, MTAWait0Payload { c1_v, c2_v }) =
+        other_message(st, me, &msgs.mta_wait_0, "mta_wait_0")?;
+    check_payload_len(p, "c1_v", c1_v, st.triples_count)?;
+    check_payload_len(p, "c2_v", c2_v, st.triples_count)?;
 
     let mut chi1_seed_1_v = vec![];
     let mut chi1_seed_2_v = vec![];
@@ -386,27 +412,27 @@ pub fn multiplication_receiver_many_step_4<C: CSCurve>(
 pub fn multiplication_sender_many_step_1<C: CSCurve>(
     st: &mut TriplesState<C>,
     msgs: &RcvdTriplesMessages<C>,
-    _me: Participant,
+    me: Participant,
 ) -> Result<Vec<Vec<SerializablePoint<C>>>, ProtocolError> {
     // batch random receiver
-    let mut big_y_affine_v = vec![];
-
-    for (_p, v_v) in &msgs.batch_random_ot_wait_0 {
-        if v_v.len() < 1 {
-            return Err(ProtocolError::Other(
-                format!("big_y_affine_v_v is empty").into(),
-            ));
-        }
-
-        big_y_affine_v = v_v[0].clone();
-        break;
-    }
-
-    if big_y_affine_v.len() < 1 {
-        return Err(ProtocolError::Other(
-            format!("big_y_affine_v missing").into(),
-        ));
-    }
+    let (p, v_v) = other_message(
+        st,
+        me,
+        &msgs.batch_random_ot_wait_0,
+        "batch_random_ot_wait_0",
+    )?;
+    let [big_y_affine_v] = v_v.as_slice() else {
+        return Err(ProtocolError::Malformed(format!(
+            "batch random OT points from {p:?} have {} rows, expected 1",
+            v_v.len()
+        )));
+    };
+    check_payload_len(
+        p,
+        "batch random OT points",
+        big_y_affine_v,
+        st.triples_count,
+    )?;
 
     let mut big_y_v = vec![];
     let mut delta_v = vec![];
@@ -426,11 +452,11 @@ pub fn multiplication_sender_many_step_1<C: CSCurve>(
     }
 
     let big_y_v_arc = big_y_v;
-    let big_y_affine_v_arc = big_y_affine_v;
+    let big_y_affine_v_arc = big_y_affine_v.clone();
 
     // inner is batch, outer is bits
     let mut choices: Vec<Vec<_>> = Vec::new();
-    for _ in delta_v[0].bits() {
+    for _ in 0..SECURITY_PARAMETER {
         choices.push(Vec::new());
     }
     for j in 0..st.triples_count {
@@ -501,8 +527,10 @@ pub fn multiplication_sender_many_step_1<C: CSCurve>(
         let delta = delta_v[j];
         let out = &outs[j];
         let big_k: BitMatrix = out.iter().cloned().collect();
-        let h = SquareBitMatrix::try_from(big_k);
-        ret.push((delta, h.unwrap()))
+        let h = SquareBitMatrix::try_from(big_k).map_err(|_| {
+            ProtocolError::AssertionFailed("batch random OT output is not square".into())
+        })?;
+        ret.push((delta, h))
     }
 
     st.dkv = ret;
@@ -513,16 +541,18 @@ pub fn multiplication_sender_many_step_1<C: CSCurve>(
 pub fn multiplication_sender_many_step_2<C: CSCurve>(
     st: &mut TriplesState<C>,
     msgs: &RcvdTriplesMessages<C>,
-    _me: Participant,
+    me: Participant,
 ) -> Result<Vec<[u8; 32]>, ProtocolError> {
+    check_computed(&st.dkv, st.triples_count, "dkv")?;
+    check_computed(&st.my_confirmations, st.triples_count, "my_confirmations")?;
+    check_computed(&st.e0_v, st.triples_count, "e0_v")?;
+    check_computed(&st.f0_v, st.triples_count, "f0_v")?;
+
     let dkv = &st.dkv;
     let sid = &st.my_confirmations;
 
-    let mut u_v = vec![];
-    for (_p, u_v_) in &msgs.correlated_ot_wait_0 {
-        u_v = u_v_.clone();
-        break;
-    }
+    let (p, u_v) = other_message(st, me, &msgs.correlated_ot_wait_0, "correlated_ot_wait_0")?;
+    check_payload_len(p, "correlated OT matrices", u_v, st.triples_count)?;
 
     let mut q_v = vec![];
     let mut seed_v = vec![];
@@ -592,8 +622,12 @@ pub fn multiplication_sender_many_step_2<C: CSCurve>(
 pub fn multiplication_sender_many_step_3<C: CSCurve>(
     st: &mut TriplesState<C>,
     msgs: &RcvdTriplesMessages<C>,
-    _me: Participant,
+    me: Participant,
 ) -> Result<(), ProtocolError> {
+    check_computed(&st.seed_v, st.triples_count, "seed_v")?;
+    check_computed(&st.dkv, st.triples_count, "dkv")?;
+    check_computed(&st.q_v, st.triples_count, "q_v")?;
+
     let batch_size = C::BITS + SECURITY_PARAMETER;
     let random_ot_extension_params = RandomOtExtensionParams {
         sid: &[],
@@ -604,11 +638,18 @@ pub fn multiplication_sender_many_step_3<C: CSCurve>(
 
     let mu = adjusted_size / SECURITY_PARAMETER;
 
-    let mut small_x_t_v = vec![];
-    for (_, small_x_t_v_) in &msgs.random_ot_extension_wait_1 {
-        small_x_t_v = small_x_t_v_.clone();
-        break;
-    }
+    let (p, small_x_t_v) = other_message(
+        st,
+        me,
+        &msgs.random_ot_extension_wait_1,
+        "random_ot_extension_wait_1",
+    )?;
+    check_payload_len(
+        p,
+        "random OT extension checks",
+        small_x_t_v,
+        st.triples_count,
+    )?;
 
     let mut sender_res0_v = vec![];
     let mut sender_res1_v = vec![];
@@ -681,6 +722,11 @@ pub fn multiplication_sender_many_step_4<C: CSCurve>(
     _msgs: &RcvdTriplesMessages<C>,
     _me: Participant,
 ) -> Result<MTAWait0Payload<C>, ProtocolError> {
+    check_computed(&st.e0_v, st.triples_count, "e0_v")?;
+    check_computed(&st.f0_v, st.triples_count, "f0_v")?;
+    check_computed(&st.sender_res0_v, st.triples_count, "sender_res0_v")?;
+    check_computed(&st.sender_res1_v, st.triples_count, "sender_res1_v")?;
+
     let mut c1_v = vec![];
     let mut c2_v = vec![];
 
@@ -715,22 +761,22 @@ pub fn multiplication_sender_many_step_5<C: CSCurve>(
     msgs: &RcvdTriplesMessages<C>,
     me: Participant,
 ) -> Result<(), ProtocolError> {
-    let participant_list = ParticipantList::new(&st.participants).ok_or_else(|| {
-        ProtocolError::Other(
-            "participant list cannot contain duplicates"
-                .to_string()
-                .into(),
-        )
-    })?;
-
-    let mut chi_seed_1_v = vec![];
-    let mut chi_seed_2_v = vec![];
-
-    for (_, payload) in &msgs.mta_wait_1 {
-        chi_seed_1_v = payload.chi1_seed_1_v.clone();
-        chi_seed_2_v = payload.chi1_seed_2_v.clone();
-        break;
-    }
+    let participant_list = ParticipantList::from_state(&st.participants)?;
+
+    check_computed(&st.delta_1_v, st.triples_count, "delta_1_v")?;
+    check_computed(&st.delta_2_v, st.triples_count, "delta_2_v")?;
+    check_computed(&st.e0_v, st.triples_count, "e0_v")?;
+    check_computed(&st.f0_v, st.triples_count, "f0_v")?;
+
+    let (
+        p,
+        MTAWait1Payload {
+            chi1_seed_1_v: chi_seed_1_v,
+            chi1_seed_2_v: chi_seed_2_v,
+        },
+    ) = other_message(st, me, &msgs.mta_wait_1, "mta_wait_1")?;
+    check_payload_len(p, "chi1_seed_1_v", chi_seed_1_v, st.triples_count)?;
+    check_payload_len(p, "chi1_seed_2_v", chi_seed_2_v, st.triples_count)?;
 
     let mut gamma_sum_v = vec![];
     for i in 0..st.triples_count {
@@ -795,10 +841,13 @@ fn mta_sender_step_2<C: CSCurve>(
     chi1: Scalar<C>,
     seed: [u8; 32],
 ) -> Result<Scalar<C>, ProtocolError> {
-    let mut alpha = delta[0] * C::Scalar::from(chi1);
+    let (delta_0, delta_rest) = delta
+        .split_first()
+        .ok_or_else(|| ProtocolError::AssertionFailed("delta is empty".to_owned()))?;
+    let mut alpha = *delta_0 * C::Scalar::from(chi1);
 
     let mut prng = MeowRng::new(&seed);
-    for &delta_i in &delta[1..] {
+    for &delta_i in delta_rest {
         let chi_i = C::Scalar::random(&mut prng);
         alpha += delta_i * chi_i;
     }
@@ -821,6 +870,9 @@ fn mta_receiver_step_1<C: CSCurve>(
         ))
         .into());
     }
+    if tv.is_empty() {
+        return Err(ProtocolError::AssertionFailed("tv is empty".to_owned()));
+    }
     let mut m = tv.iter().zip(c.iter()).map(|((t_i, v_i), (c0_i, c1_i))| {
         C::Scalar::conditional_select(&(*c0_i).into(), &(*c1_i).into(), *t_i) - v_i
     });
@@ -839,10 +891,27 @@ fn mta_receiver_step_1<C: CSCurve>(
     chi1.conditional_assign(&(-chi1), tv[0].0);
 
     // Step 5
-    let mut beta = chi1 * m.next().unwrap();
+    let mut beta = chi1 * m.next().unwrap_or(C::Scalar::ZERO);
     for (&chi_i, m_i) in chi.iter().zip(m) {
         beta += chi_i * m_i;
     }
 
     return Ok(((chi1, seed), beta));
 }
+
+/// The message at the `wait` point from the other participant, since the
+/// multiplication is run between two.
+fn other_message<'a, C: CSCurve, T>(
+    st: &TriplesState<C>,
+    me: Participant,
+    msgs: &'a HashMap<Participant, T>,
+    wait: &'static str,
+) -> Result<(Participant, &'a T), ProtocolError> {
+    let p = st
+        .participants
+        .iter()
+        .copied()
+        .find(|p| *p != me)
+        .ok_or_else(|| ProtocolError::Malformed("no participant to multiply with".to_string()))?;
+    Ok((p, message_from(msgs, p, wait)?))
+}
@@ -11,7 +11,7 @@ const MEOW_CTX: &[u8] = b"Random OT Extension Hash";
 
 pub fn hash_to_scalar<C: CSCurve>(i: usize, v: &BitVector) -> C::Scalar {
     let mut meow = Meow::new(MEOW_CTX);
-    let i64 = u64::try_from(i).expect("failed to convert usize to u64");
+    let i64 = i as u64;
     meow.meta_ad(&i64.to_le_bytes(), false);
     meow.ad(&v.bytes(), false);
     let mut seed = [0u8; 32];
@@ -3,6 +3,8 @@ use elliptic_curve::ScalarPrimitive;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::compat::SerializablePoint;
 use crate::crypto::Commitment;
@@ -29,7 +31,11 @@ pub struct TranscriptFeed {
     confirmation: Vec<u8>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+/// The state of a participant between the steps of triple generation.
+///
+/// The polynomials, the shares and the state of the oblivious transfers are
+/// wiped when the state is dropped, and never show up in `Debug` output.
+#[derive(Deserialize, Serialize, Clone)]
 #[serde(bound = "
     C::Scalar: Serialize + for<'a> Deserialize<'a>,
     C::AffinePoint: Serialize + for<'a> Deserialize<'a>
@@ -39,6 +45,8 @@ pub struct TriplesState<C: CSCurve> {
     pub participants: Vec<Participant>,
     pub threshold: usize,
     pub triples_count: usize,
+    /// Binds the transcript and commitments to a single session.
+    pub session_id: Vec<u8>,
 
     pub my_commitments: Vec<Commitment>,
     pub my_randomizers: Vec<Randomizer>,
@@ -114,12 +122,14 @@ impl<'a, C: CSCurve> TriplesState<C> {
         participants: &[Participant],
         threshold: usize,
         triples_count: usize,
+        session_id: Vec<u8>,
     ) -> TriplesState<C> {
         TriplesState {
             participants: participants.to_vec(),
             transcript_feed: BTreeMap::new(),
             threshold,
             triples_count,
+            session_id,
             my_commitments: vec![],
             my_randomizers: vec![],
             e_v_coefficients: vec![],
@@ -183,6 +193,54 @@ impl<'a, C: CSCurve> TriplesState<C> {
     }
 }
 
+impl<C: CSCurve> fmt::Debug for TriplesState<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TriplesState")
+            .field("participants", &self.participants)
+            .field("threshold", &self.threshold)
+            .field("triples_count", &self.triples_count)
+            .field("session_id", &self.session_id)
+            .field("my_commitments", &self.my_commitments)
+            .field("my_confirmations", &self.my_confirmations)
+            .field("big_c_v", &self.big_c_v)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: CSCurve> Zeroize for TriplesState<C> {
+    fn zeroize(&mut self) {
+        self.e_v_coefficients.zeroize();
+        self.f_v_coefficients.zeroize();
+        self.l_v_coefficients.zeroize();
+        self.a_i_v.zeroize();
+        self.b_i_v.zeroize();
+        self.e0_v.zeroize();
+        self.f0_v.zeroize();
+        self.yv.zeroize();
+        self.dkv.zeroize();
+        self.t_v.zeroize();
+        self.q_v.zeroize();
+        self.b_v.zeroize();
+        self.seed_v.zeroize();
+        self.delta_1_v.zeroize();
+        self.delta_2_v.zeroize();
+        self.receiver_res0_v.zeroize();
+        self.receiver_res1_v.zeroize();
+        self.sender_res0_v.zeroize();
+        self.sender_res1_v.zeroize();
+        self.l0_v.zeroize();
+        self.c_i_v.zeroize();
+    }
+}
+
+impl<C: CSCurve> Drop for TriplesState<C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<C: CSCurve> ZeroizeOnDrop for TriplesState<C> {}
+
 type From = Participant;
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(bound = "
@@ -248,6 +306,26 @@ impl<C: CSCurve> RcvdTriplesMessages<C> {
             mta_wait_1: HashMap::new(),
         }
     }
+
+    /// Merge messages received from another participant into this buffer.
+    pub fn extend(&mut self, other: RcvdTriplesMessages<C>) {
+        self.wait_0.extend(other.wait_0);
+        self.wait_1.extend(other.wait_1);
+        self.wait_2.extend(other.wait_2);
+        self.wait_3.extend(other.wait_3);
+        self.wait_4.extend(other.wait_4);
+        self.wait_5.extend(other.wait_5);
+        self.wait_6.extend(other.wait_6);
+        self.batch_random_ot_wait_0
+            .extend(other.batch_random_ot_wait_0);
+        self.correlated_ot_wait_0.extend(other.correlated_ot_wait_0);
+        self.random_ot_extension_wait_0
+            .extend(other.random_ot_extension_wait_0);
+        self.random_ot_extension_wait_1
+            .extend(other.random_ot_extension_wait_1);
+        self.mta_wait_0.extend(other.mta_wait_0);
+        self.mta_wait_1.extend(other.mta_wait_1);
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
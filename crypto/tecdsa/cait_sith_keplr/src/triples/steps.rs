@@ -3,6 +3,7 @@ use magikitten::Transcript;
 use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
+use zeroize::Zeroize;
 
 use super::state::TriplesState;
 use super::{
@@ -27,8 +28,10 @@ pub enum TriplesTranscriptKeys {
     Participants,
     Threshold,
     Confirmation,
+    SessionId,
 }
 
+const SESSION_ID: &[u8] = b"session id";
 const GROUP: &'static [u8] = b"group";
 const PARTICIPANTS: &[u8] = b"participants";
 const THRESHOLD: &[u8] = b"participants";
@@ -41,14 +44,10 @@ const DLOGEQ0: &[u8] = b"dlogeq0";
 pub fn step_1<C: CSCurve>(st: &mut TriplesState<C>) -> Result<(), ProtocolError> {
     let mut rng = OsRng;
 
-    let participant_list = ParticipantList::new(&st.participants).ok_or_else(|| {
-        ProtocolError::Other(
-            "participant list cannot contain duplicates"
-                .to_string()
-                .into(),
-        )
-    })?;
+    let participant_list = ParticipantList::from_state(&st.participants)?;
 
+    st.transcript_feed
+        .insert(TriplesTranscriptKeys::SessionId, st.session_id.clone());
     st.transcript_feed
         .insert(TriplesTranscriptKeys::Group, C::NAME.to_vec());
     st.transcript_feed.insert(
@@ -57,12 +56,12 @@ pub fn step_1<C: CSCurve>(st: &mut TriplesState<C>) -> Result<(), ProtocolError>
     );
     st.transcript_feed.insert(
         TriplesTranscriptKeys::Threshold,
-        u64::try_from(st.threshold).unwrap().to_be_bytes().to_vec(),
+        (st.threshold as u64).to_be_bytes().to_vec(),
     );
 
     for _ in 0..st.triples_count {
-        let e: Polynomial<C> = Polynomial::random(&mut rng, st.threshold);
-        let f: Polynomial<C> = Polynomial::random(&mut rng, st.threshold);
+        let mut e: Polynomial<C> = Polynomial::random(&mut rng, st.threshold);
+        let mut f: Polynomial<C> = Polynomial::random(&mut rng, st.threshold);
         let mut l: Polynomial<C> = Polynomial::random(&mut rng, st.threshold);
 
         // Spec 1.3
@@ -74,7 +73,8 @@ pub fn step_1<C: CSCurve>(st: &mut TriplesState<C>) -> Result<(), ProtocolError>
         let big_l_i = l.commit();
 
         // Spec 1.5
-        let (my_commitment, my_randomizer) = commit(&mut rng, &(&big_e_i, &big_f_i, &big_l_i));
+        let (my_commitment, my_randomizer) =
+            commit(&mut rng, &(&st.session_id, &big_e_i, &big_f_i, &big_l_i));
 
         st.my_commitments.push(my_commitment);
         st.my_randomizers.push(my_randomizer);
@@ -83,9 +83,12 @@ pub fn step_1<C: CSCurve>(st: &mut TriplesState<C>) -> Result<(), ProtocolError>
         st.big_f_i_v.push(big_f_i);
         st.big_l_i_v.push(big_l_i);
 
-        st.e_v_coefficients.push(e.coefficients);
-        st.f_v_coefficients.push(f.coefficients);
-        st.l_v_coefficients.push(l.coefficients);
+        st.e_v_coefficients
+            .push(std::mem::take(&mut e.coefficients));
+        st.f_v_coefficients
+            .push(std::mem::take(&mut f.coefficients));
+        st.l_v_coefficients
+            .push(std::mem::take(&mut l.coefficients));
     }
 
     Ok(())
@@ -96,13 +99,9 @@ pub fn step_2<C: CSCurve>(
     msgs: &RcvdTriplesMessages<C>,
     me: Participant,
 ) -> Result<(), ProtocolError> {
-    let participant_list = ParticipantList::new(&st.participants).ok_or_else(|| {
-        ProtocolError::Other(
-            "participant list cannot contain duplicates"
-                .to_string()
-                .into(),
-        )
-    })?;
+    let participant_list = ParticipantList::from_state(&st.participants)?;
+
+    check_computed(&st.my_commitments, st.triples_count, "my_commitments")?;
 
     // Spec 2.1
     let mut all_commitments_vec: Vec<ParticipantMap<Commitment>> = vec![];
@@ -117,15 +116,12 @@ pub fn step_2<C: CSCurve>(
         all_commitments_vec_2.push(m_2);
     }
 
-    while all_commitments_vec
-        .iter()
-        .any(|all_commitments| !all_commitments.full())
-    {
+    for p in participant_list.others(me) {
+        let commitments = message_from(&msgs.wait_0, p, "wait_0")?;
+        check_payload_len(p, "commitments", commitments, st.triples_count)?;
         for i in 0..st.triples_count {
-            for (from, commitments) in &msgs.wait_0 {
-                all_commitments_vec[i].put(*from, commitments[i]);
-                all_commitments_vec_2[i].insert(*from, commitments[i]);
-            }
+            all_commitments_vec[i].put(p, commitments[i]);
+            all_commitments_vec_2[i].insert(p, commitments[i]);
         }
     }
 
@@ -156,10 +152,15 @@ pub fn step_3<C: CSCurve>(
 ) -> Result<(), ProtocolError> {
     let mut rng = OsRng;
 
+    check_computed(&st.e_v_coefficients, st.triples_count, "e_v_coefficients")?;
+    check_computed(&st.f_v_coefficients, st.triples_count, "f_v_coefficients")?;
+    check_computed(&st.big_e_i_v, st.triples_count, "big_e_i_v")?;
+    check_computed(&st.big_f_i_v, st.triples_count, "big_f_i_v")?;
+
     let mut my_phi_proof0v = vec![];
     let mut my_phi_proof1v = vec![];
 
-    let transcript = make_transcript(&st.transcript_feed);
+    let transcript = make_transcript(&st.transcript_feed)?;
 
     for i in 0..st.triples_count {
         let big_e_i = &st.big_e_i_v[i];
@@ -216,13 +217,10 @@ pub fn step_4<C: CSCurve>(
     _msgs: &RcvdTriplesMessages<C>,
     me: Participant,
 ) -> Result<Vec<(Participant, Wait3Payload<C>)>, ProtocolError> {
-    let participant_list = ParticipantList::new(&st.participants).ok_or_else(|| {
-        ProtocolError::Other(
-            "participant list cannot contain duplicates"
-                .to_string()
-                .into(),
-        )
-    })?;
+    let participant_list = ParticipantList::from_state(&st.participants)?;
+
+    check_computed(&st.e_v_coefficients, st.triples_count, "e_v_coefficients")?;
+    check_computed(&st.f_v_coefficients, st.triples_count, "f_v_coefficients")?;
 
     let mut ret = vec![];
     for p in participant_list.others(me) {
@@ -261,13 +259,18 @@ pub fn step_5<C: CSCurve>(
 ) -> Result<Wait4Payload<C>, ProtocolError> {
     let mut rng = OsRng;
 
-    let participant_list = ParticipantList::new(&st.participants).ok_or_else(|| {
-        ProtocolError::Other(
-            "participant list cannot contain duplicates"
-                .to_string()
-                .into(),
-        )
-    })?;
+    let participant_list = ParticipantList::from_state(&st.participants)?;
+
+    check_computed(&st.e_v_coefficients, st.triples_count, "e_v_coefficients")?;
+    check_computed(&st.f_v_coefficients, st.triples_count, "f_v_coefficients")?;
+    check_computed(&st.big_e_i_v, st.triples_count, "big_e_i_v")?;
+    check_computed(&st.big_f_i_v, st.triples_count, "big_f_i_v")?;
+    check_computed(&st.big_l_i_v, st.triples_count, "big_l_i_v")?;
+    check_computed(
+        &st.all_commitments_vec_2,
+        st.triples_count,
+        "all_commitments_vec_2",
+    )?;
 
     let mut a_i_v = vec![];
     let mut b_i_v = vec![];
@@ -293,15 +296,13 @@ pub fn step_5<C: CSCurve>(
 
     // Spec 3.1 + 3.2
     for p in participant_list.others(me) {
-        let confirmation = msgs
-            .wait_1
-            .get(&p)
-            .ok_or(ProtocolError::Other(format!("msg not exists").into()))?;
+        let confirmation = message_from(&msgs.wait_1, p, "wait_1")?;
 
         if *confirmation != st.my_confirmations {
-            return Err(ProtocolError::AssertionFailed(format!(
-                "confirmation from {p:?} did not match expectation"
-            )));
+            return Err(ProtocolError::BadShare(
+                p,
+                "confirmation did not match expectation".to_string(),
+            ));
         }
     }
 
@@ -330,10 +331,13 @@ pub fn step_5<C: CSCurve>(
             my_randomizers: their_randomizers,
             my_phi_proof0v: their_phi_proof0_v,
             my_phi_proof1v: their_phi_proof1_v,
-        } = msgs
-            .wait_2
-            .get(&p)
-            .ok_or(ProtocolError::Other(format!("msg not exists").into()))?;
+        } = message_from(&msgs.wait_2, p, "wait_2")?;
+        check_payload_len(p, "big_e_i_v", their_big_e_v, st.triples_count)?;
+        check_payload_len(p, "big_f_i_v", their_big_f_v, st.triples_count)?;
+        check_payload_len(p, "big_l_i_v", their_big_l_v, st.triples_count)?;
+        check_payload_len(p, "my_randomizers", their_randomizers, st.triples_count)?;
+        check_payload_len(p, "my_phi_proof0v", their_phi_proof0_v, st.triples_count)?;
+        check_payload_len(p, "my_phi_proof1v", their_phi_proof1_v, st.triples_count)?;
 
         let mut all_commitments_vec: Vec<ParticipantMap<Commitment>> = vec![];
 
@@ -361,40 +365,41 @@ pub fn step_5<C: CSCurve>(
                 || their_big_f.len() != st.threshold
                 || their_big_l.len() != st.threshold
             {
-                return Err(ProtocolError::AssertionFailed(format!(
-                    "polynomial from {p:?} has the wrong length"
-                )));
+                return Err(ProtocolError::BadShare(
+                    p,
+                    "polynomial has the wrong length".to_string(),
+                ));
             }
 
             if !bool::from(their_big_l.evaluate_zero().is_identity()) {
-                return Err(ProtocolError::AssertionFailed(format!(
-                    "L(0) from {p:?} is not 0"
-                )));
+                return Err(ProtocolError::BadShare(p, "L(0) is not 0".to_string()));
             }
 
             if !all_commitments[p].check(
-                &(&their_big_e, &their_big_f, &their_big_l),
+                &(&st.session_id, their_big_e, their_big_f, their_big_l),
                 their_randomizer,
             ) {
-                return Err(ProtocolError::AssertionFailed(format!(
-                    "commitment from {p:?} did not match revealed F"
-                )));
+                return Err(ProtocolError::BadShare(
+                    p,
+                    "commitment did not match revealed F".to_string(),
+                ));
             }
 
             let statement0 = dlog::Statement::<C> {
                 public: &their_big_e.evaluate_zero(),
             };
 
-            let transcript = make_transcript(&st.transcript_feed);
+            let transcript = make_transcript(&st.transcript_feed)?;
 
             if !dlog::verify(
                 &mut transcript.forked(b"dlog0", &p.bytes()),
                 statement0,
                 their_phi_proof0,
             ) {
-                return Err(ProtocolError::AssertionFailed(format!(
-                    "dlog proof from {p:?} failed to verify"
-                )));
+                return Err(ProtocolError::BadProof(
+                    p,
+                    "dlog proof failed to verify".to_string(),
+                ));
             }
 
             let statement1 = dlog::Statement::<C> {
@@ -406,9 +411,10 @@ pub fn step_5<C: CSCurve>(
                 statement1,
                 their_phi_proof1,
             ) {
-                return Err(ProtocolError::AssertionFailed(format!(
-                    "dlog proof from {p:?} failed to verify"
-                )));
+                return Err(ProtocolError::BadProof(
+                    p,
+                    "dlog proof failed to verify".to_string(),
+                ));
             }
 
             big_e_j_zero_v[i].put(p, their_big_e.evaluate_zero());
@@ -425,10 +431,9 @@ pub fn step_5<C: CSCurve>(
         let Wait3Payload {
             a_i_j_v: a_j_i_v,
             b_i_j_v: b_j_i_v,
-        } = msgs
-            .wait_3
-            .get(&p)
-            .ok_or(ProtocolError::Other(format!("msg not exists").into()))?;
+        } = message_from(&msgs.wait_3, p, "wait_3")?;
+        check_payload_len(p, "a_i_j_v", a_j_i_v, st.triples_count)?;
+        check_payload_len(p, "b_i_j_v", b_j_i_v, st.triples_count)?;
 
         //     if !seen.put(from) {
         //         continue;
@@ -481,7 +486,7 @@ pub fn step_5<C: CSCurve>(
             x: &e.evaluate_zero(),
         };
 
-        let transcript = make_transcript(&st.transcript_feed);
+        let transcript = make_transcript(&st.transcript_feed)?;
 
         let my_phi_proof = dlogeq::prove(
             &mut rng,
@@ -521,13 +526,11 @@ pub fn step_6<C: CSCurve>(
     msgs: &RcvdTriplesMessages<C>,
     me: Participant,
 ) -> Result<(), ProtocolError> {
-    let participant_list = ParticipantList::new(&st.participants).ok_or_else(|| {
-        ProtocolError::Other(
-            "participant list cannot contain duplicates"
-                .to_string()
-                .into(),
-        )
-    })?;
+    let participant_list = ParticipantList::from_state(&st.participants)?;
+
+    check_computed(&st.big_c_i_v, st.triples_count, "big_c_i_v")?;
+    check_computed(&st.big_e_j_zero_v_2, st.triples_count, "big_e_j_zero_v_2")?;
+    check_computed(&st.big_f_v, st.triples_count, "big_f_v")?;
 
     // Spec 4.1 + 4.2 + 4.3
     let mut big_c_v = vec![];
@@ -554,10 +557,9 @@ pub fn step_6<C: CSCurve>(
         let Wait4Payload {
             big_c_i_points: big_c_j_v,
             my_phi_proofs: their_phi_proofs,
-        } = msgs
-            .wait_4
-            .get(&p)
-            .ok_or(ProtocolError::Other(format!("msg not exists").into()))?;
+        } = message_from(&msgs.wait_4, p, "wait_4")?;
+        check_payload_len(p, "big_c_i_points", big_c_j_v, st.triples_count)?;
+        check_payload_len(p, "my_phi_proofs", their_phi_proofs, st.triples_count)?;
 
         for i in 0..st.triples_count {
             let big_e_j_zero = &big_e_j_zero_v[i];
@@ -571,16 +573,17 @@ pub fn step_6<C: CSCurve>(
                 public1: &big_c_j,
             };
 
-            let transcript = make_transcript(&st.transcript_feed);
+            let transcript = make_transcript(&st.transcript_feed)?;
 
             if !dlogeq::verify(
                 &mut transcript.forked(DLOGEQ0, &p.bytes()),
                 statement,
                 their_phi_proof,
             ) {
-                return Err(ProtocolError::AssertionFailed(format!(
-                    "dlogeq proof from {p:?} failed to verify"
-                )));
+                return Err(ProtocolError::BadProof(
+                    p,
+                    "dlogeq proof failed to verify".to_string(),
+                ));
             }
 
             let mut src = ProjectivePoint::<C>::from(big_c_v[i]);
@@ -599,13 +602,10 @@ pub fn step_7<C: CSCurve>(
     _msgs: &RcvdTriplesMessages<C>,
     me: Participant,
 ) -> Result<(Wait5Payload<C>, Wait6Payload<C>), ProtocolError> {
-    let participant_list = ParticipantList::new(&st.participants).ok_or_else(|| {
-        ProtocolError::Other(
-            "participant list cannot contain duplicates"
-                .to_string()
-                .into(),
-        )
-    })?;
+    let participant_list = ParticipantList::from_state(&st.participants)?;
+
+    check_computed(&st.l0_v, st.triples_count, "l0_v")?;
+    check_computed(&st.l_v_coefficients, st.triples_count, "l_v_coefficients")?;
 
     let mut rng = OsRng;
 
@@ -613,7 +613,7 @@ pub fn step_7<C: CSCurve>(
     let mut hat_big_c_i_v = vec![];
     let mut my_phi_proofs = vec![];
 
-    let transcript = make_transcript(&st.transcript_feed);
+    let transcript = make_transcript(&st.transcript_feed)?;
 
     for i in 0..st.triples_count {
         // Spec 4.5
@@ -687,32 +687,37 @@ pub fn step_8<C: CSCurve>(
     msgs: &RcvdTriplesMessages<C>,
     me: Participant,
 ) -> Result<TriplesGenManyResult<C>, ProtocolError> {
-    let participant_list = ParticipantList::new(&st.participants).ok_or_else(|| {
-        ProtocolError::Other(
-            "participant list cannot contain duplicates"
-                .to_string()
-                .into(),
-        )
-    })?;
+    let participant_list = ParticipantList::from_state(&st.participants)?;
+
+    check_computed(&st.hat_big_c_i_v, st.triples_count, "hat_big_c_i_v")?;
+    check_computed(&st.big_l_v, st.triples_count, "big_l_v")?;
+    check_computed(&st.big_c_v, st.triples_count, "big_c_v")?;
+    check_computed(&st.c_i_v, st.triples_count, "c_i_v")?;
+    check_computed(&st.a_i_v, st.triples_count, "a_i_v")?;
+    check_computed(&st.b_i_v, st.triples_count, "b_i_v")?;
+    check_computed(&st.big_e_v, st.triples_count, "big_e_v")?;
+    check_computed(&st.big_f_v, st.triples_count, "big_f_v")?;
 
     let mut hat_big_c_v = vec![];
     for i in 0..st.triples_count {
         hat_big_c_v.push(st.hat_big_c_i_v[i]);
     }
 
-    let transcript = make_transcript(&st.transcript_feed);
+    let transcript = make_transcript(&st.transcript_feed)?;
 
-    let mut their_hat_big_c_i_points = vec![];
-    let mut their_phi_proofs = vec![];
-    let mut from = me;
-    for (p, payload) in &msgs.wait_5 {
-        their_hat_big_c_i_points = payload.hat_big_c_i_points.clone();
-        their_phi_proofs = payload.my_phi_proofs.clone();
-        from = *p;
-        break;
-    }
+    for p in participant_list.others(me) {
+        let Wait5Payload {
+            hat_big_c_i_points: their_hat_big_c_i_points,
+            my_phi_proofs: their_phi_proofs,
+        } = message_from(&msgs.wait_5, p, "wait_5")?;
+        check_payload_len(
+            p,
+            "hat_big_c_i_points",
+            their_hat_big_c_i_points,
+            st.triples_count,
+        )?;
+        check_payload_len(p, "my_phi_proofs", their_phi_proofs, st.triples_count)?;
 
-    for _ in participant_list.others(me) {
         for i in 0..st.triples_count {
             let their_hat_big_c = their_hat_big_c_i_points[i].to_projective();
             let their_phi_proof = &their_phi_proofs[i];
@@ -721,13 +726,14 @@ pub fn step_8<C: CSCurve>(
                 public: &their_hat_big_c,
             };
             if !dlog::verify(
-                &mut transcript.forked(DLOG2, &from.bytes()),
+                &mut transcript.forked(DLOG2, &p.bytes()),
                 statement,
                 their_phi_proof,
             ) {
-                return Err(ProtocolError::AssertionFailed(format!(
-                    "dlog proof from {from:?} failed to verify"
-                )));
+                return Err(ProtocolError::BadProof(
+                    p,
+                    "dlog proof failed to verify".to_string(),
+                ));
             }
 
             let mut src = ProjectivePoint::<C>::from(hat_big_c_v[i]);
@@ -754,13 +760,9 @@ pub fn step_8<C: CSCurve>(
 
     // Spec 5.5 + 5.6
 
-    let mut c_j_i_v = vec![];
-    for (_, payload) in &msgs.wait_6 {
-        c_j_i_v = payload.c_i_j_v.clone();
-        break;
-    }
-
-    for _ in participant_list.others(me) {
+    for p in participant_list.others(me) {
+        let c_j_i_v = &message_from(&msgs.wait_6, p, "wait_6")?.c_i_j_v;
+        check_payload_len(p, "c_i_j_v", c_j_i_v, st.triples_count)?;
         for i in 0..st.triples_count {
             let c_j_i = c_j_i_v[i];
             st.c_i_v[i] += C::Scalar::from(c_j_i);
@@ -807,21 +809,71 @@ pub fn step_8<C: CSCurve>(
         });
     }
 
+    // The shares are in the output now, and nothing else in the state is needed.
+    st.zeroize();
+
     Ok(ret)
 }
 
-fn make_transcript(transcript_feed: &BTreeMap<TriplesTranscriptKeys, Vec<u8>>) -> Transcript {
+/// The message from `p` at the `wait` point.
+pub(super) fn message_from<'a, T>(
+    msgs: &'a HashMap<Participant, T>,
+    p: Participant,
+    wait: &'static str,
+) -> Result<&'a T, ProtocolError> {
+    msgs.get(&p)
+        .ok_or(ProtocolError::MissingMessage { from: p, wait })
+}
+
+/// Check that the `what` payload from `p` holds one entry per triple.
+pub(super) fn check_payload_len<T>(
+    p: Participant,
+    what: &str,
+    payload: &[T],
+    triples_count: usize,
+) -> Result<(), ProtocolError> {
+    if payload.len() != triples_count {
+        return Err(ProtocolError::Malformed(format!(
+            "{what} from {p:?} has {} entries, expected {triples_count}",
+            payload.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Check that `what` in the state holds one entry per triple, i.e. that the
+/// step computing it was run.
+pub(super) fn check_computed<T>(
+    state: &[T],
+    triples_count: usize,
+    what: &'static str,
+) -> Result<(), ProtocolError> {
+    if state.len() != triples_count {
+        return Err(ProtocolError::OutOfOrder(what));
+    }
+    Ok(())
+}
+
+fn make_transcript(
+    transcript_feed: &BTreeMap<TriplesTranscriptKeys, Vec<u8>>,
+) -> Result<Transcript, ProtocolError> {
+    let get = |key, what| {
+        transcript_feed
+            .get(&key)
+            .ok_or(ProtocolError::OutOfOrder(what))
+    };
+
     let mut transcript = Transcript::new(LABEL);
-    let group = transcript_feed.get(&TriplesTranscriptKeys::Group).unwrap();
-    let participants = transcript_feed
-        .get(&TriplesTranscriptKeys::Participants)
-        .unwrap();
-    let threshold = transcript_feed
-        .get(&TriplesTranscriptKeys::Threshold)
-        .unwrap();
-    let confirmation = transcript_feed
-        .get(&TriplesTranscriptKeys::Confirmation)
-        .unwrap();
+    let session_id = get(TriplesTranscriptKeys::SessionId, "transcript session id")?;
+    let group = get(TriplesTranscriptKeys::Group, "transcript group")?;
+    let participants = get(
+        TriplesTranscriptKeys::Participants,
+        "transcript participants",
+    )?;
+    let threshold = get(TriplesTranscriptKeys::Threshold, "transcript threshold")?;
+    let confirmation = get(TriplesTranscriptKeys::Confirmation, "confirmation")?;
+
+    transcript.message(SESSION_ID, session_id);
 
     transcript.message(GROUP, group);
 
@@ -831,5 +883,5 @@ fn make_transcript(transcript_feed: &BTreeMap<TriplesTranscriptKeys, Vec<u8>>) -
 
     transcript.message(CONFIRMATION, confirmation);
 
-    transcript
+    Ok(transcript)
 }
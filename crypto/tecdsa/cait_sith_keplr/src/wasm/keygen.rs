@@ -0,0 +1,87 @@
+use k256::Secp256k1;
+use wasm_bindgen::prelude::*;
+
+use super::{PrivateStepOutput, StepOutput};
+use crate::keyshare::{KeyshareState2, RcvdKeyshareMessages};
+use crate::tecdsa_cli_srv::bindings::{envelope, parse_config, BindingError, StepState};
+use crate::tecdsa_cli_srv::cli_keygen::KeygenClient;
+use crate::tecdsa_cli_srv::envelope::ProtocolKind::Keygen;
+
+type KeygenState = StepState<KeyshareState2<Secp256k1>, RcvdKeyshareMessages<Secp256k1>>;
+
+/// The client side of key generation, exported as `KeygenClient`.
+#[wasm_bindgen(js_name = KeygenClient)]
+pub struct WasmKeygenClient {}
+
+#[wasm_bindgen(js_class = KeygenClient)]
+impl WasmKeygenClient {
+    #[wasm_bindgen(js_name = keygenStep1)]
+    pub fn keygen_step_1(config: &str) -> Result<StepOutput, JsValue> {
+        let config = parse_config(config)?;
+        let out =
+            KeygenClient::cli_keygen_step_1::<Secp256k1>(&config).map_err(BindingError::from)?;
+        let state = KeygenState {
+            st: out.st_0,
+            rcvd: RcvdKeyshareMessages::new(),
+        };
+        Ok(StepOutput::new(
+            &state,
+            &envelope(&config, Keygen, 1),
+            &out.msgs_1,
+        ))
+    }
+
+    #[wasm_bindgen(js_name = keygenStep2)]
+    pub fn keygen_step_2(config: &str, state: &[u8], msgs: &[u8]) -> Result<StepOutput, JsValue> {
+        let config = parse_config(config)?;
+        let KeygenState { st, rcvd } = KeygenState::receive(&config, Keygen, 1, state, msgs)?;
+        let out =
+            KeygenClient::cli_keygen_step_2(&config, st, &rcvd).map_err(BindingError::from)?;
+        Ok(StepOutput::new(
+            &KeygenState { st: out.st_0, rcvd },
+            &envelope(&config, Keygen, 2),
+            &out.msgs_1,
+        ))
+    }
+
+    #[wasm_bindgen(js_name = keygenStep3)]
+    pub fn keygen_step_3(config: &str, state: &[u8], msgs: &[u8]) -> Result<StepOutput, JsValue> {
+        let config = parse_config(config)?;
+        let KeygenState { st, rcvd } = KeygenState::receive(&config, Keygen, 2, state, msgs)?;
+        let out =
+            KeygenClient::cli_keygen_step_3(&config, st, &rcvd).map_err(BindingError::from)?;
+        Ok(StepOutput::new(
+            &KeygenState { st: out.st_0, rcvd },
+            &envelope(&config, Keygen, 3),
+            &out.msgs_1,
+        ))
+    }
+
+    /// Returns private messages, to be read with `msgsFor(participant)`.
+    #[wasm_bindgen(js_name = keygenStep4)]
+    pub fn keygen_step_4(
+        config: &str,
+        state: &[u8],
+        msgs: &[u8],
+    ) -> Result<PrivateStepOutput, JsValue> {
+        let config = parse_config(config)?;
+        let KeygenState { st, rcvd } = KeygenState::receive(&config, Keygen, 3, state, msgs)?;
+        let out =
+            KeygenClient::cli_keygen_step_4(&config, st, &rcvd).map_err(BindingError::from)?;
+        Ok(PrivateStepOutput::new(
+            &KeygenState { st: out.st_0, rcvd },
+            &envelope(&config, Keygen, 4),
+            &out.msgs_1,
+        ))
+    }
+
+    /// Returns the encoded `KeygenOutput`.
+    #[wasm_bindgen(js_name = keygenStep5)]
+    pub fn keygen_step_5(config: &str, state: &[u8], msgs: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let config = parse_config(config)?;
+        let KeygenState { st, rcvd } = KeygenState::receive(&config, Keygen, 4, state, msgs)?;
+        let keygen_0 =
+            KeygenClient::cli_keygen_step_5(&config, st, &rcvd).map_err(BindingError::from)?;
+        Ok(crate::serde::encode(&keygen_0))
+    }
+}
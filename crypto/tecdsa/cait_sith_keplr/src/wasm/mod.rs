@@ -0,0 +1,116 @@
+//! wasm-bindgen bindings for the client side of the step API, over secp256k1.
+//!
+//! Each step takes the session config as JSON, e.g.
+//! `{"participants":[0,1],"me":0,"threshold":2,"session_id":"...","key_id":"..."}`,
+//! where `key_id` is only required to presign and sign, and `participants` must
+//! name exactly two parties, the client and the server. The state and messages
+//! are `Uint8Array`s. The state is the msgpack encoding the Rust API uses, and
+//! also keeps the messages received so far, so each step only needs the
+//! messages of the latest round. The messages are sealed in an [`Envelope`],
+//! and are rejected unless they come from the previous step of the same flow.
+//!
+//! Failures are thrown as JS `Error`s, whose `name` is one of `ConfigError`,
+//! `DecodeError`, `ProtocolError`, `BadShareError`, `BadProofError`,
+//! `MissingMessageError` or `OutOfOrderError`. The errors blaming another party
+//! also have a `participant` property, naming it.
+mod keygen;
+mod presign;
+mod sign;
+mod triples;
+
+pub use keygen::*;
+pub use presign::*;
+pub use sign::*;
+pub use triples::*;
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::protocol::Participant;
+use crate::tecdsa_cli_srv::bindings::BindingError;
+use crate::tecdsa_cli_srv::envelope::Envelope;
+
+impl From<BindingError> for JsValue {
+    fn from(e: BindingError) -> Self {
+        let js_error = js_sys::Error::new(&e.to_string());
+        if let Some(p) = e.culprit() {
+            let _ = js_sys::Reflect::set(
+                &js_error,
+                &JsValue::from_str("participant"),
+                &JsValue::from(u32::from(p)),
+            );
+        }
+        js_error.set_name(e.kind().name());
+        js_error.into()
+    }
+}
+
+/// The output of a step: the state to pass to the next step, and the messages to send.
+#[wasm_bindgen]
+pub struct StepOutput {
+    state: Vec<u8>,
+    msgs: Vec<u8>,
+}
+
+impl StepOutput {
+    fn new<S: Serialize, M: Serialize>(state: &S, envelope: &Envelope, msgs: &M) -> Self {
+        StepOutput {
+            state: crate::serde::encode(state),
+            msgs: envelope.seal(msgs),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StepOutput {
+    #[wasm_bindgen(getter)]
+    pub fn state(&self) -> Vec<u8> {
+        self.state.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn msgs(&self) -> Vec<u8> {
+        self.msgs.clone()
+    }
+}
+
+/// The output of a step sending a different message to each participant.
+#[wasm_bindgen]
+pub struct PrivateStepOutput {
+    state: Vec<u8>,
+    msgs: HashMap<Participant, Vec<u8>>,
+}
+
+impl PrivateStepOutput {
+    fn new<S: Serialize, M: Serialize>(
+        state: &S,
+        envelope: &Envelope,
+        msgs: &HashMap<Participant, M>,
+    ) -> Self {
+        PrivateStepOutput {
+            state: crate::serde::encode(state),
+            msgs: msgs.iter().map(|(p, m)| (*p, envelope.seal(m))).collect(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl PrivateStepOutput {
+    #[wasm_bindgen(getter)]
+    pub fn state(&self) -> Vec<u8> {
+        self.state.clone()
+    }
+
+    /// The messages to send to `participant`.
+    #[wasm_bindgen(js_name = msgsFor)]
+    pub fn msgs_for(&self, participant: u32) -> Result<Vec<u8>, JsValue> {
+        self.msgs
+            .get(&Participant::from(participant))
+            .cloned()
+            .ok_or_else(|| {
+                BindingError::Config(format!("no messages for participant {}", participant)).into()
+            })
+    }
+}
@@ -0,0 +1,83 @@
+use k256::Secp256k1;
+use wasm_bindgen::prelude::*;
+
+use super::StepOutput;
+use crate::presign::{PresignState2, RcvdPresignMessages2};
+use crate::tecdsa::triples_2::TriplesGenManyResult;
+use crate::tecdsa_cli_srv::bindings::{decode, envelope, parse_config, BindingError, StepState};
+use crate::tecdsa_cli_srv::cli_presign::PresignClient;
+use crate::tecdsa_cli_srv::envelope::ProtocolKind::Presign;
+use crate::KeygenOutput;
+
+type PresignClientState = StepState<PresignState2<Secp256k1>, RcvdPresignMessages2<Secp256k1>>;
+
+/// The client side of presigning, exported as `PresignClient`.
+#[wasm_bindgen(js_name = PresignClient)]
+pub struct WasmPresignClient {}
+
+#[wasm_bindgen(js_class = PresignClient)]
+impl WasmPresignClient {
+    /// Start presigning with the output of `triplesStep11` and `keygenStep5`.
+    #[wasm_bindgen(js_name = presignStep1)]
+    pub fn presign_step_1(
+        config: &str,
+        triples: &[u8],
+        keygen: &[u8],
+    ) -> Result<StepOutput, JsValue> {
+        let config = parse_config(config)?;
+        let triples_0: TriplesGenManyResult<Secp256k1> = decode("triples", triples)?;
+        let keygen_0: KeygenOutput<Secp256k1> = decode("keygen", keygen)?;
+
+        let (Some(pub_0), Some(pub_1)) = (triples_0.pub_v.first(), triples_0.pub_v.get(1)) else {
+            return Err(BindingError::Decode("triples: expected two triples".to_string()).into());
+        };
+        let (Some(share_0), Some(share_1)) = (triples_0.share_v.first(), triples_0.share_v.get(1))
+        else {
+            return Err(BindingError::Decode("triples: expected two shares".to_string()).into());
+        };
+
+        let out = PresignClient::presign_step_1(
+            &config,
+            pub_0.clone(),
+            pub_1.clone(),
+            share_0.clone(),
+            share_1.clone(),
+            keygen_0,
+        )
+        .map_err(BindingError::from)?;
+        let state = PresignClientState {
+            st: out.st_0,
+            rcvd: RcvdPresignMessages2::new(),
+        };
+        Ok(StepOutput::new(
+            &state,
+            &envelope(&config, Presign, 1),
+            &out.msgs_1,
+        ))
+    }
+
+    /// The step itself needs no messages, but those of the first round are kept for `presignStep3`.
+    #[wasm_bindgen(js_name = presignStep2)]
+    pub fn presign_step_2(config: &str, state: &[u8], msgs: &[u8]) -> Result<StepOutput, JsValue> {
+        let config = parse_config(config)?;
+        let PresignClientState { st, rcvd } =
+            PresignClientState::receive(&config, Presign, 1, state, msgs)?;
+        let out = PresignClient::presign_step_2(&config, st).map_err(BindingError::from)?;
+        Ok(StepOutput::new(
+            &PresignClientState { st: out.st_0, rcvd },
+            &envelope(&config, Presign, 2),
+            &out.msgs_1,
+        ))
+    }
+
+    /// Returns the encoded `PresignOutput`.
+    #[wasm_bindgen(js_name = presignStep3)]
+    pub fn presign_step_3(config: &str, state: &[u8], msgs: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let config = parse_config(config)?;
+        let PresignClientState { st, rcvd } =
+            PresignClientState::receive(&config, Presign, 2, state, msgs)?;
+        let presig_0 =
+            PresignClient::presign_step_3(&config, st, &rcvd).map_err(BindingError::from)?;
+        Ok(crate::serde::encode(&presig_0))
+    }
+}
@@ -0,0 +1,96 @@
+use k256::Secp256k1;
+use wasm_bindgen::prelude::*;
+
+use super::StepOutput;
+use crate::sign::{RcvdSignMessages, SignOutput, SignState2};
+use crate::tecdsa_cli_srv::bindings::{
+    decode, decode_msg_hash, envelope, parse_config, BindingError, StepState,
+};
+use crate::tecdsa_cli_srv::cli_sign::SignClient;
+use crate::tecdsa_cli_srv::envelope::ProtocolKind::Sign;
+use crate::PresignOutput;
+
+type SignClientState = StepState<SignState2<Secp256k1>, RcvdSignMessages<Secp256k1>>;
+
+/// The client side of signing, exported as `SignClient`.
+#[wasm_bindgen(js_name = SignClient)]
+pub struct WasmSignClient {}
+
+#[wasm_bindgen(js_class = SignClient)]
+impl WasmSignClient {
+    /// Start signing the 32 byte `msg_hash` with the output of `presignStep3`.
+    #[wasm_bindgen(js_name = signStep1)]
+    pub fn sign_step_1(
+        config: &str,
+        msg_hash: &[u8],
+        presign: &[u8],
+    ) -> Result<StepOutput, JsValue> {
+        let config = parse_config(config)?;
+        let msg_hash = decode_msg_hash(msg_hash)?;
+        let presig_0: PresignOutput<Secp256k1> = decode("presign", presign)?;
+        let out =
+            SignClient::sign_step_1(&config, msg_hash, presig_0).map_err(BindingError::from)?;
+        let state = SignClientState {
+            st: out.st_0,
+            rcvd: RcvdSignMessages::new(),
+        };
+        Ok(StepOutput::new(
+            &state,
+            &envelope(&config, Sign, 1),
+            &out.msgs_1,
+        ))
+    }
+
+    #[wasm_bindgen(js_name = signStep2)]
+    pub fn sign_step_2(
+        config: &str,
+        state: &[u8],
+        msgs: &[u8],
+        presign: &[u8],
+    ) -> Result<Signature, JsValue> {
+        let config = parse_config(config)?;
+        let SignClientState { mut st, rcvd } =
+            SignClientState::receive(&config, Sign, 1, state, msgs)?;
+        let presig_0: PresignOutput<Secp256k1> = decode("presign", presign)?;
+        let out = SignClient::sign_step_2(&config, &mut st, &rcvd, presig_0)
+            .map_err(BindingError::from)?;
+        Ok(Signature(out))
+    }
+}
+
+/// A finished signature, in the encodings chains expect.
+#[wasm_bindgen]
+pub struct Signature(SignOutput<Secp256k1>);
+
+#[wasm_bindgen]
+impl Signature {
+    /// The 64 byte `r || s` encoding.
+    #[wasm_bindgen(getter)]
+    pub fn compact(&self) -> Vec<u8> {
+        self.0.sig.to_compact()
+    }
+
+    /// The 65 byte `r || s || v` encoding, with `v` being 27 or 28.
+    #[wasm_bindgen(getter)]
+    pub fn recoverable(&self) -> Result<Vec<u8>, JsValue> {
+        self.0
+            .recoverable()
+            .to_bytes()
+            .map_err(|e| BindingError::from(e).into())
+    }
+
+    /// The recovery id, 0 or 1.
+    #[wasm_bindgen(getter, js_name = recoveryId)]
+    pub fn recovery_id(&self) -> u8 {
+        self.0.recoverable().recovery_id
+    }
+
+    /// The ASN.1 DER encoding.
+    #[wasm_bindgen(getter)]
+    pub fn der(&self) -> Result<Vec<u8>, JsValue> {
+        self.0
+            .sig
+            .to_der()
+            .map_err(|e| BindingError::from(e).into())
+    }
+}
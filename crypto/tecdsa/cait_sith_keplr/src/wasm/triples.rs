@@ -0,0 +1,80 @@
+use k256::Secp256k1;
+use wasm_bindgen::prelude::*;
+
+use super::StepOutput;
+use crate::tecdsa_cli_srv::bindings::{envelope, parse_config, BindingError, StepState};
+use crate::tecdsa_cli_srv::cli_triples::TriplesClient;
+use crate::tecdsa_cli_srv::envelope::ProtocolKind::Triples;
+use crate::triples::{RcvdTriplesMessages, TriplesState};
+
+type TriplesClientState = StepState<TriplesState<Secp256k1>, RcvdTriplesMessages<Secp256k1>>;
+
+/// The client side of triple generation, exported as `TriplesClient`.
+#[wasm_bindgen(js_name = TriplesClient)]
+pub struct WasmTriplesClient {}
+
+#[wasm_bindgen(js_class = TriplesClient)]
+impl WasmTriplesClient {
+    #[wasm_bindgen(js_name = triplesStep1)]
+    pub fn triples_step_1(config: &str) -> Result<StepOutput, JsValue> {
+        let config = parse_config(config)?;
+        let out =
+            TriplesClient::triples_step_1::<Secp256k1>(&config).map_err(BindingError::from)?;
+        let state = TriplesClientState {
+            st: out.st_0,
+            rcvd: RcvdTriplesMessages::new(),
+        };
+        Ok(StepOutput::new(
+            &state,
+            &envelope(&config, Triples, 1),
+            &out.msgs_1,
+        ))
+    }
+
+    /// Returns the encoded `TriplesGenManyResult`, holding the two triples a presignature uses.
+    #[wasm_bindgen(js_name = triplesStep11)]
+    pub fn triples_step_11(config: &str, state: &[u8], msgs: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let config = parse_config(config)?;
+        let TriplesClientState { st, rcvd } =
+            TriplesClientState::receive(&config, Triples, 10, state, msgs)?;
+        let triples_0 =
+            TriplesClient::triples_step_11(&config, st, rcvd).map_err(BindingError::from)?;
+        Ok(crate::serde::encode(&triples_0))
+    }
+}
+
+/// The steps in between all take the state and messages, and return the same.
+macro_rules! impl_triples_steps {
+    ($($step:ident => $js_name:ident, $n:literal),* $(,)?) => {
+        #[wasm_bindgen(js_class = TriplesClient)]
+        impl WasmTriplesClient {
+            $(
+                #[wasm_bindgen(js_name = $js_name)]
+                pub fn $step(config: &str, state: &[u8], msgs: &[u8]) -> Result<StepOutput, JsValue> {
+                    let config = parse_config(config)?;
+                    let TriplesClientState { st, rcvd } =
+                        TriplesClientState::receive(&config, Triples, $n - 1, state, msgs)?;
+                    let out = TriplesClient::$step(&config, st, rcvd.clone())
+                        .map_err(BindingError::from)?;
+                    Ok(StepOutput::new(
+                        &TriplesClientState { st: out.st_0, rcvd },
+                        &envelope(&config, Triples, $n),
+                        &out.msgs_1,
+                    ))
+                }
+            )*
+        }
+    };
+}
+
+impl_triples_steps!(
+    triples_step_2 => triplesStep2, 2,
+    triples_step_3 => triplesStep3, 3,
+    triples_step_4 => triplesStep4, 4,
+    triples_step_5 => triplesStep5, 5,
+    triples_step_6 => triplesStep6, 6,
+    triples_step_7 => triplesStep7, 7,
+    triples_step_8 => triplesStep8, 8,
+    triples_step_9 => triplesStep9, 9,
+    triples_step_10 => triplesStep10, 10,
+);
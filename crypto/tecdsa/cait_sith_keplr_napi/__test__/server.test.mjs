@@ -0,0 +1,72 @@
+import assert from "node:assert/strict";
+import { createRequire } from "node:module";
+import { test } from "node:test";
+
+const require = createRequire(import.meta.url);
+const napi = require("../index.js");
+
+const configs = [0, 1].map((me) =>
+  JSON.stringify({
+    participants: [0, 1],
+    me,
+    threshold: 2,
+    session_id: "test",
+    key_id: "key",
+  }),
+);
+
+// Both parties run the server steps, exchanging the messages each step returns.
+async function runBoth(step, states, msgs, ...args) {
+  return Promise.all(
+    configs.map((config, i) => step(config, states[i], msgs[1 - i], ...args)),
+  );
+}
+
+// Keygen is symmetric, so two servers can run it against each other. The other
+// protocols pair the server with the client side, from the wasm bindings.
+test("keygen", async () => {
+  const step1 = configs.map((config) => napi.keygenStep1(config));
+  assert.ok(step1.every((p) => p instanceof Promise));
+
+  let outs = await Promise.all(step1);
+  for (const step of [napi.keygenStep2, napi.keygenStep3]) {
+    outs = await runBoth(step, outs.map((o) => o.state), outs.map((o) => o.msgs));
+  }
+  const outs4 = await runBoth(
+    napi.keygenStep4,
+    outs.map((o) => o.state),
+    outs.map((o) => o.msgs),
+  );
+  assert.deepEqual(Object.keys(outs4[0].msgs), ["1"]);
+  assert.deepEqual(Object.keys(outs4[1].msgs), ["0"]);
+
+  const keygens = await Promise.all(
+    configs.map((config, i) =>
+      napi.keygenStep5(config, outs4[i].state, outs4[1 - i].msgs[String(i)]),
+    ),
+  );
+  assert.ok(keygens.every((k) => Buffer.isBuffer(k) && k.length > 0));
+});
+
+test("errors have a code", async () => {
+  await assert.rejects(napi.keygenStep1("{"), { code: "ConfigError" });
+
+  const config = configs[1];
+  const out = await napi.keygenStep1(config);
+  await assert.rejects(napi.keygenStep2(config, out.state, Buffer.from([0xc1])), {
+    code: "DecodeError",
+  });
+  // The messages of step 1 are not those step 3 expects.
+  const other = await napi.keygenStep1(configs[0]);
+  await assert.rejects(napi.keygenStep3(config, out.state, other.msgs), {
+    code: "DecodeError",
+    message: /step 1, expected 2/,
+  });
+
+  await assert.rejects(
+    napi.keygenStep1(
+      JSON.stringify({ participants: [0], me: 0, threshold: 2, session_id: "test" }),
+    ),
+    { code: "ConfigError" },
+  );
+});
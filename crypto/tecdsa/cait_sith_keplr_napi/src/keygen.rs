@@ -0,0 +1,42 @@
+use cait_sith_keplr::tecdsa_cli_srv::bindings::server;
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi_derive::napi;
+
+use crate::{OutputTask, PrivateStepTask, StepTask};
+
+#[napi(js_name = "keygenStep1")]
+pub fn keygen_step_1(config: String) -> AsyncTask<StepTask> {
+    StepTask::new(move || Ok(server::keygen_step_1(&config)?.into()))
+}
+
+/// The steps in between all take the state and messages, and return the same.
+macro_rules! impl_keygen_steps {
+    ($($step:ident => $js_name:literal),* $(,)?) => {
+        $(
+            #[napi(js_name = $js_name)]
+            pub fn $step(config: String, state: Buffer, msgs: Buffer) -> AsyncTask<StepTask> {
+                let (state, msgs) = (state.to_vec(), msgs.to_vec());
+                StepTask::new(move || Ok(server::$step(&config, &state, &msgs)?.into()))
+            }
+        )*
+    };
+}
+
+impl_keygen_steps!(
+    keygen_step_2 => "keygenStep2",
+    keygen_step_3 => "keygenStep3",
+);
+
+/// Resolves with private messages, keyed by recipient.
+#[napi(js_name = "keygenStep4")]
+pub fn keygen_step_4(config: String, state: Buffer, msgs: Buffer) -> AsyncTask<PrivateStepTask> {
+    let (state, msgs) = (state.to_vec(), msgs.to_vec());
+    PrivateStepTask::new(move || Ok(server::keygen_step_4(&config, &state, &msgs)?.into()))
+}
+
+/// Resolves with the encoded `KeygenOutput`.
+#[napi(js_name = "keygenStep5")]
+pub fn keygen_step_5(config: String, state: Buffer, msgs: Buffer) -> AsyncTask<OutputTask> {
+    let (state, msgs) = (state.to_vec(), msgs.to_vec());
+    OutputTask::new(move || Ok(server::keygen_step_5(&config, &state, &msgs)?.into()))
+}
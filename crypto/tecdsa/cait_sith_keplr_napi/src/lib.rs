@@ -0,0 +1,35 @@
+//! N-API bindings for the server side of the step API, over secp256k1.
+//!
+//! The steps themselves live in `cait_sith_keplr::tecdsa_cli_srv::bindings::server`,
+//! and only their inputs and outputs are converted here.
+//!
+//! Each step takes the session config as JSON, e.g.
+//! `{"participants":[0,1],"me":1,"threshold":2,"session_id":"...","key_id":"..."}`,
+//! where `key_id` is only required to presign and sign, and `participants` must
+//! name exactly two parties, the client and the server. The state and messages
+//! are `Buffer`s. The state is the msgpack encoding the Rust API uses, and also
+//! keeps the messages received so far, so each step only needs the messages of
+//! the latest round. The messages are sealed in an `Envelope`, and are rejected
+//! unless they come from the previous step of the same flow. The exception is
+//! triples, where the server runs behind the client: each server step takes the
+//! messages of the client step of the same number, so `triplesStep1` takes
+//! those of the client's `triplesStep1`.
+//!
+//! Every step returns a `Promise`, and runs on the libuv thread pool rather than
+//! the main thread, since the triple steps in particular take a while.
+//!
+//! Failures reject with a JS `Error`, whose `code` is one of `ConfigError`,
+//! `DecodeError`, `ProtocolError`, `BadShareError`, `BadProofError`,
+//! `MissingMessageError` or `OutOfOrderError`. The errors blaming another party
+//! also have a `participant` property, naming it.
+mod keygen;
+mod presign;
+mod sign;
+mod task;
+mod triples;
+
+pub use keygen::*;
+pub use presign::*;
+pub use sign::*;
+pub use task::*;
+pub use triples::*;
@@ -0,0 +1,26 @@
+use cait_sith_keplr::tecdsa_cli_srv::bindings::server;
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi_derive::napi;
+
+use crate::{OutputTask, StepTask};
+
+/// Start presigning with the output of `triplesStep11` and `keygenStep5`.
+#[napi(js_name = "presignStep1")]
+pub fn presign_step_1(config: String, triples: Buffer, keygen: Buffer) -> AsyncTask<StepTask> {
+    let (triples, keygen) = (triples.to_vec(), keygen.to_vec());
+    StepTask::new(move || Ok(server::presign_step_1(&config, &triples, &keygen)?.into()))
+}
+
+/// The step itself needs no messages, but those of the first round are kept for `presignStep3`.
+#[napi(js_name = "presignStep2")]
+pub fn presign_step_2(config: String, state: Buffer, msgs: Buffer) -> AsyncTask<StepTask> {
+    let (state, msgs) = (state.to_vec(), msgs.to_vec());
+    StepTask::new(move || Ok(server::presign_step_2(&config, &state, &msgs)?.into()))
+}
+
+/// Resolves with the encoded `PresignOutput`.
+#[napi(js_name = "presignStep3")]
+pub fn presign_step_3(config: String, state: Buffer, msgs: Buffer) -> AsyncTask<OutputTask> {
+    let (state, msgs) = (state.to_vec(), msgs.to_vec());
+    OutputTask::new(move || Ok(server::presign_step_3(&config, &state, &msgs)?.into()))
+}
@@ -0,0 +1,45 @@
+use cait_sith_keplr::tecdsa_cli_srv::bindings::server;
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi_derive::napi;
+
+use crate::{SignTask, StepTask};
+
+/// A finished signature, in the encodings chains expect.
+#[napi(object)]
+pub struct Signature {
+    /// The 64 byte `r || s` encoding.
+    pub compact: Buffer,
+    /// The 65 byte `r || s || v` encoding, with `v` being 27 or 28.
+    pub recoverable: Buffer,
+    /// The recovery id, 0 or 1.
+    pub recovery_id: u32,
+    /// The ASN.1 DER encoding.
+    pub der: Buffer,
+}
+
+/// Start signing the 32 byte `msg_hash` with the output of `presignStep3`.
+#[napi(js_name = "signStep1")]
+pub fn sign_step_1(config: String, msg_hash: Buffer, presign: Buffer) -> AsyncTask<StepTask> {
+    let (msg_hash, presign) = (msg_hash.to_vec(), presign.to_vec());
+    StepTask::new(move || Ok(server::sign_step_1(&config, &msg_hash, &presign)?.into()))
+}
+
+#[napi(js_name = "signStep2")]
+pub fn sign_step_2(
+    config: String,
+    state: Buffer,
+    msgs: Buffer,
+    presign: Buffer,
+) -> AsyncTask<SignTask> {
+    let (state, msgs, presign) = (state.to_vec(), msgs.to_vec(), presign.to_vec());
+    SignTask::new(move || {
+        let out = server::sign_step_2(&config, &state, &msgs, &presign)?;
+        let recoverable = out.recoverable();
+        Ok(Signature {
+            compact: out.sig.to_compact().into(),
+            recoverable: recoverable.to_bytes()?.into(),
+            recovery_id: recoverable.recovery_id.into(),
+            der: out.sig.to_der()?.into(),
+        })
+    })
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use cait_sith_keplr::tecdsa_cli_srv::bindings::server::{PrivateStepBytes, StepBytes};
+use cait_sith_keplr::tecdsa_cli_srv::bindings::BindingError;
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi::{Env, JsError, JsObject, Task};
+use napi_derive::napi;
+
+/// The output of a step: the state to pass to the next step, and the messages to send.
+#[napi(object)]
+pub struct StepOutput {
+    pub state: Buffer,
+    pub msgs: Buffer,
+}
+
+impl From<StepBytes> for StepOutput {
+    fn from(out: StepBytes) -> Self {
+        StepOutput {
+            state: out.state.into(),
+            msgs: out.msgs.into(),
+        }
+    }
+}
+
+/// The output of a step sending a different message to each participant.
+#[napi(object)]
+pub struct PrivateStepOutput {
+    pub state: Buffer,
+    /// The messages to send, keyed by recipient.
+    pub msgs: HashMap<String, Buffer>,
+}
+
+impl From<PrivateStepBytes> for PrivateStepOutput {
+    fn from(out: PrivateStepBytes) -> Self {
+        PrivateStepOutput {
+            state: out.state.into(),
+            msgs: out
+                .msgs
+                .into_iter()
+                .map(|(p, m)| (u32::from(p).to_string(), m.into()))
+                .collect(),
+        }
+    }
+}
+
+/// Turn a binding error into the JS error the promise rejects with.
+fn js_error(env: Env, e: BindingError) -> napi::Error {
+    let js_error =
+        JsError::from(napi::Error::new(e.kind().name(), e.to_string())).into_unknown(env);
+    // SAFETY: the value was just created as an error, which is an object.
+    let mut js_error: JsObject = unsafe { js_error.cast() };
+    if let Some(p) = e.culprit() {
+        let set = env
+            .create_uint32(u32::from(p))
+            .and_then(|p| js_error.set_named_property("participant", p));
+        if let Err(e) = set {
+            return e;
+        }
+    }
+    napi::Error::from(js_error.into_unknown())
+}
+
+/// Each task runs a step on the libuv thread pool, and resolves with its output.
+macro_rules! step_tasks {
+    ($($task:ident => $output:ty),* $(,)?) => {
+        $(
+            pub struct $task(Option<Box<dyn FnOnce() -> Result<$output, BindingError> + Send>>);
+
+            impl $task {
+                pub(crate) fn new(
+                    run: impl FnOnce() -> Result<$output, BindingError> + Send + 'static,
+                ) -> AsyncTask<Self> {
+                    AsyncTask::new($task(Some(Box::new(run))))
+                }
+            }
+
+            #[napi]
+            impl Task for $task {
+                type Output = Result<$output, BindingError>;
+                type JsValue = $output;
+
+                fn compute(&mut self) -> napi::Result<Self::Output> {
+                    let run = self.0.take().expect("task computed twice");
+                    Ok(run())
+                }
+
+                fn resolve(&mut self, env: Env, output: Self::Output) -> napi::Result<$output> {
+                    output.map_err(|e| js_error(env, e))
+                }
+            }
+        )*
+    };
+}
+
+step_tasks!(
+    StepTask => StepOutput,
+    PrivateStepTask => PrivateStepOutput,
+    OutputTask => Buffer,
+    SignTask => crate::Signature,
+);
@@ -0,0 +1,47 @@
+use cait_sith_keplr::tecdsa_cli_srv::bindings::server;
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi_derive::napi;
+
+use crate::{OutputTask, StepTask};
+
+/// Takes the messages of the client's `triplesStep1`, which `triplesStep2` needs.
+#[napi(js_name = "triplesStep1")]
+pub fn triples_step_1(config: String, msgs: Buffer) -> AsyncTask<StepTask> {
+    let msgs = msgs.to_vec();
+    StepTask::new(move || Ok(server::triples_step_1(&config, &msgs)?.into()))
+}
+
+/// The steps in between all take the state and the messages of the client's step of the same
+/// number, and return the same.
+macro_rules! impl_triples_steps {
+    ($($step:ident => $js_name:literal),* $(,)?) => {
+        $(
+            #[napi(js_name = $js_name)]
+            pub fn $step(config: String, state: Buffer, msgs: Buffer) -> AsyncTask<StepTask> {
+                let (state, msgs) = (state.to_vec(), msgs.to_vec());
+                StepTask::new(move || Ok(server::$step(&config, &state, &msgs)?.into()))
+            }
+        )*
+    };
+}
+
+impl_triples_steps!(
+    triples_step_2 => "triplesStep2",
+    triples_step_3 => "triplesStep3",
+    triples_step_4 => "triplesStep4",
+    triples_step_5 => "triplesStep5",
+    triples_step_6 => "triplesStep6",
+    triples_step_7 => "triplesStep7",
+    triples_step_8 => "triplesStep8",
+    triples_step_9 => "triplesStep9",
+    triples_step_10 => "triplesStep10",
+);
+
+/// Takes the messages of the client's `triplesStep10` again.
+///
+/// Resolves with the encoded `TriplesGenManyResult`, holding the two triples a presignature uses.
+#[napi(js_name = "triplesStep11")]
+pub fn triples_step_11(config: String, state: Buffer, msgs: Buffer) -> AsyncTask<OutputTask> {
+    let (state, msgs) = (state.to_vec(), msgs.to_vec());
+    OutputTask::new(move || Ok(server::triples_step_11(&config, &state, &msgs)?.into()))
+}